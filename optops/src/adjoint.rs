@@ -0,0 +1,692 @@
+//! Reverse-mode algorithmic differentiation: a scalar that records every
+//! operation on a tape, so one backward sweep over a generic pricer's
+//! arithmetic yields the derivative of its price in every input at once.
+
+use crate::error::{OptOpsError, Result};
+use crate::market::Market;
+use crate::units::{Rate, Spot, Vol};
+use core::cmp::Ordering;
+use core::num::FpCategory;
+use core::ops::{Add, Div, Mul, Neg, Rem, Sub};
+use num_traits::{Num, NumCast, One, ToPrimitive, Zero};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::marker::PhantomData;
+
+/// Index of a value that depends on no variable and is never recorded.
+const CONSTANT: usize = usize::MAX;
+
+thread_local! {
+    /// Operations recorded since this thread's `Tape` started; `None` while
+    /// no tape is live here. Each thread records alone, so pricers taking
+    /// gradients on different threads never wait on one another.
+    static NODES: RefCell<Option<Vec<Node>>> = const { RefCell::new(None) };
+}
+
+/// One recorded operation: the nodes it was computed from, each with the
+/// partial derivative of the result in it.
+#[derive(Debug, Clone, Copy)]
+struct Node {
+    parents: [(usize, f64); 2],
+}
+
+/// `f` of this thread's live tape.
+///
+/// # Panics
+///
+/// If no tape is live on this thread: a variable's arithmetic moved to
+/// another thread, where its tape cannot follow.
+fn with_nodes<R>(f: impl FnOnce(&mut Vec<Node>) -> R) -> R {
+    NODES.with_borrow_mut(|nodes| {
+        f(nodes
+            .as_mut()
+            .expect("adjoint variables are recorded only on the thread of their tape"))
+    })
+}
+
+/// Whether a tape is live on this thread, in which case the parallel
+/// pricers keep their work on it so every operation is recorded.
+#[cfg(feature = "rayon")]
+pub(crate) fn recording() -> bool {
+    NODES.with_borrow(Option::is_some)
+}
+
+/// An `f64` that remembers how it was computed from the variables of the
+/// live `Tape`. Every pricer generic over `Float`, the lattices and the
+/// Monte Carlo engines among them, runs on it unchanged; the price it
+/// returns is then differentiated by `Tape::gradient`.
+///
+/// Comparisons and branches see only the value, so the derivative is that
+/// of the branch taken: exact almost everywhere for the maxima of exercise
+/// decisions and payoffs, and zero across the jump of a digital or a
+/// barrier, which only a smoothed payoff or a likelihood ratio can see.
+#[derive(Debug, Clone, Copy)]
+pub struct Adjoint {
+    value: f64,
+    index: usize,
+}
+
+impl Adjoint {
+    /// `value` as a constant, whose derivative in everything is zero.
+    pub fn constant(value: f64) -> Self {
+        Adjoint {
+            value,
+            index: CONSTANT,
+        }
+    }
+
+    pub fn value(&self) -> f64 {
+        self.value
+    }
+
+    /// `value` computed from `self` with derivative `slope`.
+    fn unary(self, value: f64, slope: f64) -> Self {
+        if self.index == CONSTANT {
+            return Adjoint::constant(value);
+        }
+        Adjoint::record(value, [(self.index, slope), (CONSTANT, 0.0)])
+    }
+
+    /// `value` computed from `self` and `other` with partial derivatives
+    /// `slope` and `other_slope`.
+    fn binary(self, other: Self, value: f64, slope: f64, other_slope: f64) -> Self {
+        match (self.index, other.index) {
+            (CONSTANT, CONSTANT) => Adjoint::constant(value),
+            (_, CONSTANT) => self.unary(value, slope),
+            (CONSTANT, _) => other.unary(value, other_slope),
+            _ => Adjoint::record(value, [(self.index, slope), (other.index, other_slope)]),
+        }
+    }
+
+    fn record(value: f64, parents: [(usize, f64); 2]) -> Self {
+        let index = with_nodes(|nodes| {
+            nodes.push(Node { parents });
+            nodes.len() - 1
+        });
+        Adjoint { value, index }
+    }
+}
+
+/// A recording session on the current thread. Each thread holds at most
+/// one live tape, so gradients on different threads run side by side,
+/// and the crate's parallel pricers stay on the recording thread while it
+/// is live. Dropping the tape clears it; variables outlive it only as
+/// values, and neither they nor the tape may move to another thread.
+#[derive(Debug)]
+pub struct Tape {
+    _thread: PhantomData<*const ()>,
+}
+
+impl Tape {
+    /// An empty tape; an error if one is already live on this thread,
+    /// whose variables it would otherwise clobber.
+    pub fn new() -> Result<Self> {
+        NODES.with_borrow_mut(|nodes| match nodes {
+            Some(_) => Err(OptOpsError::Unsupported {
+                engine: "adjoint",
+                reason: "nested tapes on one thread",
+            }),
+            None => {
+                *nodes = Some(Vec::new());
+                Ok(Tape {
+                    _thread: PhantomData,
+                })
+            }
+        })
+    }
+
+    /// An input to differentiate in.
+    pub fn variable(&self, value: f64) -> Adjoint {
+        Adjoint::record(value, [(CONSTANT, 0.0); 2])
+    }
+
+    /// Operations recorded so far.
+    pub fn len(&self) -> usize {
+        with_nodes(|nodes| nodes.len())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Derivatives of `output` in every variable, from one sweep back over
+    /// the tape.
+    pub fn gradient(&self, output: Adjoint) -> Gradient {
+        with_nodes(|nodes| Tape::sweep(nodes, output))
+    }
+
+    fn sweep(nodes: &[Node], output: Adjoint) -> Gradient {
+        let mut adjoints = vec![0.0; nodes.len()];
+        if output.index == CONSTANT {
+            return Gradient { adjoints };
+        }
+        adjoints[output.index] = 1.0;
+        for i in (0..=output.index).rev() {
+            let adjoint = adjoints[i];
+            if adjoint == 0.0 {
+                continue;
+            }
+            for &(parent, slope) in &nodes[i].parents {
+                if parent != CONSTANT {
+                    adjoints[parent] += adjoint * slope;
+                }
+            }
+        }
+        Gradient { adjoints }
+    }
+
+    /// `market` with its spot, rate, vol and dividend yield as variables.
+    /// Only flat markets carry over: curves, surfaces, discrete dividends
+    /// and quantos are rejected.
+    pub fn market(&self, market: &Market) -> Result<Market<Adjoint>> {
+        let unsupported = |reason| OptOpsError::Unsupported {
+            engine: "adjoint",
+            reason,
+        };
+        if market.rate_curve.is_some() || market.vol_curve.is_some() {
+            return Err(unsupported("term structures"));
+        }
+        if market.vol_surface.is_some() {
+            return Err(unsupported("vol surfaces"));
+        }
+        if !market.dividends.is_empty() {
+            return Err(unsupported("discrete dividends"));
+        }
+        if market.quanto.is_some() {
+            return Err(unsupported("quantos"));
+        }
+        Ok(Market::new(
+            Spot(self.variable(market.spot)),
+            Rate(self.variable(market.rate)),
+            Vol(self.variable(market.vol)),
+        )
+        .with_dividend_yield(Rate(self.variable(market.dividend_yield)))
+        .with_compounding(market.compounding))
+    }
+}
+
+impl Drop for Tape {
+    fn drop(&mut self) {
+        NODES.set(None);
+    }
+}
+
+/// Derivatives of one output in every variable of its tape.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Gradient {
+    adjoints: Vec<f64>,
+}
+
+impl Gradient {
+    /// Derivative in `variable`; zero for constants and for variables the
+    /// output does not depend on.
+    pub fn wrt(&self, variable: Adjoint) -> f64 {
+        self.adjoints.get(variable.index).copied().unwrap_or(0.0)
+    }
+}
+
+/// A price with its first-order sensitivities to each flat market input,
+/// per unit of each.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct MarketGradient {
+    pub price: f64,
+    pub delta: f64,
+    pub vega: f64,
+    pub rho: f64,
+    /// Sensitivity to the dividend yield.
+    pub dividend_rho: f64,
+}
+
+/// `price` of `market` with delta, vega, rho and dividend rho, from one
+/// recording of `price` and one backward sweep, however many steps or
+/// paths it takes. `price` is any pricer generic over `Float`, such as a
+/// lattice's `price_only` or `MonteCarloEngine::estimate`, run on the
+/// market `Tape::market` makes; Monte Carlo derivatives are those of the
+/// estimate, sharing its sampling error. A Cox-Ross-Rubinstein lattice's
+/// derivatives carry its strike-placement error, which shrinks only as
+/// the root of the steps; Leisen-Reimer, centred on the strike, converges
+/// as fast in the Greeks as in the price.
+pub fn market_gradient<F>(market: &Market, price: F) -> Result<MarketGradient>
+where
+    F: FnOnce(&Market<Adjoint>) -> Result<Adjoint>,
+{
+    let tape = Tape::new()?;
+    let variables = tape.market(market)?;
+    let value = price(&variables)?;
+    let gradient = tape.gradient(value);
+    Ok(MarketGradient {
+        price: value.value,
+        delta: gradient.wrt(variables.spot),
+        vega: gradient.wrt(variables.vol),
+        rho: gradient.wrt(variables.rate),
+        dividend_rho: gradient.wrt(variables.dividend_yield),
+    })
+}
+
+impl PartialEq for Adjoint {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl PartialOrd for Adjoint {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.value.partial_cmp(&other.value)
+    }
+}
+
+impl Add for Adjoint {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        self.binary(other, self.value + other.value, 1.0, 1.0)
+    }
+}
+
+impl Sub for Adjoint {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        self.binary(other, self.value - other.value, 1.0, -1.0)
+    }
+}
+
+impl Mul for Adjoint {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        self.binary(other, self.value * other.value, other.value, self.value)
+    }
+}
+
+impl Div for Adjoint {
+    type Output = Self;
+
+    fn div(self, other: Self) -> Self {
+        let value = self.value / other.value;
+        self.binary(other, value, 1.0 / other.value, -value / other.value)
+    }
+}
+
+impl Rem for Adjoint {
+    type Output = Self;
+
+    fn rem(self, other: Self) -> Self {
+        let (a, b) = (self.value, other.value);
+        self.binary(other, a % b, 1.0, -(a / b).trunc())
+    }
+}
+
+impl Neg for Adjoint {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        self.unary(-self.value, -1.0)
+    }
+}
+
+impl Zero for Adjoint {
+    fn zero() -> Self {
+        Adjoint::constant(0.0)
+    }
+
+    fn is_zero(&self) -> bool {
+        self.value == 0.0
+    }
+}
+
+impl One for Adjoint {
+    fn one() -> Self {
+        Adjoint::constant(1.0)
+    }
+}
+
+impl Num for Adjoint {
+    type FromStrRadixErr = <f64 as Num>::FromStrRadixErr;
+
+    fn from_str_radix(text: &str, radix: u32) -> core::result::Result<Self, Self::FromStrRadixErr> {
+        f64::from_str_radix(text, radix).map(Adjoint::constant)
+    }
+}
+
+impl ToPrimitive for Adjoint {
+    fn to_i64(&self) -> Option<i64> {
+        self.value.to_i64()
+    }
+
+    fn to_u64(&self) -> Option<u64> {
+        self.value.to_u64()
+    }
+
+    fn to_f64(&self) -> Option<f64> {
+        Some(self.value)
+    }
+}
+
+impl NumCast for Adjoint {
+    fn from<N: ToPrimitive>(n: N) -> Option<Self> {
+        n.to_f64().map(Adjoint::constant)
+    }
+}
+
+impl num_traits::Float for Adjoint {
+    fn nan() -> Self {
+        Adjoint::constant(f64::NAN)
+    }
+
+    fn infinity() -> Self {
+        Adjoint::constant(f64::INFINITY)
+    }
+
+    fn neg_infinity() -> Self {
+        Adjoint::constant(f64::NEG_INFINITY)
+    }
+
+    fn neg_zero() -> Self {
+        Adjoint::constant(-0.0)
+    }
+
+    fn min_value() -> Self {
+        Adjoint::constant(f64::MIN)
+    }
+
+    fn min_positive_value() -> Self {
+        Adjoint::constant(f64::MIN_POSITIVE)
+    }
+
+    fn max_value() -> Self {
+        Adjoint::constant(f64::MAX)
+    }
+
+    fn epsilon() -> Self {
+        Adjoint::constant(f64::EPSILON)
+    }
+
+    fn is_nan(self) -> bool {
+        self.value.is_nan()
+    }
+
+    fn is_infinite(self) -> bool {
+        self.value.is_infinite()
+    }
+
+    fn is_finite(self) -> bool {
+        self.value.is_finite()
+    }
+
+    fn is_normal(self) -> bool {
+        self.value.is_normal()
+    }
+
+    fn classify(self) -> FpCategory {
+        self.value.classify()
+    }
+
+    fn floor(self) -> Self {
+        Adjoint::constant(self.value.floor())
+    }
+
+    fn ceil(self) -> Self {
+        Adjoint::constant(self.value.ceil())
+    }
+
+    fn round(self) -> Self {
+        Adjoint::constant(self.value.round())
+    }
+
+    fn trunc(self) -> Self {
+        Adjoint::constant(self.value.trunc())
+    }
+
+    fn fract(self) -> Self {
+        self.unary(self.value.fract(), 1.0)
+    }
+
+    fn abs(self) -> Self {
+        let sign = if self.value < 0.0 { -1.0 } else { 1.0 };
+        self.unary(self.value.abs(), sign)
+    }
+
+    fn signum(self) -> Self {
+        Adjoint::constant(self.value.signum())
+    }
+
+    fn is_sign_positive(self) -> bool {
+        self.value.is_sign_positive()
+    }
+
+    fn is_sign_negative(self) -> bool {
+        self.value.is_sign_negative()
+    }
+
+    fn mul_add(self, a: Self, b: Self) -> Self {
+        self * a + b
+    }
+
+    fn recip(self) -> Self {
+        let value = self.value.recip();
+        self.unary(value, -value * value)
+    }
+
+    fn powi(self, n: i32) -> Self {
+        let slope = if n == 0 {
+            0.0
+        } else {
+            n as f64 * self.value.powi(n - 1)
+        };
+        self.unary(self.value.powi(n), slope)
+    }
+
+    fn powf(self, n: Self) -> Self {
+        let value = self.value.powf(n.value);
+        let slope = if n.value == 0.0 {
+            0.0
+        } else {
+            n.value * self.value.powf(n.value - 1.0)
+        };
+        let exponent_slope = if self.value > 0.0 {
+            value * self.value.ln()
+        } else {
+            0.0
+        };
+        self.binary(n, value, slope, exponent_slope)
+    }
+
+    fn sqrt(self) -> Self {
+        let value = self.value.sqrt();
+        self.unary(value, 0.5 / value)
+    }
+
+    fn exp(self) -> Self {
+        let value = self.value.exp();
+        self.unary(value, value)
+    }
+
+    fn exp2(self) -> Self {
+        let value = self.value.exp2();
+        self.unary(value, value * core::f64::consts::LN_2)
+    }
+
+    fn ln(self) -> Self {
+        self.unary(self.value.ln(), 1.0 / self.value)
+    }
+
+    fn log(self, base: Self) -> Self {
+        self.ln() / base.ln()
+    }
+
+    fn log2(self) -> Self {
+        self.unary(
+            self.value.log2(),
+            1.0 / (self.value * core::f64::consts::LN_2),
+        )
+    }
+
+    fn log10(self) -> Self {
+        self.unary(
+            self.value.log10(),
+            1.0 / (self.value * core::f64::consts::LN_10),
+        )
+    }
+
+    /// The larger operand itself, so the derivative follows it; NaN loses,
+    /// as it does for `f64`.
+    fn max(self, other: Self) -> Self {
+        if self.value.is_nan() || other.value > self.value {
+            other
+        } else {
+            self
+        }
+    }
+
+    fn min(self, other: Self) -> Self {
+        if self.value.is_nan() || other.value < self.value {
+            other
+        } else {
+            self
+        }
+    }
+
+    #[allow(deprecated)]
+    fn abs_sub(self, other: Self) -> Self {
+        (self - other).max(Adjoint::zero())
+    }
+
+    fn cbrt(self) -> Self {
+        let value = self.value.cbrt();
+        self.unary(value, 1.0 / (3.0 * value * value))
+    }
+
+    fn hypot(self, other: Self) -> Self {
+        let value = self.value.hypot(other.value);
+        self.binary(other, value, self.value / value, other.value / value)
+    }
+
+    fn sin(self) -> Self {
+        self.unary(self.value.sin(), self.value.cos())
+    }
+
+    fn cos(self) -> Self {
+        self.unary(self.value.cos(), -self.value.sin())
+    }
+
+    fn tan(self) -> Self {
+        let cos = self.value.cos();
+        self.unary(self.value.tan(), 1.0 / (cos * cos))
+    }
+
+    fn asin(self) -> Self {
+        self.unary(
+            self.value.asin(),
+            1.0 / (1.0 - self.value * self.value).sqrt(),
+        )
+    }
+
+    fn acos(self) -> Self {
+        self.unary(
+            self.value.acos(),
+            -1.0 / (1.0 - self.value * self.value).sqrt(),
+        )
+    }
+
+    fn atan(self) -> Self {
+        self.unary(self.value.atan(), 1.0 / (1.0 + self.value * self.value))
+    }
+
+    fn atan2(self, other: Self) -> Self {
+        let (y, x) = (self.value, other.value);
+        let squared = x * x + y * y;
+        self.binary(other, y.atan2(x), x / squared, -y / squared)
+    }
+
+    fn sin_cos(self) -> (Self, Self) {
+        (self.sin(), self.cos())
+    }
+
+    fn exp_m1(self) -> Self {
+        self.unary(self.value.exp_m1(), self.value.exp())
+    }
+
+    fn ln_1p(self) -> Self {
+        self.unary(self.value.ln_1p(), 1.0 / (1.0 + self.value))
+    }
+
+    fn sinh(self) -> Self {
+        self.unary(self.value.sinh(), self.value.cosh())
+    }
+
+    fn cosh(self) -> Self {
+        self.unary(self.value.cosh(), self.value.sinh())
+    }
+
+    fn tanh(self) -> Self {
+        let value = self.value.tanh();
+        self.unary(value, 1.0 - value * value)
+    }
+
+    fn asinh(self) -> Self {
+        self.unary(
+            self.value.asinh(),
+            1.0 / (self.value * self.value + 1.0).sqrt(),
+        )
+    }
+
+    fn acosh(self) -> Self {
+        self.unary(
+            self.value.acosh(),
+            1.0 / (self.value * self.value - 1.0).sqrt(),
+        )
+    }
+
+    fn atanh(self) -> Self {
+        self.unary(self.value.atanh(), 1.0 / (1.0 - self.value * self.value))
+    }
+
+    fn integer_decode(self) -> (u64, i16, i8) {
+        num_traits::Float::integer_decode(self.value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A second tape on the same thread is refused rather than waited for.
+    #[test]
+    fn nested_tapes_are_rejected() {
+        let tape = Tape::new().unwrap();
+        assert!(Tape::new().is_err());
+        let market: Market = Market::new(Spot(100.0), Rate(0.05), Vol(0.2));
+        assert!(market_gradient(&market, |m| Ok(m.spot)).is_err());
+        drop(tape);
+        assert!(Tape::new().is_ok());
+    }
+
+    /// Tapes on different threads record independently.
+    #[test]
+    fn threads_record_on_their_own_tapes() {
+        let gradients: Vec<(f64, f64)> = std::thread::scope(|scope| {
+            let handles: Vec<_> = [1.0, 2.0, 3.0, 4.0]
+                .into_iter()
+                .map(|k: f64| {
+                    scope.spawn(move || {
+                        let tape = Tape::new().unwrap();
+                        let x = tape.variable(k);
+                        let y = tape.variable(2.0);
+                        let mut z = x * y;
+                        for _ in 0..10_000 {
+                            z = z + x * Adjoint::constant(1e-4);
+                        }
+                        let gradient = tape.gradient(z);
+                        (gradient.wrt(x), gradient.wrt(y))
+                    })
+                })
+                .collect();
+            handles.into_iter().map(|h| h.join().unwrap()).collect()
+        });
+        for (k, (dx, dy)) in [1.0, 2.0, 3.0, 4.0].into_iter().zip(gradients) {
+            assert!((dx - 3.0).abs() < 1e-9, "{dx}");
+            assert!((dy - k).abs() < 1e-12, "{dy}");
+        }
+    }
+}
@@ -0,0 +1,868 @@
+//! Closed-form approximations to American option prices, orders of
+//! magnitude cheaper than a lattice.
+
+use crate::analytic::{black_scholes_price, d1_d2, norm_cdf, norm_pdf, ImpliedVolSolver};
+use crate::core;
+use crate::engine::{PriceResult, PricingEngine, PricingResult};
+use crate::error::{Domain, OptOpsError, Result};
+use crate::float::{cast, to_f64, Float};
+use crate::instrument::{ExerciseStyle, OptionSpec, OptionType};
+use crate::market::Market;
+use crate::validate;
+use serde::{Deserialize, Serialize};
+use std::time::Instant;
+
+/// Newton iterations allowed when solving for a critical spot.
+const MAX_ITERATIONS: usize = 100;
+
+/// Inputs shared by the approximations: a continuous rate and yield and a
+/// single vol to expiry.
+struct Inputs<T> {
+    spot: T,
+    strike: T,
+    rate: T,
+    dividend_yield: T,
+    vol: T,
+    expiry: T,
+}
+
+impl<T: Float> Inputs<T> {
+    /// Rejects what the approximations cannot represent: barriers and
+    /// discrete dividends.
+    fn new(engine: &'static str, spec: &OptionSpec<T>, market: &Market<T>) -> Result<Self> {
+        if spec.barrier.is_some() {
+            return Err(OptOpsError::Unsupported {
+                engine,
+                reason: "barrier options",
+            });
+        }
+        if !market.dividends.is_empty() {
+            return Err(OptOpsError::Unsupported {
+                engine,
+                reason: "discrete dividends",
+            });
+        }
+        market.validate()?;
+        let expiry = validate::positive("expiry", spec.expiry)?;
+        let strike = validate::positive("strike", spec.strike)?;
+        let (rate, vol) = (
+            market.continuous_rate(expiry)?,
+            market.vol_for(strike, expiry)?,
+        );
+        Ok(Inputs {
+            spot: market.spot,
+            strike,
+            rate,
+            dividend_yield: market.carry_yield(rate, vol),
+            vol,
+            expiry,
+        })
+    }
+
+    fn european(&self, option_type: OptionType, spot: T) -> T {
+        let Inputs {
+            strike,
+            rate,
+            dividend_yield,
+            vol,
+            expiry,
+            ..
+        } = *self;
+        core::black_scholes(option_type, spot, strike, rate, dividend_yield, vol, expiry)
+    }
+}
+
+/// Critical spot `S*` of the quadratic approximation and its exponent `q`:
+/// early exercise is worth `A (S / S*)^q` beyond `S*`, and `S*` is where
+/// that value pastes smoothly onto the intrinsic, found by Newton's method.
+fn quadratic_boundary<T: Float>(
+    engine: &'static str,
+    inputs: &Inputs<T>,
+    option_type: OptionType,
+) -> Result<(T, T)> {
+    let Inputs {
+        strike,
+        rate,
+        dividend_yield,
+        vol,
+        expiry,
+        ..
+    } = *inputs;
+    let (one, two) = (T::one(), cast::<T>(2.0));
+    let carry = rate - dividend_yield;
+    let variance = vol * vol;
+    let sigma_sqrt = vol * expiry.sqrt();
+    let m = two * rate / variance;
+    let n = two * carry / variance;
+    let carry_discount = ((carry - rate) * expiry).exp();
+    // Half the root of the discriminant of the quadratic in `q`.
+    let half_root = |ratio: T| ((n - one) * (n - one) + cast::<T>(4.0) * ratio).sqrt() / two;
+    let centre = -(n - one) / two;
+    let d1 = |s: T| d1_d2(s, strike, rate, dividend_yield, vol, expiry).0;
+    let tolerance = cast::<T>(1e-8) * strike;
+    let non_convergence = OptOpsError::NonConvergence {
+        solver: engine,
+        iterations: MAX_ITERATIONS,
+    };
+
+    match option_type {
+        OptionType::Call => {
+            let q2 = centre + half_root(m_over_k(inputs));
+            // Seed from the perpetual boundary (Barone-Adesi and Whaley).
+            let q2_inf = centre + half_root(m);
+            let s_inf = strike / (one - one / q2_inf);
+            let h2 = -(carry * expiry + two * sigma_sqrt) * strike / (s_inf - strike);
+            let mut critical = strike + (s_inf - strike) * (one - h2.exp());
+            for _ in 0..MAX_ITERATIONS {
+                let delta = carry_discount * norm_cdf(d1(critical));
+                let rhs =
+                    inputs.european(OptionType::Call, critical) + (one - delta) * critical / q2;
+                if (critical - strike - rhs).abs() < tolerance {
+                    return Ok((q2, critical));
+                }
+                let slope = delta * (one - one / q2)
+                    + (one - carry_discount * norm_pdf(d1(critical)) / sigma_sqrt) / q2;
+                critical = (strike + rhs - slope * critical) / (one - slope);
+            }
+            Err(non_convergence)
+        }
+        OptionType::Put => {
+            let q1 = centre - half_root(m_over_k(inputs));
+            let q1_inf = centre - half_root(m);
+            let s_inf = strike / (one - one / q1_inf);
+            let h1 = (carry * expiry - two * sigma_sqrt) * strike / (strike - s_inf);
+            let mut critical = s_inf + (strike - s_inf) * h1.exp();
+            for _ in 0..MAX_ITERATIONS {
+                let delta = carry_discount * norm_cdf(-d1(critical));
+                let rhs =
+                    inputs.european(OptionType::Put, critical) - (one - delta) * critical / q1;
+                if (strike - critical - rhs).abs() < tolerance {
+                    return Ok((q1, critical));
+                }
+                let slope = -delta * (one - one / q1)
+                    - (one + carry_discount * norm_pdf(-d1(critical)) / sigma_sqrt) / q1;
+                critical = (strike - rhs + slope * critical) / (one + slope);
+            }
+            Err(non_convergence)
+        }
+    }
+}
+
+/// `M / K = 2r / (vol^2 (1 - exp(-rT)))`, whose limit as the rate vanishes
+/// is `2 / (vol^2 T)`.
+fn m_over_k<T: Float>(inputs: &Inputs<T>) -> T {
+    let Inputs {
+        rate, vol, expiry, ..
+    } = *inputs;
+    let two = cast::<T>(2.0);
+    if rate == T::zero() {
+        two / (vol * vol * expiry)
+    } else {
+        two * rate / (vol * vol) / (T::one() - (-rate * expiry).exp())
+    }
+}
+
+/// Whether `spec` can be worth exercising before expiry; calls on assets
+/// without a positive yield never are.
+fn exercisable<T: Float>(spec: &OptionSpec<T>, inputs: &Inputs<T>) -> bool {
+    let call_without_yield =
+        spec.option_type == OptionType::Call && inputs.dividend_yield <= T::zero();
+    spec.exercise == ExerciseStyle::American && !call_without_yield
+}
+
+/// Whether `spot` lies in the exercise region beyond `critical`.
+fn beyond<T: Float>(option_type: OptionType, spot: T, critical: T) -> bool {
+    match option_type {
+        OptionType::Call => spot >= critical,
+        OptionType::Put => spot <= critical,
+    }
+}
+
+/// Barone-Adesi-Whaley (1987) quadratic approximation to the American
+/// price of `spec`: the European value plus an early-exercise premium
+/// `A (S / S*)^q` above (calls) or below (puts) a critical spot `S*` found
+/// by Newton's method. European contracts get their Black-Scholes value;
+/// calls on assets without a positive yield are never exercised early.
+pub fn barone_adesi_whaley<T: Float>(spec: &OptionSpec<T>, market: &Market<T>) -> Result<T> {
+    let engine = "Barone-Adesi-Whaley";
+    let inputs = Inputs::new(engine, spec, market)?;
+    let european = inputs.european(spec.option_type, inputs.spot);
+    if !exercisable(spec, &inputs) {
+        return Ok(european);
+    }
+    let (exponent, critical) = quadratic_boundary(engine, &inputs, spec.option_type)?;
+    let intrinsic = spec.option_type.intrinsic(inputs.spot, inputs.strike);
+    if beyond(spec.option_type, inputs.spot, critical) {
+        return Ok(intrinsic);
+    }
+    // Smooth pasting makes `A` the premium at the critical spot.
+    let premium = spec.option_type.intrinsic(critical, inputs.strike)
+        - inputs.european(spec.option_type, critical);
+    Ok(european + premium * (inputs.spot / critical).powf(exponent))
+}
+
+/// Ju-Zhong (1999) refinement of Barone-Adesi-Whaley: the quadratic
+/// premium divided by `1 - chi(S)`, where `chi = b ln(S/S*)^2 + c ln(S/S*)`
+/// restores the time decay of the premium that the quadratic drops. The
+/// critical spot is Barone-Adesi-Whaley's; the correction matters most for
+/// long-dated puts.
+pub fn ju_zhong<T: Float>(spec: &OptionSpec<T>, market: &Market<T>) -> Result<T> {
+    let engine = "Ju-Zhong";
+    let inputs = Inputs::new(engine, spec, market)?;
+    let european = inputs.european(spec.option_type, inputs.spot);
+    if !exercisable(spec, &inputs) {
+        return Ok(european);
+    }
+    let (lambda, critical) = quadratic_boundary(engine, &inputs, spec.option_type)?;
+    let Inputs {
+        spot,
+        strike,
+        rate,
+        dividend_yield,
+        vol,
+        expiry,
+    } = inputs;
+    if beyond(spec.option_type, spot, critical) {
+        return Ok(spec.option_type.intrinsic(spot, strike));
+    }
+    let (one, two) = (T::one(), cast::<T>(2.0));
+    let variance = vol * vol;
+    let sign = match spec.option_type {
+        OptionType::Call => one,
+        OptionType::Put => -one,
+    };
+    // In `h = 1 - exp(-rT)`, with `alpha = 2r / vol^2` and
+    // `beta = 2(r - q) / vol^2`; `alpha / h` stays finite at a zero rate.
+    let discounted = (-rate * expiry).exp();
+    let h = one - discounted;
+    let alpha_over_h = m_over_k(&inputs);
+    let beta = two * (rate - dividend_yield) / variance;
+    let root = ((beta - one) * (beta - one) + cast::<T>(4.0) * alpha_over_h).sqrt();
+    // `alpha dlambda/dh`.
+    let alpha_slope = -sign * alpha_over_h * alpha_over_h / root;
+    let denominator = two * lambda + beta - one;
+
+    let premium =
+        spec.option_type.intrinsic(critical, strike) - inputs.european(spec.option_type, critical);
+    // Sensitivity of the European value at `S*` to the time to expiry.
+    let (d1, d2) = d1_d2(critical, strike, rate, dividend_yield, vol, expiry);
+    let carried = (-dividend_yield * expiry).exp();
+    let aging = critical * carried * norm_pdf(d1) * vol / (two * expiry.sqrt())
+        + sign * rate * strike * discounted * norm_cdf(sign * d2)
+        - sign * dividend_yield * critical * carried * norm_cdf(sign * d1);
+    let b = (one - h) * alpha_slope / (two * denominator);
+    let c = -(one - h) / denominator
+        * (two / variance * aging / (discounted * premium)
+            + alpha_over_h
+            + alpha_slope / denominator);
+    let log = (spot / critical).ln();
+    let chi = b * log * log + c * log;
+    Ok(european + premium * (spot / critical).powf(lambda) / (one - chi))
+}
+
+/// Result for an approximation's `price`, with the early-exercise premium
+/// over Black-Scholes.
+fn priced(
+    engine: &'static str,
+    price: f64,
+    start: Instant,
+    instrument: &OptionSpec,
+    market: &Market,
+) -> PriceResult {
+    let european = black_scholes_price(instrument, market)?;
+    let mut result = PricingResult::new(engine, price, start.elapsed());
+    result.early_exercise_premium = Some(price - european);
+    Ok(result)
+}
+
+/// Barone-Adesi-Whaley engine: American prices without a lattice, for
+/// implied-vol and chain workflows where speed matters more than the last
+/// cent.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BaroneAdesiWhaleyEngine;
+
+impl PricingEngine for BaroneAdesiWhaleyEngine {
+    fn price(&self, instrument: &OptionSpec, market: &Market) -> PriceResult {
+        let start = Instant::now();
+        let price = barone_adesi_whaley(instrument, market)?;
+        priced("Barone-Adesi-Whaley", price, start, instrument, market)
+    }
+}
+
+/// Ju-Zhong engine: Barone-Adesi-Whaley's cost with much of its long-dated
+/// error removed.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct JuZhongEngine;
+
+impl PricingEngine for JuZhongEngine {
+    fn price(&self, instrument: &OptionSpec, market: &Market) -> PriceResult {
+        let start = Instant::now();
+        let price = ju_zhong(instrument, market)?;
+        priced("Ju-Zhong", price, start, instrument, market)
+    }
+}
+
+/// Flat-boundary building blocks of Bjerksund and Stensland, for a call
+/// under rate `rate`, cost of carry `carry` and vol `vol`.
+struct FlatBoundary<T> {
+    rate: T,
+    carry: T,
+    vol: T,
+}
+
+impl<T: Float> FlatBoundary<T> {
+    /// Drift of `ln S` under the measure weighting by `S^gamma`.
+    fn drift(&self, gamma: T) -> T {
+        self.carry + (gamma - cast(0.5)) * self.vol * self.vol
+    }
+
+    /// Growth rate of `E[S^gamma]`, net of discounting.
+    fn lambda(&self, gamma: T) -> T {
+        -self.rate
+            + gamma * self.carry
+            + cast::<T>(0.5) * gamma * (gamma - T::one()) * self.vol * self.vol
+    }
+
+    fn kappa(&self, gamma: T) -> T {
+        cast::<T>(2.0) * self.carry / (self.vol * self.vol) + cast::<T>(2.0) * gamma - T::one()
+    }
+
+    /// Value of `S^gamma` paid at `expiry` if the spot ends at or below
+    /// `strike` without having touched the flat barrier `barrier`.
+    fn phi(&self, spot: T, expiry: T, gamma: T, strike: T, barrier: T) -> T {
+        let sigma_sqrt = self.vol * expiry.sqrt();
+        let d = -((spot / strike).ln() + self.drift(gamma) * expiry) / sigma_sqrt;
+        let reflection = cast::<T>(2.0) * (barrier / spot).ln() / sigma_sqrt;
+        (self.lambda(gamma) * expiry).exp()
+            * spot.powf(gamma)
+            * (norm_cdf(d) - (barrier / spot).powf(self.kappa(gamma)) * norm_cdf(d - reflection))
+    }
+
+    /// `phi` over two stages: barrier `barrier` (`second` hereafter) until
+    /// `t1`, then `first` until `expiry`.
+    #[allow(clippy::too_many_arguments)]
+    fn psi(&self, spot: T, expiry: T, gamma: T, strike: T, second: T, first: T, t1: T) -> T {
+        let (drift, kappa) = (self.drift(gamma), self.kappa(gamma));
+        let (root_t1, root_t) = (self.vol * t1.sqrt(), self.vol * expiry.sqrt());
+        let e = |x: T, sign: T| (x.ln() + sign * drift * t1) / root_t1;
+        let f = |x: T| (x.ln() + drift * expiry) / root_t;
+        let (up, down) = (T::one(), -T::one());
+        let e1 = e(spot / first, up);
+        let e2 = e(second * second / (spot * first), up);
+        let e3 = e(spot / first, down);
+        let e4 = e(second * second / (spot * first), down);
+        let f1 = f(spot / strike);
+        let f2 = f(second * second / (spot * strike));
+        let f3 = f(first * first / (spot * strike));
+        let f4 = f(spot * first * first / (strike * second * second));
+        let rho = (t1 / expiry).sqrt();
+        let m = core::bivariate_norm_cdf;
+        (self.lambda(gamma) * expiry).exp()
+            * spot.powf(gamma)
+            * (m(-e1, -f1, rho)
+                - (second / spot).powf(kappa) * m(-e2, -f2, rho)
+                - (first / spot).powf(kappa) * m(-e3, -f3, -rho)
+                + (first / second).powf(kappa) * m(-e4, -f4, -rho))
+    }
+
+    /// Exercise boundary `B0 + (Binf - B0)(1 - e^h)` for `tau` of time left,
+    /// and the value `alpha` of holding at it per unit of `S^beta`.
+    fn boundary(&self, strike: T, beta: T, tau: T) -> (T, T) {
+        let one = T::one();
+        let b_inf = beta / (beta - one) * strike;
+        let b_0 = strike.max(self.rate / (self.rate - self.carry) * strike);
+        let h = -(self.carry * tau + cast::<T>(2.0) * self.vol * tau.sqrt()) * strike * strike
+            / ((b_inf - b_0) * b_0);
+        let trigger = b_0 + (b_inf - b_0) * (one - h.exp());
+        (trigger, (trigger - strike) * trigger.powf(-beta))
+    }
+
+    /// American call when early exercise can pay, holding until the spot
+    /// first reaches one flat trigger or, with `two_stage`, one trigger
+    /// until `t1 = (sqrt 5 - 1) T / 2` and a lower one thereafter.
+    fn call(&self, spot: T, strike: T, expiry: T, two_stage: bool) -> T {
+        let (half, variance) = (cast::<T>(0.5), self.vol * self.vol);
+        let skew = self.carry / variance - half;
+        let beta = -skew + (skew * skew + cast::<T>(2.0) * self.rate / variance).sqrt();
+        let (zero, one) = (T::zero(), T::one());
+        if !two_stage {
+            // The 1993 trigger uses `B0` rather than `K^2 / B0` in `h`.
+            let b_inf = beta / (beta - one) * strike;
+            let b_0 = strike.max(self.rate / (self.rate - self.carry) * strike);
+            let h = -(self.carry * expiry + cast::<T>(2.0) * self.vol * expiry.sqrt()) * b_0
+                / (b_inf - b_0);
+            let trigger = b_0 + (b_inf - b_0) * (one - h.exp());
+            if spot >= trigger {
+                return spot - strike;
+            }
+            let alpha = (trigger - strike) * trigger.powf(-beta);
+            return alpha * spot.powf(beta)
+                - alpha * self.phi(spot, expiry, beta, trigger, trigger)
+                + self.phi(spot, expiry, one, trigger, trigger)
+                - self.phi(spot, expiry, one, strike, trigger)
+                - strike * self.phi(spot, expiry, zero, trigger, trigger)
+                + strike * self.phi(spot, expiry, zero, strike, trigger);
+        }
+
+        let t1 = half * (cast::<T>(5.0).sqrt() - one) * expiry;
+        let (first, alpha1) = self.boundary(strike, beta, t1);
+        let (second, alpha2) = self.boundary(strike, beta, expiry);
+        if spot >= second {
+            return spot - strike;
+        }
+        alpha2 * spot.powf(beta) - alpha2 * self.phi(spot, t1, beta, second, second)
+            + self.phi(spot, t1, one, second, second)
+            - self.phi(spot, t1, one, first, second)
+            - strike * self.phi(spot, t1, zero, second, second)
+            + strike * self.phi(spot, t1, zero, first, second)
+            + alpha1 * self.phi(spot, t1, beta, first, second)
+            - alpha1 * self.psi(spot, expiry, beta, first, second, first, t1)
+            + self.psi(spot, expiry, one, first, second, first, t1)
+            - self.psi(spot, expiry, one, strike, second, first, t1)
+            - strike * self.psi(spot, expiry, zero, first, second, first, t1)
+            + strike * self.psi(spot, expiry, zero, strike, second, first, t1)
+    }
+}
+
+/// Bjerksund-Stensland (2002) approximation to the American price of
+/// `spec`: the value of exercising the first time the spot reaches a flat
+/// trigger, with one trigger over the first `(sqrt 5 - 1) / 2` of the life
+/// and a lower one over the rest. It is a lower bound on the true price.
+/// Puts are priced as calls through the put-call transformation
+/// `P(S, K, r, q) = C(K, S, q, r)`; contracts that would never be exercised
+/// early get their Black-Scholes value.
+pub fn bjerksund_stensland<T: Float>(spec: &OptionSpec<T>, market: &Market<T>) -> Result<T> {
+    flat_boundary("Bjerksund-Stensland", spec, market, true)
+}
+
+/// The single-trigger Bjerksund-Stensland (1993) approximation, slightly
+/// cheaper and less accurate than `bjerksund_stensland`.
+pub fn bjerksund_stensland_1993<T: Float>(spec: &OptionSpec<T>, market: &Market<T>) -> Result<T> {
+    flat_boundary("Bjerksund-Stensland", spec, market, false)
+}
+
+fn flat_boundary<T: Float>(
+    engine: &'static str,
+    spec: &OptionSpec<T>,
+    market: &Market<T>,
+    two_stage: bool,
+) -> Result<T> {
+    let inputs = Inputs::new(engine, spec, market)?;
+    let european = inputs.european(spec.option_type, inputs.spot);
+    let Inputs {
+        spot,
+        strike,
+        rate,
+        dividend_yield,
+        vol,
+        expiry,
+    } = inputs;
+    // Calls are held to expiry unless the asset yields more than cash, and
+    // puts, through the transformation, unless cash earns interest.
+    let (spot, strike, rate, carry) = match spec.option_type {
+        OptionType::Call => (spot, strike, rate, rate - dividend_yield),
+        OptionType::Put => (strike, spot, dividend_yield, dividend_yield - rate),
+    };
+    if spec.exercise == ExerciseStyle::European || carry >= rate {
+        return Ok(european);
+    }
+    let american = FlatBoundary { rate, carry, vol }.call(spot, strike, expiry, two_stage);
+    Ok(american.max(european))
+}
+
+/// Bjerksund-Stensland engine: a fast lower bound on the American price.
+/// Its error against a converged lattice is small and smooth in the inputs,
+/// which makes it a good control variate for the tree.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct BjerksundStenslandEngine {
+    /// Use the single flat trigger of the 1993 paper.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub single_stage: bool,
+}
+
+impl PricingEngine for BjerksundStenslandEngine {
+    fn price(&self, instrument: &OptionSpec, market: &Market) -> PriceResult {
+        let start = Instant::now();
+        let price = if self.single_stage {
+            bjerksund_stensland_1993(instrument, market)?
+        } else {
+            bjerksund_stensland(instrument, market)?
+        };
+        priced("Bjerksund-Stensland", price, start, instrument, market)
+    }
+}
+
+/// Exact value of a perpetual American option and the spot at which it is
+/// optimally exercised.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Perpetual<T = f64> {
+    pub price: T,
+    /// Exercise as soon as the spot reaches this level: from below for
+    /// calls, from above for puts. Infinite for a call on an asset without
+    /// a yield, which is never exercised, and zero for a put at a zero rate.
+    pub threshold: T,
+}
+
+/// Closed-form perpetual American call or put struck at `strike` (Merton,
+/// 1973): `(K / (y - 1)) ((y - 1) S / (y K))^y` on the holding side of the
+/// threshold `S* = y K / (y - 1)`, with `y` the root of
+/// `vol^2 y (y - 1) / 2 + (r - q) y - r = 0` above one for calls and
+/// below zero for puts. It is the infinite-maturity limit of the American
+/// price and of the exercise boundary, so it bounds both at any expiry.
+///
+/// Curves have no perpetual limit and are rejected; a simply compounded
+/// rate is read as its one-year equivalent. Calls on assets with a negative
+/// yield and puts at a negative rate are worth holding without bound and
+/// are rejected too.
+pub fn perpetual<T: Float>(
+    option_type: OptionType,
+    strike: T,
+    market: &Market<T>,
+) -> Result<Perpetual<T>> {
+    let engine = "perpetual";
+    market.validate()?;
+    let strike = validate::positive("strike", strike)?;
+    let curved = market.rate_curve.is_some() || market.vol_curve.is_some();
+    if curved || market.vol_surface.is_some() {
+        return Err(OptOpsError::Unsupported {
+            engine,
+            reason: "rate or vol curves and vol surfaces",
+        });
+    }
+    if !market.dividends.is_empty() {
+        return Err(OptOpsError::Unsupported {
+            engine,
+            reason: "discrete dividends",
+        });
+    }
+    let (one, half) = (T::one(), cast::<T>(0.5));
+    let rate = market.continuous_rate(one)?;
+    let (spot, vol) = (market.spot, market.vol);
+    let dividend_yield = market.carry_yield(rate, vol);
+    let variance = vol * vol;
+    let skew = (rate - dividend_yield) / variance - half;
+    let root = (skew * skew + cast::<T>(2.0) * rate / variance).sqrt();
+
+    match option_type {
+        OptionType::Call => {
+            validate::check("dividend_yield", dividend_yield, Domain::NonNegative)?;
+            if dividend_yield == T::zero() {
+                return Ok(Perpetual {
+                    price: spot,
+                    threshold: T::infinity(),
+                });
+            }
+            let y = -skew + root;
+            let threshold = y * strike / (y - one);
+            let price = if spot >= threshold {
+                spot - strike
+            } else {
+                strike / (y - one) * (spot / threshold).powf(y)
+            };
+            Ok(Perpetual { price, threshold })
+        }
+        OptionType::Put => {
+            validate::check("rate", rate, Domain::NonNegative)?;
+            if rate == T::zero() {
+                return Ok(Perpetual {
+                    price: strike,
+                    threshold: T::zero(),
+                });
+            }
+            let y = -skew - root;
+            let threshold = y * strike / (y - one);
+            let price = if spot <= threshold {
+                strike - spot
+            } else {
+                strike / (one - y) * (spot / threshold).powf(y)
+            };
+            Ok(Perpetual { price, threshold })
+        }
+    }
+}
+
+/// Choice among the closed-form approximations, to trade speed against
+/// accuracy explicitly. All three are a small fraction of a tree's cost.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AmericanApprox {
+    /// The quadratic approximation: the fastest, but it overprices
+    /// long-dated contracts by up to a few percent.
+    BaroneAdesiWhaley,
+    /// The two-stage flat boundary: a lower bound, steady at long
+    /// maturities, at the cost of bivariate normal evaluations.
+    BjerksundStensland,
+    /// The quadratic approximation with a time-decay correction: about as
+    /// fast, and usually the most accurate of the three.
+    JuZhong,
+}
+
+impl AmericanApprox {
+    pub fn price<T: Float>(self, spec: &OptionSpec<T>, market: &Market<T>) -> Result<T> {
+        match self {
+            AmericanApprox::BaroneAdesiWhaley => barone_adesi_whaley(spec, market),
+            AmericanApprox::BjerksundStensland => bjerksund_stensland(spec, market),
+            AmericanApprox::JuZhong => ju_zhong(spec, market),
+        }
+    }
+
+    /// Flat vol at which this approximation prices `spec` at `price`,
+    /// replacing the market's vol, vol curve or surface. Listed equity
+    /// options are American, and inverting Black-Scholes on them
+    /// overstates the vol wherever early exercise is worth something.
+    /// Prices below the value of exercising now, or at least the spot
+    /// (calls) or strike (puts), are rejected as out of range, and one at
+    /// the exercise value implies zero vol.
+    pub fn implied_vol<T: Float>(
+        self,
+        price: T,
+        spec: &OptionSpec<T>,
+        market: &Market<T>,
+    ) -> Result<T> {
+        if market.quanto.is_some() {
+            return Err(OptOpsError::Unsupported {
+                engine: "American implied vol",
+                reason: "quanto prices, which need not rise with vol",
+            });
+        }
+        market.validate()?;
+        let strike = validate::positive("strike", spec.strike)?;
+        let price = check_american_price(spec.option_type, market.spot, strike, price)?;
+        if price == spec.option_type.intrinsic(market.spot, strike) {
+            return Ok(T::zero());
+        }
+        let mut flat = Market {
+            vol_curve: None,
+            vol_surface: None,
+            ..market.clone()
+        };
+        ImpliedVolSolver::default().invert(price, market.vol, |vol| {
+            flat.vol = vol;
+            self.price(spec, &flat)
+        })
+    }
+}
+
+/// Checks that `price` is no less than the value of exercising now and
+/// below the spot (calls) or strike (puts), the range an American price
+/// must lie in whatever the vol, and returns it.
+pub(crate) fn check_american_price<T: Float>(
+    option_type: OptionType,
+    spot: T,
+    strike: T,
+    price: T,
+) -> Result<T> {
+    let upper = match option_type {
+        OptionType::Call => spot,
+        OptionType::Put => strike,
+    };
+    let price = validate::finite("price", price)?;
+    let floor = option_type.intrinsic(spot, strike);
+    if price < floor || price >= upper {
+        return Err(OptOpsError::InvalidParameter {
+            name: "price",
+            value: to_f64(price),
+            domain: Domain::Between(to_f64(floor), to_f64(upper)),
+        });
+    }
+    Ok(price)
+}
+
+impl PricingEngine for AmericanApprox {
+    fn price(&self, instrument: &OptionSpec, market: &Market) -> PriceResult {
+        match self {
+            AmericanApprox::BaroneAdesiWhaley => BaroneAdesiWhaleyEngine.price(instrument, market),
+            AmericanApprox::BjerksundStensland => {
+                BjerksundStenslandEngine::default().price(instrument, market)
+            }
+            AmericanApprox::JuZhong => JuZhongEngine.price(instrument, market),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tree::OptimalExerciseBinTree;
+    use crate::units::{Rate, Spot, Strike, Vol};
+
+    fn markets() -> Vec<Market> {
+        vec![
+            Market::new(Spot(100.0), Rate(0.08), Vol(0.2)),
+            Market::new(Spot(90.0), Rate(0.05), Vol(0.35)).with_dividend_yield(Rate(0.03)),
+            Market::new(Spot(110.0), Rate(0.03), Vol(0.25)).with_dividend_yield(Rate(0.06)),
+        ]
+    }
+
+    #[test]
+    fn american_prices_stay_below_the_perpetual() {
+        for market in markets() {
+            for option_type in [OptionType::Call, OptionType::Put] {
+                let bound = perpetual(option_type, 100.0, &market).unwrap().price;
+                for expiry in [0.25, 1.0, 3.0] {
+                    let spec = OptionSpec::american(option_type, Strike(100.0), expiry);
+                    let tree = OptimalExerciseBinTree::builder()
+                        .num_steps(500)
+                        .option(spec)
+                        .build()
+                        .unwrap()
+                        .price_only(&market)
+                        .unwrap();
+                    let prices = [
+                        tree,
+                        barone_adesi_whaley(&spec, &market).unwrap(),
+                        bjerksund_stensland(&spec, &market).unwrap(),
+                    ];
+                    for price in prices {
+                        assert!(
+                            price < bound,
+                            "{option_type:?} {expiry}: {price} >= {bound}"
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn put_exercise_boundary_stays_above_the_perpetual_threshold() {
+        for market in markets() {
+            let threshold = perpetual(OptionType::Put, 100.0, &market)
+                .unwrap()
+                .threshold;
+            let tree = OptimalExerciseBinTree::builder()
+                .expiry(1.0)
+                .num_steps(500)
+                .vanilla_put(100.0)
+                .build()
+                .unwrap();
+            let (_, policy) = tree.get_opt_vf_and_policy(&market).unwrap();
+            let boundary = tree.option_exercise_boundary(&market, &policy, OptionType::Put);
+            assert!(!boundary.is_empty());
+            for (time, spot) in boundary {
+                assert!(spot > threshold, "at {time}: {spot} <= {threshold}");
+            }
+        }
+    }
+
+    /// Barone-Adesi and Whaley's (1987) Table I: calls at 100 with
+    /// `r = 8%` and a cost of carry of `-4%`, quoted to the cent.
+    #[test]
+    fn barone_adesi_whaley_matches_its_table() {
+        let rows = [
+            (0.25, 0.2, [0.03, 0.59, 3.52, 10.31, 20.00]),
+            (0.25, 0.4, [1.07, 3.28, 7.41, 13.50, 21.23]),
+            (0.5, 0.2, [0.23, 1.39, 4.72, 10.96, 20.00]),
+        ];
+        for (expiry, vol, prices) in rows {
+            let spec = OptionSpec::american(OptionType::Call, Strike(100.0), expiry);
+            for (spot, reference) in [80.0, 90.0, 100.0, 110.0, 120.0].into_iter().zip(prices) {
+                let market: Market =
+                    Market::new(Spot(spot), Rate(0.08), Vol(vol)).with_dividend_yield(Rate(0.12));
+                let price = barone_adesi_whaley(&spec, &market).unwrap();
+                assert!(
+                    (price - reference).abs() < 5e-3,
+                    "{expiry} {vol} {spot}: {price} vs {reference}"
+                );
+            }
+        }
+    }
+
+    /// Haug's (2007) table of Bjerksund-Stensland (2002) calls at 100 with
+    /// `r = 10%` and no cost of carry.
+    #[test]
+    // 2.7180 is a price in the table, not Euler's number.
+    #[allow(clippy::approx_constant)]
+    fn bjerksund_stensland_matches_haug() {
+        let rows = [
+            (0.1, 0.15, [0.0205, 1.8757, 10.0000]),
+            (0.1, 0.25, [0.3151, 3.1256, 10.3725]),
+            (0.1, 0.35, [0.9479, 4.3746, 11.1578]),
+            (0.5, 0.15, [0.8099, 4.0628, 10.7898]),
+            (0.5, 0.25, [2.7180, 6.7661, 12.9814]),
+            (0.5, 0.35, [4.9665, 9.4608, 15.5137]),
+        ];
+        for (expiry, vol, prices) in rows {
+            let spec = OptionSpec::american(OptionType::Call, Strike(100.0), expiry);
+            for (spot, reference) in [90.0, 100.0, 110.0].into_iter().zip(prices) {
+                let market: Market =
+                    Market::new(Spot(spot), Rate(0.1), Vol(vol)).with_dividend_yield(Rate(0.1));
+                let price = bjerksund_stensland(&spec, &market).unwrap();
+                assert!(
+                    (price - reference).abs() < 5e-5,
+                    "{expiry} {vol} {spot}: {price} vs {reference}"
+                );
+            }
+        }
+    }
+
+    /// Exercising at a flat trigger is one feasible policy, so both calls
+    /// and puts stay below the lattice's optimal-exercise price.
+    #[test]
+    fn bjerksund_stensland_is_a_lower_bound() {
+        for market in markets() {
+            for option_type in [OptionType::Call, OptionType::Put] {
+                for strike in [85.0, 100.0, 115.0] {
+                    let spec = OptionSpec::american(option_type, Strike(strike), 2.0);
+                    let tree = OptimalExerciseBinTree::builder()
+                        .num_steps(2001)
+                        .scheme(core::TreeScheme::LeisenReimer)
+                        .option(spec)
+                        .build()
+                        .unwrap()
+                        .price_only(&market)
+                        .unwrap();
+                    let price = bjerksund_stensland(&spec, &market).unwrap();
+                    assert!(
+                        price <= tree + 1e-3,
+                        "{option_type:?} {strike}: {price} vs {tree}"
+                    );
+                }
+            }
+        }
+    }
+
+    /// Ju and Zhong's correction removes most of Barone-Adesi-Whaley's
+    /// error on long-dated puts, measured against a fine lattice.
+    #[test]
+    fn ju_zhong_improves_on_barone_adesi_whaley_for_long_puts() {
+        let (mut baw, mut jz) = (0.0, 0.0);
+        for vol in [0.2, 0.4] {
+            for spot in [80.0, 100.0, 120.0] {
+                let market: Market = Market::new(Spot(spot), Rate(0.08), Vol(vol));
+                let spec = OptionSpec::american(OptionType::Put, Strike(100.0), 3.0);
+                let tree = OptimalExerciseBinTree::builder()
+                    .num_steps(2001)
+                    .scheme(core::TreeScheme::LeisenReimer)
+                    .option(spec)
+                    .build()
+                    .unwrap()
+                    .price_only(&market)
+                    .unwrap();
+                let error = ju_zhong(&spec, &market).unwrap() - tree;
+                assert!(error.abs() < 0.08, "{vol} {spot}: {error}");
+                jz += error * error;
+                baw += (barone_adesi_whaley(&spec, &market).unwrap() - tree).powi(2);
+            }
+        }
+        assert!(jz < 0.1 * baw, "{jz} vs {baw}");
+    }
+
+    /// The enum prices through the function it names.
+    #[test]
+    fn approximations_dispatch_to_their_pricers() {
+        let spec = OptionSpec::american(OptionType::Put, Strike(105.0), 1.5);
+        for market in markets() {
+            let pairs = [
+                (
+                    AmericanApprox::BaroneAdesiWhaley,
+                    barone_adesi_whaley(&spec, &market),
+                ),
+                (AmericanApprox::JuZhong, ju_zhong(&spec, &market)),
+            ];
+            for (approx, price) in pairs {
+                assert_eq!(approx.price(&spec, &market).unwrap(), price.unwrap());
+            }
+        }
+    }
+}
@@ -0,0 +1,460 @@
+//! Closed-form Black-Scholes pricing used as the European benchmark.
+
+use crate::core;
+use crate::engine::{Greeks, PriceResult, PricingEngine, PricingResult};
+use crate::error::{OptOpsError, Result};
+use crate::float::{cast, Float};
+use crate::instrument::{ExerciseStyle, OptionSpec, OptionType};
+use crate::market::Market;
+use crate::validate;
+use serde::{Deserialize, Serialize};
+use std::time::Instant;
+
+mod asian;
+mod bachelier;
+mod barrier;
+mod chain;
+mod chooser;
+mod compound;
+mod digital;
+mod exchange;
+mod forward_start;
+mod implied;
+mod lookback;
+mod rational;
+mod spread;
+
+pub use crate::core::{bivariate_norm_cdf, d1_d2, norm_cdf, norm_pdf};
+pub use asian::geometric_asian;
+pub use bachelier::{bachelier_greeks, bachelier_price, implied_normal_vol};
+pub use barrier::reiner_rubinstein;
+pub use chain::{chain_implied_vols, ChainQuote, ChainVol, QuoteFlag};
+pub use chooser::chooser;
+pub use compound::geske;
+pub use digital::{digital_greeks, digital_price, Digital};
+pub use exchange::margrabe;
+pub use forward_start::{cliquet, forward_start};
+pub use implied::{implied_vol, ImpliedVolSolver};
+pub use lookback::lookback;
+pub use rational::rational_implied_vol;
+pub use spread::kirk;
+
+/// Black-Scholes-Merton price of `spec` treated as European, whatever its
+/// exercise style. Cash dividends are escrowed out of the spot and
+/// proportional ones scale it down. Barrier contracts get their
+/// Reiner-Rubinstein price.
+pub fn black_scholes_price<T: Float>(spec: &OptionSpec<T>, market: &Market<T>) -> Result<T> {
+    if spec.barrier.is_some() {
+        return reiner_rubinstein(spec, market);
+    }
+    market.validate()?;
+    validate::positive("strike", spec.strike)?;
+    validate::positive("expiry", spec.expiry)?;
+    let rate = market.continuous_rate(spec.expiry)?;
+    let spot = market.escrowed_spot(spec.expiry)? * market.dividends.retained_fraction(spec.expiry);
+    let vol = market.vol_for(spec.strike, spec.expiry)?;
+    Ok(core::black_scholes(
+        spec.option_type,
+        spot,
+        spec.strike,
+        rate,
+        market.carry_yield(rate, vol),
+        vol,
+        spec.expiry,
+    ))
+}
+
+/// Black-Scholes-Merton delta, gamma, theta, vega and rho of `spec` treated
+/// as European, from one evaluation of `d1` and `d2`. Spot sensitivities
+/// are to today's spot, through any escrowed dividends, and vega and rho
+/// include their pull on a quanto's drift.
+pub fn greeks<T: Float>(spec: &OptionSpec<T>, market: &Market<T>) -> Result<Greeks<T>> {
+    let Terms {
+        spot,
+        rate,
+        dividend_yield: q,
+        vol,
+        expiry,
+        retained,
+        d1,
+        d2,
+        carried,
+        discounted,
+    } = Terms::new(spec, market)?;
+    let sqrt_t = expiry.sqrt();
+    let density = carried * norm_pdf(d1);
+    let decay = -density * vol / (sqrt_t + sqrt_t);
+    let (delta, theta, rho) = match spec.option_type {
+        OptionType::Call => (
+            carried * norm_cdf(d1) / spot,
+            decay - rate * discounted * norm_cdf(d2) + q * carried * norm_cdf(d1),
+            expiry * discounted * norm_cdf(d2),
+        ),
+        OptionType::Put => (
+            -carried * norm_cdf(-d1) / spot,
+            decay + rate * discounted * norm_cdf(-d2) - q * carried * norm_cdf(-d1),
+            -expiry * discounted * norm_cdf(-d2),
+        ),
+    };
+    // A quanto's yield moves one for one with the domestic rate and with
+    // the vol times the FX vol's correlated share.
+    let (vega, rho) = match &market.quanto {
+        Some(quanto) => {
+            let to_yield = -expiry * spot * delta;
+            let fx_share = quanto.correlation * quanto.fx_vol;
+            (density * sqrt_t + to_yield * fx_share, rho + to_yield)
+        }
+        None => (density * sqrt_t, rho),
+    };
+    Ok(Greeks {
+        delta: delta * retained,
+        gamma: density / (spot * spot * vol * sqrt_t) * retained * retained,
+        theta,
+        vega: Some(vega),
+        rho: Some(rho),
+    })
+}
+
+/// Which diffusion the market's vol is read against: the vol of log
+/// returns, as equity desks quote it, or of price changes themselves, as
+/// rates and some commodity desks do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Model {
+    /// Black-Scholes-Merton.
+    #[default]
+    Lognormal,
+    /// Bachelier.
+    Normal,
+}
+
+impl Model {
+    /// Price of `spec` treated as European under this model.
+    pub fn price<T: Float>(self, spec: &OptionSpec<T>, market: &Market<T>) -> Result<T> {
+        match self {
+            Model::Lognormal => black_scholes_price(spec, market),
+            Model::Normal => bachelier_price(spec, market),
+        }
+    }
+
+    /// Greeks of `spec` treated as European under this model, vega per
+    /// unit of its own vol.
+    pub fn greeks<T: Float>(self, spec: &OptionSpec<T>, market: &Market<T>) -> Result<Greeks<T>> {
+        match self {
+            Model::Lognormal => greeks(spec, market),
+            Model::Normal => bachelier_greeks(spec, market),
+        }
+    }
+
+    /// This model's vol at which `spec` treated as European is worth
+    /// `price`, with the default solver settings for lognormal vols.
+    pub fn implied_vol<T: Float>(
+        self,
+        price: T,
+        spec: &OptionSpec<T>,
+        market: &Market<T>,
+    ) -> Result<T> {
+        match self {
+            Model::Lognormal => implied_vol(price, spec, market),
+            Model::Normal => implied_normal_vol(price, spec, market),
+        }
+    }
+}
+
+/// Second- and third-order Black-Scholes-Merton sensitivities. Time
+/// derivatives are per year of calendar time passing, like theta.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct HigherOrderGreeks<T = f64> {
+    /// Change in delta per unit vol, equally vega per unit spot.
+    pub vanna: T,
+    /// Change in vega per unit vol.
+    pub volga: T,
+    /// Change in delta as time passes.
+    pub charm: T,
+    /// Change in vega as time passes.
+    pub veta: T,
+    /// Change in gamma per unit spot.
+    pub speed: T,
+    /// Change in gamma per unit vol.
+    pub zomma: T,
+    /// Change in gamma as time passes.
+    pub color: T,
+    /// Change in volga per unit vol.
+    pub ultima: T,
+}
+
+/// The higher-order Greeks of `spec` treated as European, from the same
+/// `d1` and `d2` terms as `greeks`. Only charm depends on the option type.
+/// A quanto's adjusted yield is held fixed.
+pub fn higher_order_greeks<T: Float>(
+    spec: &OptionSpec<T>,
+    market: &Market<T>,
+) -> Result<HigherOrderGreeks<T>> {
+    let Terms {
+        spot,
+        rate,
+        dividend_yield: q,
+        vol,
+        expiry,
+        retained,
+        d1,
+        d2,
+        carried,
+        ..
+    } = Terms::new(spec, market)?;
+    let two = cast::<T>(2.0);
+    let sqrt_t = expiry.sqrt();
+    let sigma_sqrt = vol * sqrt_t;
+    let carry = rate - q;
+    let density = carried * norm_pdf(d1);
+    let vega = density * sqrt_t;
+    let gamma = density / (spot * spot * sigma_sqrt);
+    let volga = vega * d1 * d2 / vol;
+    // Shared by charm and color: how the distance to the strike in
+    // standard deviations drifts with time.
+    let drift = (two * carry * expiry - d2 * sigma_sqrt) / (two * expiry * sigma_sqrt);
+    let charm = match spec.option_type {
+        OptionType::Call => q * carried * norm_cdf(d1) / spot - density / spot * drift,
+        OptionType::Put => -q * carried * norm_cdf(-d1) / spot - density / spot * drift,
+    };
+    let veta = vega * (q + carry * d1 / sigma_sqrt - (T::one() + d1 * d2) / (two * expiry));
+    let color = gamma * (two * q * expiry + T::one() + two * expiry * drift * d1) / (two * expiry);
+    let ultima = -vega / (vol * vol) * (d1 * d2 * (T::one() - d1 * d2) + d1 * d1 + d2 * d2);
+    Ok(HigherOrderGreeks {
+        vanna: -density * d2 / (vol * spot) * retained,
+        volga,
+        charm: charm * retained,
+        veta,
+        speed: -gamma / spot * (d1 / sigma_sqrt + T::one()) * retained * retained * retained,
+        zomma: gamma * (d1 * d2 - T::one()) / vol * retained * retained,
+        color: color * retained * retained,
+        ultima,
+    })
+}
+
+/// The Black-Scholes-Merton inputs and terms every Greek is built from,
+/// with any cash dividends escrowed out of the spot.
+struct Terms<T> {
+    spot: T,
+    rate: T,
+    dividend_yield: T,
+    vol: T,
+    expiry: T,
+    /// Share of the spot left after proportional dividends; today's spot
+    /// moves the escrowed one by this much.
+    retained: T,
+    d1: T,
+    d2: T,
+    /// Spot carried at the dividend yield to expiry and discounted back.
+    carried: T,
+    /// Discounted strike.
+    discounted: T,
+}
+
+impl<T: Float> Terms<T> {
+    fn new(spec: &OptionSpec<T>, market: &Market<T>) -> Result<Self> {
+        if spec.barrier.is_some() {
+            return Err(OptOpsError::Unsupported {
+                engine: "Black-Scholes",
+                reason: "barrier options",
+            });
+        }
+        market.validate()?;
+        let strike = validate::positive("strike", spec.strike)?;
+        let expiry = validate::positive("expiry", spec.expiry)?;
+        let rate = market.continuous_rate(expiry)?;
+        let retained = market.dividends.retained_fraction(expiry);
+        let spot = market.escrowed_spot(expiry)? * retained;
+        let vol = market.vol_for(strike, expiry)?;
+        let dividend_yield = market.carry_yield(rate, vol);
+        let (d1, d2) = d1_d2(spot, strike, rate, dividend_yield, vol, expiry);
+        Ok(Terms {
+            spot,
+            rate,
+            dividend_yield,
+            vol,
+            expiry,
+            retained,
+            d1,
+            d2,
+            carried: spot * (-dividend_yield * expiry).exp(),
+            discounted: strike * (-rate * expiry).exp(),
+        })
+    }
+}
+
+/// Closed-form engine for European exercise.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BlackScholesEngine;
+
+impl PricingEngine for BlackScholesEngine {
+    fn price(&self, instrument: &OptionSpec, market: &Market) -> PriceResult {
+        let start = Instant::now();
+        if instrument.exercise != ExerciseStyle::European {
+            return Err(OptOpsError::Unsupported {
+                engine: "Black-Scholes",
+                reason: "early exercise",
+            });
+        }
+        let price = black_scholes_price(instrument, market)?;
+        let mut result = PricingResult::new("Black-Scholes", price, start.elapsed());
+        result.early_exercise_premium = Some(0.0);
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::units::{Rate, Spot, Strike, Vol};
+
+    fn market() -> Market {
+        Market::new(Spot(100.0), Rate(0.05), Vol(0.25)).with_dividend_yield(Rate(0.02))
+    }
+
+    /// Inputs a Greek differentiates in; time passing shortens the expiry.
+    #[derive(Clone, Copy)]
+    enum Input {
+        Spot,
+        Vol,
+        Rate,
+        Time,
+    }
+
+    /// Central difference of `f` at `spec` in `input`, with step `h`.
+    fn derivative<F>(f: F, spec: OptionSpec, input: Input, h: f64) -> f64
+    where
+        F: Fn(&OptionSpec, &Market) -> f64,
+    {
+        let at = |h: f64| {
+            let market = market();
+            let (spec, market) = match input {
+                Input::Spot => (
+                    spec,
+                    Market {
+                        spot: market.spot + h,
+                        ..market
+                    },
+                ),
+                Input::Vol => (
+                    spec,
+                    Market {
+                        vol: market.vol + h,
+                        ..market
+                    },
+                ),
+                Input::Rate => (
+                    spec,
+                    Market {
+                        rate: market.rate + h,
+                        ..market
+                    },
+                ),
+                Input::Time => (
+                    OptionSpec {
+                        expiry: spec.expiry - h,
+                        ..spec
+                    },
+                    market,
+                ),
+            };
+            f(&spec, &market)
+        };
+        (at(h) - at(-h)) / (2.0 * h)
+    }
+
+    fn price(spec: &OptionSpec, market: &Market) -> f64 {
+        black_scholes_price(spec, market).unwrap()
+    }
+
+    fn specs() -> impl Iterator<Item = OptionSpec> {
+        [OptionType::Call, OptionType::Put]
+            .into_iter()
+            .flat_map(|option_type| {
+                [80.0, 100.0, 125.0]
+                    .map(|strike| OptionSpec::european(option_type, Strike(strike), 0.75))
+            })
+    }
+
+    /// Hull's (2018) worked example: a 20-week call at 50 on a stock at 49
+    /// with `r = 5%` and `sigma = 20%`.
+    #[test]
+    fn greeks_match_hull() {
+        let market: Market = Market::new(Spot(49.0), Rate(0.05), Vol(0.2));
+        let spec = OptionSpec::european(OptionType::Call, Strike(50.0), 20.0 / 52.0);
+        let greeks = greeks(&spec, &market).unwrap();
+        let expected = [
+            (greeks.delta, 0.522, 1e-3),
+            (greeks.gamma, 0.066, 1e-3),
+            (greeks.theta, -4.31, 1e-2),
+            (greeks.vega.unwrap(), 12.1, 0.05),
+            (greeks.rho.unwrap(), 8.91, 1e-2),
+        ];
+        for (value, reference, tolerance) in expected {
+            assert!(
+                (value - reference).abs() < tolerance,
+                "{value} vs {reference}"
+            );
+        }
+    }
+
+    /// Every Greek is the derivative of the closed-form price.
+    #[test]
+    fn greeks_match_finite_differences() {
+        let delta = |spec: &OptionSpec, market: &Market| greeks(spec, market).unwrap().delta;
+        for spec in specs() {
+            let greeks = greeks(&spec, &market()).unwrap();
+            let checks = [
+                (greeks.delta, derivative(price, spec, Input::Spot, 1e-3)),
+                (greeks.gamma, derivative(delta, spec, Input::Spot, 1e-3)),
+                (
+                    greeks.vega.unwrap(),
+                    derivative(price, spec, Input::Vol, 1e-5),
+                ),
+                (
+                    greeks.rho.unwrap(),
+                    derivative(price, spec, Input::Rate, 1e-5),
+                ),
+                (greeks.theta, derivative(price, spec, Input::Time, 1e-5)),
+            ];
+            for (exact, numeric) in checks {
+                assert!(
+                    (exact - numeric).abs() < 1e-5 * (1.0 + exact.abs()),
+                    "{spec:?}: {exact} vs {numeric}"
+                );
+            }
+        }
+    }
+
+    /// Each higher-order Greek is the derivative of a lower-order one.
+    #[test]
+    fn higher_order_greeks_match_finite_differences() {
+        let first = |pick: fn(&Greeks) -> f64| {
+            move |spec: &OptionSpec, market: &Market| pick(&greeks(spec, market).unwrap())
+        };
+        let delta = first(|greeks| greeks.delta);
+        let gamma = first(|greeks| greeks.gamma);
+        let vega = first(|greeks| greeks.vega.unwrap());
+        let volga =
+            |spec: &OptionSpec, market: &Market| higher_order_greeks(spec, market).unwrap().volga;
+        for spec in specs() {
+            let higher = higher_order_greeks(&spec, &market()).unwrap();
+            let checks = [
+                (higher.vanna, derivative(delta, spec, Input::Vol, 1e-5)),
+                (higher.volga, derivative(vega, spec, Input::Vol, 1e-5)),
+                (higher.charm, derivative(delta, spec, Input::Time, 1e-5)),
+                (higher.veta, derivative(vega, spec, Input::Time, 1e-5)),
+                (higher.speed, derivative(gamma, spec, Input::Spot, 1e-3)),
+                (higher.zomma, derivative(gamma, spec, Input::Vol, 1e-5)),
+                (higher.color, derivative(gamma, spec, Input::Time, 1e-5)),
+                (higher.ultima, derivative(volga, spec, Input::Vol, 1e-5)),
+            ];
+            for (i, (exact, numeric)) in checks.into_iter().enumerate() {
+                assert!(
+                    (exact - numeric).abs() < 1e-5 * (1.0 + exact.abs()),
+                    "{spec:?} #{i}: {exact} vs {numeric}"
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,68 @@
+//! Closed-form prices of geometric-average Asian options.
+
+use crate::core;
+use crate::error::{Domain, OptOpsError, Result};
+use crate::float::{cast, Float};
+use crate::instrument::{AsianSpec, Average, Sampling};
+use crate::market::Market;
+use crate::validate;
+
+/// Exact price of an Asian option on the geometric average of the spot,
+/// whose logarithm is normal under Black-Scholes dynamics (Kemna and Vorst,
+/// 1990, for continuous sampling). With `n` fixings at `iT / n` the log
+/// average has mean `ln S + (b - vol^2 / 2) T (n + 1) / 2n` and variance
+/// `vol^2 T (n + 1)(2n + 1) / 6n^2`, tending to `T / 2` and `vol^2 T / 3` as
+/// sampling becomes continuous. The contract is then a vanilla on an asset
+/// with that forward and vol, priced by Black-Scholes.
+///
+/// It is the standard control variate for the arithmetic average, which
+/// has no closed form and is rejected here.
+pub fn geometric_asian<T: Float>(spec: &AsianSpec<T>, market: &Market<T>) -> Result<T> {
+    let engine = "geometric Asian";
+    if spec.average == Average::Arithmetic {
+        return Err(OptOpsError::Unsupported {
+            engine,
+            reason: "arithmetic averages",
+        });
+    }
+    if !market.dividends.is_empty() {
+        return Err(OptOpsError::Unsupported {
+            engine,
+            reason: "discrete dividends",
+        });
+    }
+    market.validate()?;
+    let strike = validate::positive("strike", spec.strike)?;
+    let expiry = validate::positive("expiry", spec.expiry)?;
+    let (one, two) = (T::one(), cast::<T>(2.0));
+    // Fractions of `T` in the mean and of `vol^2 T` in the variance of the
+    // log average.
+    let (mean_fraction, variance_fraction) = match spec.sampling {
+        Sampling::Continuous => (cast(0.5), cast(1.0 / 3.0)),
+        Sampling::Discrete(fixings) => {
+            validate::check("fixings", fixings as f64, Domain::AtLeast(1.0))?;
+            let n = cast::<T>(fixings as f64);
+            (
+                (n + one) / (two * n),
+                (n + one) * (two * n + one) / (cast::<T>(6.0) * n * n),
+            )
+        }
+    };
+    let rate = market.continuous_rate(expiry)?;
+    let vol = market.vol_for(strike, expiry)?;
+    let carry = rate - market.carry_yield(rate, vol);
+    let variance = vol * vol * variance_fraction;
+    // Forward of the geometric average, as the growth of an asset with
+    // `vol^2 variance_fraction` of variance paying the balance as a yield.
+    let log_forward = (carry - vol * vol / two) * mean_fraction * expiry + variance * expiry / two;
+    let average_yield = rate - log_forward / expiry;
+    Ok(core::black_scholes(
+        spec.option_type,
+        market.spot,
+        strike,
+        rate,
+        average_yield,
+        variance.sqrt(),
+        expiry,
+    ))
+}
@@ -0,0 +1,180 @@
+//! Bachelier prices, Greeks and implied vols, for markets that quote the
+//! vol of the price itself rather than of its log.
+
+use super::{norm_cdf, norm_pdf};
+use crate::engine::Greeks;
+use crate::error::{Domain, OptOpsError, Result};
+use crate::float::{cast, to_f64, Float};
+use crate::instrument::{OptionSpec, OptionType};
+use crate::market::Market;
+use crate::validate;
+
+/// Newton iterations allowed when solving for a normal vol.
+const MAX_ITERATIONS: usize = 100;
+
+/// Bachelier price of `spec` treated as European, reading the market's
+/// vol as a normal vol in price units per square-root year:
+/// `e^{-rT} [±(F - K) N(±d) + σ√T n(d)]` with `d = (F - K) / σ√T` and `F`
+/// the forward. Strikes may be zero or negative. Dividends enter the
+/// forward as they do in `black_scholes_price`.
+pub fn bachelier_price<T: Float>(spec: &OptionSpec<T>, market: &Market<T>) -> Result<T> {
+    let terms = NormalTerms::new(spec, market)?;
+    let deviation = terms.vol * terms.expiry.sqrt();
+    Ok(terms.discount * undiscounted(spec.option_type, terms.spread, deviation))
+}
+
+/// Delta, gamma, theta, vega and rho of `spec` under `bachelier_price`.
+/// Vega is per unit of normal vol, and spot sensitivities are to today's
+/// spot through any escrowed dividends.
+pub fn bachelier_greeks<T: Float>(spec: &OptionSpec<T>, market: &Market<T>) -> Result<Greeks<T>> {
+    let NormalTerms {
+        rate,
+        dividend_yield: q,
+        vol,
+        expiry,
+        retained,
+        forward,
+        spread,
+        discount,
+    } = NormalTerms::new(spec, market)?;
+    let sqrt_t = expiry.sqrt();
+    let deviation = vol * sqrt_t;
+    let d = spread / deviation;
+    let sign: T = match spec.option_type {
+        OptionType::Call => T::one(),
+        OptionType::Put => -T::one(),
+    };
+    let value = discount * undiscounted(spec.option_type, spread, deviation);
+    // Sensitivities of the discounted price to the forward and to the
+    // forward's standard deviation.
+    let to_forward = discount * sign * norm_cdf(sign * d);
+    let density = discount * norm_pdf(d);
+    let growth = ((rate - q) * expiry).exp();
+    Ok(Greeks {
+        delta: to_forward * growth * retained,
+        gamma: density / deviation * growth * growth * retained * retained,
+        theta: rate * value - to_forward * (rate - q) * forward - density * vol / (sqrt_t + sqrt_t),
+        vega: Some(density * sqrt_t),
+        rho: Some(expiry * (to_forward * forward - value)),
+    })
+}
+
+/// Normal vol at which `bachelier_price` of `spec` matches `price`, as
+/// `implied_vol` is for lognormal vols. Newton's method runs on the
+/// forward's standard deviation, which the undiscounted price is
+/// increasing and convex in, and steps leaving the bracket around the root
+/// bisect it instead. A price at intrinsic value on the forward implies
+/// zero vol; one below it admits arbitrage and is rejected. The market's
+/// own vol is ignored.
+pub fn implied_normal_vol<T: Float>(
+    price: T,
+    spec: &OptionSpec<T>,
+    market: &Market<T>,
+) -> Result<T> {
+    let terms = NormalTerms::new(spec, market)?;
+    let target = validate::finite("price", price)? / terms.discount;
+    let intrinsic = undiscounted(spec.option_type, terms.spread, T::zero());
+    let floor = to_f64(intrinsic * terms.discount);
+    validate::check("price", price, Domain::AtLeast(floor))?;
+    if target <= intrinsic {
+        return Ok(T::zero());
+    }
+
+    // The time value grows no faster than the deviation over sqrt(2π), so
+    // the root lies above that; double from there to bracket it.
+    let root_two_pi = cast::<T>((2.0 * std::f64::consts::PI).sqrt());
+    let gap = |deviation: T| undiscounted(spec.option_type, terms.spread, deviation) - target;
+    let (mut lo, mut hi) = (T::zero(), (target - intrinsic) * root_two_pi);
+    while gap(hi) < T::zero() {
+        lo = hi;
+        hi = hi + hi;
+    }
+    let tolerance = cast::<T>(1e-14) * target;
+    let mut deviation = hi;
+    for _ in 0..MAX_ITERATIONS {
+        let miss = gap(deviation);
+        if miss.abs() <= tolerance || hi - lo <= T::epsilon() * hi {
+            return Ok(deviation / terms.expiry.sqrt());
+        }
+        if miss > T::zero() {
+            hi = deviation;
+        } else {
+            lo = deviation;
+        }
+        let step = deviation - miss / norm_pdf(terms.spread / deviation);
+        deviation = if lo < step && step < hi {
+            step
+        } else {
+            (lo + hi) / cast(2.0)
+        };
+    }
+    Err(OptOpsError::NonConvergence {
+        solver: "Bachelier implied vol",
+        iterations: MAX_ITERATIONS,
+    })
+}
+
+/// Undiscounted Bachelier price with the forward `spread` above the
+/// strike and the forward's standard deviation `deviation` at expiry.
+fn undiscounted<T: Float>(option_type: OptionType, spread: T, deviation: T) -> T {
+    let spread = match option_type {
+        OptionType::Call => spread,
+        OptionType::Put => -spread,
+    };
+    if deviation <= T::zero() {
+        return spread.max(T::zero());
+    }
+    let d = spread / deviation;
+    spread * norm_cdf(d) + deviation * norm_pdf(d)
+}
+
+/// The inputs every Bachelier quantity is built from, with any cash
+/// dividends escrowed out of the spot.
+struct NormalTerms<T> {
+    rate: T,
+    dividend_yield: T,
+    vol: T,
+    expiry: T,
+    /// Share of the spot left after proportional dividends.
+    retained: T,
+    forward: T,
+    /// Forward less the strike.
+    spread: T,
+    /// Discount factor to expiry.
+    discount: T,
+}
+
+impl<T: Float> NormalTerms<T> {
+    fn new(spec: &OptionSpec<T>, market: &Market<T>) -> Result<Self> {
+        if spec.barrier.is_some() {
+            return Err(OptOpsError::Unsupported {
+                engine: "Bachelier",
+                reason: "barrier options",
+            });
+        }
+        if market.quanto.is_some() {
+            return Err(OptOpsError::Unsupported {
+                engine: "Bachelier",
+                reason: "quanto adjustment of a normal vol",
+            });
+        }
+        market.validate()?;
+        let strike = validate::finite("strike", spec.strike)?;
+        let expiry = validate::positive("expiry", spec.expiry)?;
+        let rate = market.continuous_rate(expiry)?;
+        let retained = market.dividends.retained_fraction(expiry);
+        let spot = market.escrowed_spot(expiry)? * retained;
+        let (dividend_yield, vol) = (market.dividend_yield, market.effective_vol(expiry));
+        let forward = spot * ((rate - dividend_yield) * expiry).exp();
+        Ok(NormalTerms {
+            rate,
+            dividend_yield,
+            vol,
+            expiry,
+            retained,
+            forward,
+            spread: forward - strike,
+            discount: (-rate * expiry).exp(),
+        })
+    }
+}
@@ -0,0 +1,233 @@
+//! Closed-form prices of continuously monitored single-barrier options.
+
+use super::norm_cdf;
+use crate::barrier::BarrierKind;
+use crate::core;
+use crate::error::{OptOpsError, Result};
+use crate::float::{cast, Float};
+use crate::instrument::{OptionSpec, OptionType};
+use crate::market::Market;
+use crate::validate;
+
+/// Reiner-Rubinstein (1991) price of a European knock-in or knock-out
+/// call or put under continuous monitoring, in Haug's notation: each of
+/// the eight variants is a sum of the terms `A` to `D`, vanilla-like
+/// pieces struck at the strike or the barrier and their reflections in the
+/// barrier, plus the rebate, `E` paid at expiry if a knock-in never fired
+/// or `F` paid on the touch that knocks out. Without a barrier this is the
+/// Black-Scholes price.
+///
+/// A contract whose barrier is already breached is worth its rebate, for
+/// knock-outs, or the vanilla, for knock-ins. Discrete dividends are
+/// rejected, since they make the barrier level on the spot a different
+/// contract from a barrier on the escrowed spot.
+pub fn reiner_rubinstein<T: Float>(spec: &OptionSpec<T>, market: &Market<T>) -> Result<T> {
+    if !market.dividends.is_empty() {
+        return Err(OptOpsError::Unsupported {
+            engine: "Reiner-Rubinstein",
+            reason: "discrete dividends",
+        });
+    }
+    market.validate()?;
+    let strike = validate::positive("strike", spec.strike)?;
+    let expiry = validate::positive("expiry", spec.expiry)?;
+    let rate = market.continuous_rate(expiry)?;
+    let (spot, vol) = (market.spot, market.vol_for(strike, expiry)?);
+    let dividend_yield = market.carry_yield(rate, vol);
+    let vanilla = core::black_scholes(
+        spec.option_type,
+        spot,
+        strike,
+        rate,
+        dividend_yield,
+        vol,
+        expiry,
+    );
+    let Some(barrier) = spec.barrier else {
+        return Ok(vanilla);
+    };
+    let level = validate::positive("barrier", barrier.level)?;
+    let rebate = barrier.rebate;
+    if barrier.is_hit(spot) {
+        return Ok(if barrier.kind.is_knock_out() {
+            rebate
+        } else {
+            vanilla
+        });
+    }
+
+    let one = T::one();
+    let carry = rate - dividend_yield;
+    let variance = vol * vol;
+    let sigma_sqrt = vol * expiry.sqrt();
+    let mu = (carry - variance / cast(2.0)) / variance;
+    let lambda = (mu * mu + cast::<T>(2.0) * rate / variance).sqrt();
+    let phi = match spec.option_type {
+        OptionType::Call => one,
+        OptionType::Put => -one,
+    };
+    let eta = if barrier.kind.is_up() { -one } else { one };
+    let carried = spot * ((carry - rate) * expiry).exp();
+    let discounted = strike * (-rate * expiry).exp();
+    let ratio = level / spot;
+    let reflected = ratio.powf(cast::<T>(2.0) * (mu + one));
+    let reflected_strike = ratio.powf(cast::<T>(2.0) * mu);
+    let standardise = |log: T| log / sigma_sqrt + (one + mu) * sigma_sqrt;
+    let x1 = standardise((spot / strike).ln());
+    let x2 = standardise((spot / level).ln());
+    let y1 = standardise((level * level / (spot * strike)).ln());
+    let y2 = standardise(ratio.ln());
+    let z = ratio.ln() / sigma_sqrt + lambda * sigma_sqrt;
+
+    // Vanilla-like payoff above `x` and its reflection in the barrier.
+    let direct = |x: T| {
+        phi * carried * norm_cdf(phi * x) - phi * discounted * norm_cdf(phi * (x - sigma_sqrt))
+    };
+    let mirrored = |y: T| {
+        phi * carried * reflected * norm_cdf(eta * y)
+            - phi * discounted * reflected_strike * norm_cdf(eta * (y - sigma_sqrt))
+    };
+    let (a, b, c, d) = (direct(x1), direct(x2), mirrored(y1), mirrored(y2));
+    let untouched =
+        norm_cdf(eta * (x2 - sigma_sqrt)) - reflected_strike * norm_cdf(eta * (y2 - sigma_sqrt));
+    let e = rebate * (-rate * expiry).exp() * untouched;
+    let f = rebate
+        * (ratio.powf(mu + lambda) * norm_cdf(eta * z)
+            + ratio.powf(mu - lambda) * norm_cdf(eta * (z - cast::<T>(2.0) * lambda * sigma_sqrt)));
+
+    // Haug's table, by the strike's side of the barrier.
+    let above = strike > level;
+    use BarrierKind::*;
+    use OptionType::*;
+    Ok(match (barrier.kind, spec.option_type, above) {
+        (DownAndIn, Call, true) | (UpAndIn, Put, false) => c + e,
+        (DownAndIn, Call, false) | (UpAndIn, Put, true) => a - b + d + e,
+        (UpAndIn, Call, true) | (DownAndIn, Put, false) => a + e,
+        (UpAndIn, Call, false) | (DownAndIn, Put, true) => b - c + d + e,
+        (DownAndOut, Call, true) | (UpAndOut, Put, false) => a - c + f,
+        (DownAndOut, Call, false) | (UpAndOut, Put, true) => b - d + f,
+        (UpAndOut, Call, true) | (DownAndOut, Put, false) => f,
+        (UpAndOut, Call, false) | (DownAndOut, Put, true) => a - b + c - d + f,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::barrier::Barrier;
+    use crate::units::{Rate, Spot, Strike, Vol};
+
+    /// Haug (2007), table 4-13: S = 100, r = 8%, b = 4%, T = 0.5, rebate 3
+    /// and vol 25%, for strikes 90, 100 and 110.
+    #[test]
+    fn matches_haugs_table() {
+        let market: Market =
+            Market::new(Spot(100.0), Rate(0.08), Vol(0.25)).with_dividend_yield(Rate(0.04));
+        let table = [
+            (
+                BarrierKind::DownAndOut,
+                95.0,
+                OptionType::Call,
+                [9.0246, 6.7924, 4.8759],
+            ),
+            (
+                BarrierKind::DownAndOut,
+                100.0,
+                OptionType::Call,
+                [3.0000, 3.0000, 3.0000],
+            ),
+            (
+                BarrierKind::UpAndOut,
+                105.0,
+                OptionType::Call,
+                [2.6789, 2.3580, 2.3453],
+            ),
+            (
+                BarrierKind::DownAndIn,
+                95.0,
+                OptionType::Call,
+                [7.7627, 4.0109, 2.0576],
+            ),
+            (
+                BarrierKind::DownAndIn,
+                100.0,
+                OptionType::Call,
+                [13.8333, 7.8494, 3.9795],
+            ),
+            (
+                BarrierKind::UpAndIn,
+                105.0,
+                OptionType::Call,
+                [14.1112, 8.4482, 4.5910],
+            ),
+            (
+                BarrierKind::DownAndIn,
+                95.0,
+                OptionType::Put,
+                [2.9586, 6.5677, 11.9752],
+            ),
+            (
+                BarrierKind::DownAndIn,
+                100.0,
+                OptionType::Put,
+                [2.2845, 5.9085, 11.6465],
+            ),
+            (
+                BarrierKind::UpAndIn,
+                105.0,
+                OptionType::Put,
+                [1.4653, 3.3721, 7.0846],
+            ),
+            (
+                BarrierKind::DownAndOut,
+                95.0,
+                OptionType::Put,
+                [2.2798, 2.2947, 2.6252],
+            ),
+            (
+                BarrierKind::DownAndOut,
+                100.0,
+                OptionType::Put,
+                [3.0000, 3.0000, 3.0000],
+            ),
+            (
+                BarrierKind::UpAndOut,
+                105.0,
+                OptionType::Put,
+                [3.7760, 5.4932, 7.5187],
+            ),
+        ];
+        for (kind, level, option_type, prices) in table {
+            for (strike, expected) in [90.0, 100.0, 110.0].into_iter().zip(prices) {
+                let barrier = Barrier::new(kind, level).with_rebate(3.0);
+                let spec =
+                    OptionSpec::european(option_type, Strike(strike), 0.5).with_barrier(barrier);
+                let price = reiner_rubinstein(&spec, &market).unwrap();
+                assert!(
+                    (price - expected).abs() < 5e-5,
+                    "{kind:?} {level} {option_type:?} {strike}: {price} vs {expected}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn knock_in_and_knock_out_sum_to_the_vanilla() {
+        let market: Market = Market::new(Spot(100.0), Rate(0.05), Vol(0.3));
+        for option_type in [OptionType::Call, OptionType::Put] {
+            let spec = OptionSpec::european(option_type, Strike(100.0), 1.0);
+            let vanilla = reiner_rubinstein(&spec, &market).unwrap();
+            for (knock_in, knock_out) in [
+                (Barrier::down_and_in(90.0), Barrier::down_and_out(90.0)),
+                (Barrier::up_and_in(115.0), Barrier::up_and_out(115.0)),
+            ] {
+                let sum = reiner_rubinstein(&spec.with_barrier(knock_in), &market).unwrap()
+                    + reiner_rubinstein(&spec.with_barrier(knock_out), &market).unwrap();
+                assert!(
+                    (sum - vanilla).abs() < 1e-10,
+                    "{option_type:?}: {sum} vs {vanilla}"
+                );
+            }
+        }
+    }
+}
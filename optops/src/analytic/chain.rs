@@ -0,0 +1,272 @@
+//! Implied vols of a whole option chain at once.
+
+use super::rational_implied_vol;
+use crate::error::OptOpsError;
+use crate::float::{cast, Float};
+use crate::instrument::{OptionSpec, OptionType};
+use crate::market::Market;
+use crate::units::Strike;
+use serde::{Deserialize, Serialize};
+
+/// One European quote of a chain.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ChainQuote<T = f64> {
+    pub option_type: OptionType,
+    pub strike: T,
+    pub expiry: T,
+    pub bid: T,
+    pub ask: T,
+}
+
+impl<T: Float> ChainQuote<T> {
+    pub fn new(option_type: OptionType, strike: T, expiry: T, bid: T, ask: T) -> Self {
+        ChainQuote {
+            option_type,
+            strike,
+            expiry,
+            bid,
+            ask,
+        }
+    }
+
+    /// A quote with only a mid price, as both its bid and its ask.
+    pub fn mid(option_type: OptionType, strike: T, expiry: T, price: T) -> Self {
+        ChainQuote::new(option_type, strike, expiry, price, price)
+    }
+}
+
+/// Why a quote of a chain has no mid vol, or the arbitrage it allows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QuoteFlag {
+    /// The bid is above the ask.
+    Crossed,
+    /// The mid is outside the no-arbitrage range `implied_vol` accepts,
+    /// below the intrinsic value or at least the upper bound.
+    OutOfBounds,
+    /// A price is not finite, the strike or expiry is invalid, or the
+    /// market cannot be inverted under, such as a quanto.
+    Invalid,
+    /// Against the next strike of the same type and expiry, a call bids
+    /// above the ask of the call struck below it, or a put above the ask of
+    /// the put struck above it.
+    Monotonicity,
+    /// Against the next strike of the same type and expiry, the price
+    /// falls by more than the strikes' gap discounted to today: the bid of
+    /// the dearer quote less the ask of the cheaper exceeds it.
+    Slope,
+    /// With the strikes either side of it of the same type and expiry, the
+    /// butterfly bought at the wings' asks and sold at twice this bid costs
+    /// less than nothing.
+    Convexity,
+}
+
+/// Implied vols of one `ChainQuote`. A bid below the intrinsic value has
+/// no vol and is `None` without flagging the quote, while one at it
+/// implies zero; `flag` says why the mid has none or, for a quote that has
+/// a mid vol, which cross-strike bound it breaks with its neighbours.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ChainVol<T = f64> {
+    pub bid: Option<T>,
+    pub mid: Option<T>,
+    pub ask: Option<T>,
+    pub flag: Option<QuoteFlag>,
+}
+
+/// Black-Scholes implied vols of every quote in `quotes` against one
+/// `market`, by `rational_implied_vol`, in the order given. A bad quote is
+/// flagged in its own result and never fails the rest; with the `rayon`
+/// feature the quotes are inverted across the thread pool. The quotes with
+/// a mid vol are then checked across strikes within each type and expiry,
+/// and those that trade against a neighbour for a riskless profit are
+/// flagged too, keeping their vols; a quote breaking several bounds gets
+/// the first of `Monotonicity`, `Slope` and `Convexity`.
+pub fn chain_implied_vols<T: Float>(
+    quotes: &[ChainQuote<T>],
+    market: &Market<T>,
+) -> Vec<ChainVol<T>> {
+    let invert = |quote: &ChainQuote<T>| solve(quote, market);
+    #[cfg(feature = "rayon")]
+    let mut vols: Vec<ChainVol<T>> = if crate::adjoint::recording() {
+        quotes.iter().map(invert).collect()
+    } else {
+        use rayon::prelude::*;
+        quotes.par_iter().map(invert).collect()
+    };
+    #[cfg(not(feature = "rayon"))]
+    let mut vols: Vec<ChainVol<T>> = quotes.iter().map(invert).collect();
+    flag_arbitrage(quotes, market, &mut vols);
+    vols
+}
+
+/// Flags the solved quotes breaking the static no-arbitrage bounds across
+/// strikes of one type and expiry. The bounds are checked between bids and
+/// asks, so only a spread that could be traded for a profit is flagged.
+fn flag_arbitrage<T: Float>(
+    quotes: &[ChainQuote<T>],
+    market: &Market<T>,
+    vols: &mut [ChainVol<T>],
+) {
+    // Strikes closer than this, relative to the strike, are ties.
+    let slack = cast::<T>(1e-12);
+    let mut breaks: Vec<(QuoteFlag, Vec<usize>)> = Vec::new();
+    for option_type in [OptionType::Call, OptionType::Put] {
+        let mut sorted: Vec<usize> = (0..quotes.len())
+            .filter(|&i| vols[i].flag.is_none() && quotes[i].option_type == option_type)
+            .collect();
+        sorted.sort_by(|&a, &b| {
+            let (a, b) = (&quotes[a], &quotes[b]);
+            (a.expiry, a.strike)
+                .partial_cmp(&(b.expiry, b.strike))
+                .expect("solved quotes are finite")
+        });
+        for slice in sorted.chunk_by(|&a, &b| quotes[a].expiry == quotes[b].expiry) {
+            let expiry = quotes[slice[0]].expiry;
+            let Ok(rate) = market.continuous_rate(expiry) else {
+                continue;
+            };
+            let discount = (-rate * expiry).exp();
+            for pair in slice.windows(2) {
+                let (low, high) = (&quotes[pair[0]], &quotes[pair[1]]);
+                let tolerance = slack * high.strike;
+                let (dear, cheap) = match option_type {
+                    OptionType::Call => (low, high),
+                    OptionType::Put => (high, low),
+                };
+                if cheap.bid > dear.ask + tolerance {
+                    breaks.push((QuoteFlag::Monotonicity, pair.to_vec()));
+                } else if dear.bid - cheap.ask > discount * (high.strike - low.strike) + tolerance {
+                    breaks.push((QuoteFlag::Slope, pair.to_vec()));
+                }
+            }
+            for triple in slice.windows(3) {
+                let [low, middle, high] = [0, 1, 2].map(|k| &quotes[triple[k]]);
+                let span = high.strike - low.strike;
+                if span <= slack * high.strike {
+                    continue;
+                }
+                let weight = (high.strike - middle.strike) / span;
+                let wings = weight * low.ask + (T::one() - weight) * high.ask;
+                if wings + slack * high.strike < middle.bid {
+                    breaks.push((QuoteFlag::Convexity, triple.to_vec()));
+                }
+            }
+        }
+    }
+    breaks.sort_by_key(|&(flag, _)| flag as u8);
+    for (flag, indices) in breaks {
+        for i in indices {
+            vols[i].flag.get_or_insert(flag);
+        }
+    }
+}
+
+fn solve<T: Float>(quote: &ChainQuote<T>, market: &Market<T>) -> ChainVol<T> {
+    let unsolved = |flag| ChainVol {
+        bid: None,
+        mid: None,
+        ask: None,
+        flag: Some(flag),
+    };
+    if !(quote.bid.is_finite() && quote.ask.is_finite()) {
+        return unsolved(QuoteFlag::Invalid);
+    }
+    if quote.bid > quote.ask {
+        return unsolved(QuoteFlag::Crossed);
+    }
+    let spec = OptionSpec::european(quote.option_type, Strike(quote.strike), quote.expiry);
+    let vol = |price: T| rational_implied_vol(price, &spec, market);
+    let mid = (quote.bid + quote.ask) * cast(0.5);
+    match vol(mid) {
+        Ok(mid) => ChainVol {
+            bid: vol(quote.bid).ok(),
+            mid: Some(mid),
+            ask: vol(quote.ask).ok(),
+            flag: None,
+        },
+        Err(OptOpsError::InvalidParameter { name: "price", .. }) => {
+            unsolved(QuoteFlag::OutOfBounds)
+        }
+        Err(_) => unsolved(QuoteFlag::Invalid),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analytic::black_scholes_price;
+    use crate::units::{Rate, Spot, Vol};
+
+    fn market() -> Market {
+        Market::new(Spot(100.0), Rate(0.05), Vol(0.25)).with_dividend_yield(Rate(0.01))
+    }
+
+    /// Black-Scholes mids a cent either side of the model price.
+    fn chain(option_type: OptionType, expiry: f64, strikes: &[f64]) -> Vec<ChainQuote> {
+        strikes
+            .iter()
+            .map(|&strike| {
+                let spec = OptionSpec::european(option_type, Strike(strike), expiry);
+                let price = black_scholes_price(&spec, &market()).unwrap();
+                ChainQuote::new(option_type, strike, expiry, price - 0.01, price + 0.01)
+            })
+            .collect()
+    }
+
+    fn flags(quotes: &[ChainQuote]) -> Vec<Option<QuoteFlag>> {
+        chain_implied_vols(quotes, &market())
+            .iter()
+            .map(|vol| vol.flag)
+            .collect()
+    }
+
+    /// Model prices invert to the model's vol and break no bound, however
+    /// the quotes are ordered.
+    #[test]
+    fn model_prices_are_unflagged() {
+        let strikes = [60.0, 80.0, 95.0, 100.0, 105.0, 120.0, 150.0];
+        let mut quotes = chain(OptionType::Call, 0.5, &strikes);
+        quotes.extend(chain(OptionType::Put, 2.0, &strikes));
+        quotes.extend(chain(OptionType::Call, 2.0, &strikes));
+        quotes.reverse();
+        for (quote, vol) in quotes.iter().zip(chain_implied_vols(&quotes, &market())) {
+            assert_eq!(vol.flag, None, "{quote:?}");
+            let mid = vol.mid.unwrap();
+            assert!((mid - 0.25).abs() < 1e-6, "{quote:?}: {mid}");
+        }
+    }
+
+    /// Each cross-strike bound flags the quotes that break it and leaves
+    /// their vols in place, and crossed quotes are left out of the checks.
+    #[test]
+    fn cross_strike_arbitrage_is_flagged() {
+        let strikes = [90.0, 100.0, 110.0, 120.0];
+
+        let mut calls = chain(OptionType::Call, 1.0, &strikes);
+        calls[2].bid = calls[1].ask + 0.5;
+        calls[2].ask = calls[2].bid + 0.02;
+        let result = flags(&calls);
+        assert_eq!(result[1], Some(QuoteFlag::Monotonicity));
+        assert_eq!(result[2], Some(QuoteFlag::Monotonicity));
+
+        let mut puts = chain(OptionType::Put, 1.0, &strikes);
+        let discount = (-0.05f64).exp();
+        puts[3].bid = puts[2].ask + 10.0 * discount + 0.5;
+        puts[3].ask = puts[3].bid + 0.02;
+        let result = flags(&puts);
+        assert_eq!(&result[2..], [Some(QuoteFlag::Slope); 2]);
+        assert_eq!(&result[..2], [None, None]);
+
+        let mut calls = chain(OptionType::Call, 1.0, &strikes);
+        calls[1].bid += 1.0;
+        calls[1].ask += 1.0;
+        let vols = chain_implied_vols(&calls, &market());
+        let result: Vec<_> = vols.iter().map(|vol| vol.flag).collect();
+        assert_eq!(&result[..3], [Some(QuoteFlag::Convexity); 3]);
+        assert!(vols[1].mid.unwrap() > 0.25);
+
+        let mut calls = chain(OptionType::Call, 1.0, &strikes);
+        calls[1].bid = calls[1].ask + 5.0;
+        assert_eq!(flags(&calls), [None, Some(QuoteFlag::Crossed), None, None]);
+    }
+}
@@ -0,0 +1,177 @@
+//! Closed-form prices of options on options.
+
+use super::{bivariate_norm_cdf, d1_d2, norm_cdf};
+use crate::core;
+use crate::error::{OptOpsError, Result};
+use crate::float::{cast, Float};
+use crate::instrument::{CompoundSpec, OptionType};
+use crate::market::Market;
+use crate::validate;
+
+/// Newton iterations allowed when solving for the critical spot.
+const MAX_ITERATIONS: usize = 100;
+
+/// Geske (1979) price of a compound option under Black-Scholes dynamics,
+/// with the underlying option treated as European. The compound is
+/// exercised at its expiry `T1` when the spot is beyond the critical `S*`
+/// at which the underlying is worth the compound strike, so the price
+/// combines bivariate normal probabilities of finishing beyond `S*` at
+/// `T1` and beyond the underlying strike at `T2`, correlated by
+/// `sqrt(T1 / T2)`. A put underlying worth less than the compound strike
+/// at any spot leaves calls on it worthless and puts on it certain to be
+/// exercised.
+///
+/// One continuously compounded rate, to the underlying's expiry, and the
+/// vol to that expiry apply throughout.
+pub fn geske<T: Float>(spec: &CompoundSpec<T>, market: &Market<T>) -> Result<T> {
+    let engine = "Geske";
+    let underlying = &spec.underlying;
+    if underlying.barrier.is_some() {
+        return Err(OptOpsError::Unsupported {
+            engine,
+            reason: "barrier options",
+        });
+    }
+    if !market.dividends.is_empty() {
+        return Err(OptOpsError::Unsupported {
+            engine,
+            reason: "discrete dividends",
+        });
+    }
+    market.validate()?;
+    let strike = validate::positive("strike", spec.strike)?;
+    let inner_strike = validate::positive("strike", underlying.strike)?;
+    let t1 = validate::positive("expiry", spec.expiry)?;
+    let t2 = underlying.expiry;
+    let remaining = validate::positive("time between expiries", t2 - t1)?;
+    let rate = market.continuous_rate(t2)?;
+    let (spot, vol) = (market.spot, market.effective_vol(t2));
+    let q = market.carry_yield(rate, vol);
+    let inner = |s: T| {
+        core::black_scholes(
+            underlying.option_type,
+            s,
+            inner_strike,
+            rate,
+            q,
+            vol,
+            remaining,
+        )
+    };
+    let discount = |t: T| (-rate * t).exp();
+
+    // A put is worth at most its discounted strike, reached as the spot
+    // falls to zero.
+    if underlying.option_type == OptionType::Put && inner_strike * discount(remaining) <= strike {
+        return Ok(match spec.option_type {
+            OptionType::Call => T::zero(),
+            OptionType::Put => {
+                let put_type = OptionType::Put;
+                let put = core::black_scholes(put_type, spot, inner_strike, rate, q, vol, t2);
+                strike * discount(t1) - put
+            }
+        });
+    }
+    let delta = |s: T| {
+        let d1 = d1_d2(s, inner_strike, rate, q, vol, remaining).0;
+        let carried = (-q * remaining).exp();
+        match underlying.option_type {
+            OptionType::Call => carried * norm_cdf(d1),
+            OptionType::Put => -carried * norm_cdf(-d1),
+        }
+    };
+    let critical = critical_spot(engine, strike, inner, delta, inner_strike)?;
+
+    let (y1, y2) = d1_d2(spot, critical, rate, q, vol, t1);
+    let (z1, z2) = d1_d2(spot, inner_strike, rate, q, vol, t2);
+    let rho = (t1 / t2).sqrt();
+    let m = bivariate_norm_cdf;
+    let asset = spot * (-q * t2).exp();
+    let cash = inner_strike * discount(t2);
+    let premium = strike * discount(t1);
+    Ok(match (spec.option_type, underlying.option_type) {
+        (OptionType::Call, OptionType::Call) => {
+            asset * m(z1, y1, rho) - cash * m(z2, y2, rho) - premium * norm_cdf(y2)
+        }
+        (OptionType::Put, OptionType::Call) => {
+            cash * m(z2, -y2, -rho) - asset * m(z1, -y1, -rho) + premium * norm_cdf(-y2)
+        }
+        (OptionType::Call, OptionType::Put) => {
+            cash * m(-z2, -y2, rho) - asset * m(-z1, -y1, rho) - premium * norm_cdf(-y2)
+        }
+        (OptionType::Put, OptionType::Put) => {
+            asset * m(-z1, y1, -rho) - cash * m(-z2, y2, -rho) + premium * norm_cdf(y2)
+        }
+    })
+}
+
+/// Spot at which `value`, monotone and convex with slope `delta`, equals
+/// `target`, by Newton's method in the log of the spot from `seed`.
+fn critical_spot<T, V, D>(engine: &'static str, target: T, value: V, delta: D, seed: T) -> Result<T>
+where
+    T: Float,
+    V: Fn(T) -> T,
+    D: Fn(T) -> T,
+{
+    let tolerance = cast::<T>(1e-12) * target.max(T::one());
+    let mut log_spot = seed.ln();
+    for _ in 0..MAX_ITERATIONS {
+        let spot = log_spot.exp();
+        let gap = value(spot) - target;
+        if gap.abs() < tolerance {
+            return Ok(spot);
+        }
+        // Cap each step at a doubling or halving, where the slope vanishes.
+        let cap = cast::<T>(std::f64::consts::LN_2);
+        log_spot = log_spot - (gap / (delta(spot) * spot)).max(-cap).min(cap);
+    }
+    Err(OptOpsError::NonConvergence {
+        solver: engine,
+        iterations: MAX_ITERATIONS,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::instrument::OptionSpec;
+    use crate::units::{Rate, Spot, Strike, Vol};
+
+    /// Haug (2007), section 4.5: a put on a call, S = 500, underlying
+    /// strike 520 to six months, compound strike 50 to three months,
+    /// r = 8%, q = 3% and vol 35%.
+    #[test]
+    fn matches_haugs_put_on_call() {
+        let market: Market =
+            Market::new(Spot(500.0), Rate(0.08), Vol(0.35)).with_dividend_yield(Rate(0.03));
+        let underlying = OptionSpec::european(OptionType::Call, Strike(520.0), 0.5);
+        let spec = CompoundSpec::new(OptionType::Put, Strike(50.0), 0.25, underlying);
+        let price = geske(&spec, &market).unwrap();
+        assert!((price - 21.1965).abs() < 2e-4, "{price}");
+    }
+
+    /// A call on an option and a put on it differ by the underlying less
+    /// the discounted compound strike.
+    #[test]
+    fn satisfies_compound_parity() {
+        let market: Market =
+            Market::new(Spot(100.0), Rate(0.05), Vol(0.3)).with_dividend_yield(Rate(0.02));
+        for option_type in [OptionType::Call, OptionType::Put] {
+            let underlying = OptionSpec::european(option_type, Strike(100.0), 1.0);
+            let price = |compound| {
+                geske(
+                    &CompoundSpec::new(compound, Strike(6.0), 0.4, underlying),
+                    &market,
+                )
+                .unwrap()
+            };
+            let value = crate::analytic::black_scholes_price(&underlying, &market).unwrap();
+            let parity = value - 6.0 * (-0.05f64 * 0.4).exp();
+            let gap = price(OptionType::Call) - price(OptionType::Put);
+            assert!(
+                (gap - parity).abs() < 1e-8,
+                "{option_type:?}: {gap} vs {parity}"
+            );
+        }
+    }
+}
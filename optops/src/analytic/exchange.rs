@@ -0,0 +1,28 @@
+//! Closed-form price of the option to exchange one asset for another.
+
+use super::norm_cdf;
+use crate::error::Result;
+use crate::float::{cast, Float};
+use crate::market::AssetPair;
+use crate::validate;
+
+/// Margrabe (1978) price of the European option to receive
+/// `quantities[0]` units of the first asset for `quantities[1]` units of
+/// the second at `expiry`, `max(Q1 S1 - Q2 S2, 0)`. The second asset is the
+/// numeraire, so the rate drops out: it is a Black-Scholes call on the
+/// ratio `S1 / S2` with yields `q1` and `q2` and the vol of the ratio.
+pub fn margrabe<T: Float>(pair: &AssetPair<T>, quantities: [T; 2], expiry: T) -> Result<T> {
+    pair.validate()?;
+    let expiry = validate::positive("expiry", expiry)?;
+    let receive = validate::positive("quantity", quantities[0])? * pair.first.spot;
+    let deliver = validate::positive("quantity", quantities[1])? * pair.second.spot;
+    let receive = receive * (-pair.first.dividend_yield * expiry).exp();
+    let deliver = deliver * (-pair.second.dividend_yield * expiry).exp();
+    let sigma_sqrt = pair.ratio_vol() * expiry.sqrt();
+    if sigma_sqrt == T::zero() {
+        // Perfectly correlated assets with equal vols move in lockstep.
+        return Ok((receive - deliver).max(T::zero()));
+    }
+    let d1 = (receive / deliver).ln() / sigma_sqrt + cast::<T>(0.5) * sigma_sqrt;
+    Ok(receive * norm_cdf(d1) - deliver * norm_cdf(d1 - sigma_sqrt))
+}
@@ -0,0 +1,67 @@
+//! Closed-form prices of forward-start options and the cliquets built from
+//! them.
+
+use crate::core;
+use crate::error::{Domain, OptOpsError, Result};
+use crate::float::{to_f64, Float};
+use crate::instrument::{ForwardStartSpec, OptionType};
+use crate::market::Market;
+use crate::validate;
+
+/// Rubinstein (1990) price of a forward-start option: the strike scales
+/// with the spot at `start`, so the option is then worth that spot times a
+/// Black-Scholes option on a unit spot struck at `moneyness`, and today
+/// `S e^{-q t1}` of those. Rates and vols over the forward period come from
+/// the market's curves when it has them.
+pub fn forward_start<T: Float>(spec: &ForwardStartSpec<T>, market: &Market<T>) -> Result<T> {
+    if !market.dividends.is_empty() {
+        return Err(OptOpsError::Unsupported {
+            engine: "forward start",
+            reason: "discrete dividends",
+        });
+    }
+    market.validate()?;
+    let moneyness = validate::positive("moneyness", spec.moneyness)?;
+    let start = validate::check("start", spec.start, Domain::NonNegative)?;
+    let expiry = validate::check("expiry", spec.expiry, Domain::Above(to_f64(start)))?;
+    let tenor = expiry - start;
+    // Rate and total variance accumulated to `t`, both zero today.
+    let accrued = |t: T| -> Result<(T, T)> {
+        if t == T::zero() {
+            return Ok((T::zero(), T::zero()));
+        }
+        let vol = market.effective_vol(t);
+        Ok((market.continuous_rate(t)? * t, vol * vol * t))
+    };
+    let (rate_start, variance_start) = accrued(start)?;
+    let (rate_end, variance_end) = accrued(expiry)?;
+    let rate = (rate_end - rate_start) / tenor;
+    let variance = validate::positive("forward variance", variance_end - variance_start)?;
+    let vol = (variance / tenor).sqrt();
+    let q = market.carry_yield(rate, vol);
+    let unit = core::black_scholes(spec.option_type, T::one(), moneyness, rate, q, vol, tenor);
+    Ok(market.spot * (-q * start).exp() * unit)
+}
+
+/// A cliquet of forward-start options: one per period between consecutive
+/// `resets`, the first from today, each struck at `moneyness` times the
+/// spot at the start of its period. Without caps or floors on the periods
+/// it is the sum of their `forward_start` prices.
+pub fn cliquet<T: Float>(
+    option_type: OptionType,
+    moneyness: T,
+    resets: &[T],
+    market: &Market<T>,
+) -> Result<T> {
+    if resets.is_empty() {
+        return Err(OptOpsError::MissingParameter("resets"));
+    }
+    let mut start = T::zero();
+    let mut total = T::zero();
+    for &end in resets {
+        let period = ForwardStartSpec::new(option_type, moneyness, start, end);
+        total = total + forward_start(&period, market)?;
+        start = end;
+    }
+    Ok(total)
+}
@@ -0,0 +1,394 @@
+//! Black-Scholes implied vols of European prices.
+
+use super::norm_pdf;
+use crate::core;
+use crate::error::{Domain, OptOpsError, Result};
+use crate::float::{cast, to_f64, Float};
+use crate::instrument::{OptionSpec, OptionType};
+use crate::market::Market;
+use crate::validate;
+use serde::{Deserialize, Serialize};
+
+/// Doublings of the upper vol allowed while bracketing the root for Brent.
+const MAX_DOUBLINGS: usize = 64;
+
+/// How hard `solve` works: Newton's method from Corrado and Miller's
+/// closed-form guess, then Brent's method on a bracket if Newton leaves
+/// positive vols or runs out of iterations.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ImpliedVolSolver {
+    /// Change in vol between iterates, or width of Brent's bracket, at
+    /// which the search stops.
+    pub tolerance: f64,
+    /// Iterations allowed to each of Newton's and Brent's methods.
+    pub max_iterations: usize,
+}
+
+impl Default for ImpliedVolSolver {
+    fn default() -> Self {
+        ImpliedVolSolver {
+            tolerance: 1e-12,
+            max_iterations: 100,
+        }
+    }
+}
+
+impl ImpliedVolSolver {
+    pub fn with_tolerance(self, tolerance: f64) -> Self {
+        ImpliedVolSolver { tolerance, ..self }
+    }
+
+    pub fn with_max_iterations(self, max_iterations: usize) -> Self {
+        ImpliedVolSolver {
+            max_iterations,
+            ..self
+        }
+    }
+
+    /// Flat vol at which `black_scholes_price` of `spec` matches `price`,
+    /// which replaces the market's vol, vol curve or surface. Prices outside
+    /// the no-arbitrage range, at least the intrinsic value against the
+    /// discounted strike and below the carried spot (calls) or discounted
+    /// strike (puts), are rejected as out of range. A price at the lower
+    /// bound implies zero vol.
+    ///
+    /// A quanto's price need not rise with vol, so it has no single
+    /// implied vol and is rejected, as are barrier contracts.
+    pub fn solve<T: Float>(&self, price: T, spec: &OptionSpec<T>, market: &Market<T>) -> Result<T> {
+        let tolerance = cast::<T>(validate::positive("tolerance", self.tolerance)?);
+        validate::check(
+            "max_iterations",
+            self.max_iterations as f64,
+            Domain::AtLeast(1.0),
+        )?;
+        let Quote {
+            option_type,
+            spot,
+            strike,
+            rate,
+            dividend_yield: q,
+            expiry,
+            carried,
+            discounted,
+            price,
+            lower,
+        } = Quote::new("implied vol", price, spec, market)?;
+        if price == lower {
+            return Ok(T::zero());
+        }
+
+        let gap =
+            |vol: T| core::black_scholes(option_type, spot, strike, rate, q, vol, expiry) - price;
+        let sqrt_t = expiry.sqrt();
+        let vega = |vol: T| {
+            let (d1, _) = core::d1_d2(spot, strike, rate, q, vol, expiry);
+            carried * norm_pdf(d1) * sqrt_t
+        };
+
+        let seed = corrado_miller(option_type, price, carried, discounted) / sqrt_t;
+        let mut vol = if seed.is_finite() && seed > T::zero() {
+            seed
+        } else {
+            cast(0.2)
+        };
+        for _ in 0..self.max_iterations {
+            let next = vol - gap(vol) / vega(vol);
+            if !next.is_finite() || next <= T::zero() {
+                break;
+            }
+            if (next - vol).abs() <= tolerance {
+                return Ok(next);
+            }
+            vol = next;
+        }
+
+        // Zero vol prices at the lower bound and the price rises to the upper
+        // one without reaching it, so doubling brackets the root.
+        let mut hi = vol.max(T::one());
+        let mut at_hi = gap(hi);
+        let mut doublings = 0;
+        while at_hi < T::zero() {
+            if doublings == MAX_DOUBLINGS {
+                return Err(bracketing_failure());
+            }
+            hi = hi + hi;
+            at_hi = gap(hi);
+            doublings += 1;
+        }
+        let at_zero = lower - price;
+        let gap = |vol: T| Ok(gap(vol));
+        brent(
+            gap,
+            (T::zero(), at_zero),
+            (hi, at_hi),
+            tolerance,
+            self.max_iterations,
+        )
+    }
+
+    /// Vol at which `value`, a price increasing in vol, matches `price`,
+    /// for pricers with no closed-form vega such as lattices and American
+    /// approximations. The bracket grows from `guess` by halving or
+    /// doubling and Brent's method closes it, so `value` need only be
+    /// continuous. A price below what the smallest vol tried gives is
+    /// rejected as out of range.
+    pub fn invert<T, F>(&self, price: T, guess: T, mut value: F) -> Result<T>
+    where
+        T: Float,
+        F: FnMut(T) -> Result<T>,
+    {
+        let tolerance = cast::<T>(validate::positive("tolerance", self.tolerance)?);
+        validate::check(
+            "max_iterations",
+            self.max_iterations as f64,
+            Domain::AtLeast(1.0),
+        )?;
+        let price = validate::finite("price", price)?;
+        let mut gap = |vol: T| Ok(value(vol)? - price);
+        let (mut lo, mut hi) = (validate::positive("guess", guess)?, guess);
+        let mut at_lo = gap(lo)?;
+        let mut halvings = 0;
+        while at_lo > T::zero() {
+            if halvings == MAX_DOUBLINGS {
+                return Err(OptOpsError::InvalidParameter {
+                    name: "price",
+                    value: to_f64(price),
+                    domain: Domain::AtLeast(to_f64(price + at_lo)),
+                });
+            }
+            hi = lo;
+            lo = lo / cast(2.0);
+            at_lo = gap(lo)?;
+            halvings += 1;
+        }
+        if at_lo == T::zero() {
+            return Ok(lo);
+        }
+        let mut at_hi = if hi > lo { gap(hi)? } else { at_lo };
+        let mut doublings = 0;
+        while at_hi < T::zero() {
+            if doublings == MAX_DOUBLINGS {
+                return Err(bracketing_failure());
+            }
+            (lo, at_lo) = (hi, at_hi);
+            hi = hi + hi;
+            at_hi = gap(hi)?;
+            doublings += 1;
+        }
+        brent(
+            gap,
+            (lo, at_lo),
+            (hi, at_hi),
+            tolerance,
+            self.max_iterations,
+        )
+    }
+}
+
+/// A European price checked against its no-arbitrage bounds, with the
+/// Black-Scholes inputs it is inverted under.
+pub(super) struct Quote<T> {
+    pub(super) option_type: OptionType,
+    pub(super) spot: T,
+    pub(super) strike: T,
+    pub(super) rate: T,
+    pub(super) dividend_yield: T,
+    pub(super) expiry: T,
+    /// Spot carried at the dividend yield to expiry and discounted back.
+    pub(super) carried: T,
+    /// Discounted strike.
+    pub(super) discounted: T,
+    pub(super) price: T,
+    /// Intrinsic value against the discounted strike, the zero-vol price.
+    pub(super) lower: T,
+}
+
+impl<T: Float> Quote<T> {
+    pub(super) fn new(
+        engine: &'static str,
+        price: T,
+        spec: &OptionSpec<T>,
+        market: &Market<T>,
+    ) -> Result<Self> {
+        if spec.barrier.is_some() {
+            return Err(OptOpsError::Unsupported {
+                engine,
+                reason: "barrier options",
+            });
+        }
+        if market.quanto.is_some() {
+            return Err(OptOpsError::Unsupported {
+                engine,
+                reason: "quanto prices, which need not rise with vol",
+            });
+        }
+        market.validate()?;
+        let strike = validate::positive("strike", spec.strike)?;
+        let expiry = validate::positive("expiry", spec.expiry)?;
+        let rate = market.continuous_rate(expiry)?;
+        let spot = market.escrowed_spot(expiry)? * market.dividends.retained_fraction(expiry);
+        let dividend_yield = market.dividend_yield;
+        let carried = spot * (-dividend_yield * expiry).exp();
+        let discounted = strike * (-rate * expiry).exp();
+
+        let (lower, upper) = match spec.option_type {
+            OptionType::Call => ((carried - discounted).max(T::zero()), carried),
+            OptionType::Put => ((discounted - carried).max(T::zero()), discounted),
+        };
+        let bounds = Domain::HalfOpen(to_f64(lower), to_f64(upper));
+        let price = validate::check("price", price, bounds)?;
+        Ok(Quote {
+            option_type: spec.option_type,
+            spot,
+            strike,
+            rate,
+            dividend_yield,
+            expiry,
+            carried,
+            discounted,
+            price,
+            lower,
+        })
+    }
+}
+
+/// `ImpliedVolSolver::solve` with the default tolerance and iterations.
+pub fn implied_vol<T: Float>(price: T, spec: &OptionSpec<T>, market: &Market<T>) -> Result<T> {
+    ImpliedVolSolver::default().solve(price, spec, market)
+}
+
+/// Corrado and Miller's (1996) quadratic approximation of the total vol
+/// `σ√T`, from the carried spot and discounted strike; puts go through
+/// put-call parity.
+fn corrado_miller<T: Float>(option_type: OptionType, price: T, carried: T, discounted: T) -> T {
+    let call = match option_type {
+        OptionType::Call => price,
+        OptionType::Put => price + carried - discounted,
+    };
+    let pi = cast::<T>(std::f64::consts::PI);
+    let half_gap = (carried - discounted) / cast(2.0);
+    let excess = call - half_gap;
+    let root = (excess * excess - half_gap * half_gap * cast(4.0) / pi)
+        .max(T::zero())
+        .sqrt();
+    (pi + pi).sqrt() / (carried + discounted) * (excess + root)
+}
+
+/// The error when doubling the vol never lifts the price to the quote.
+fn bracketing_failure() -> OptOpsError {
+    OptOpsError::NonConvergence {
+        solver: "implied vol bracketing",
+        iterations: MAX_DOUBLINGS,
+    }
+}
+
+/// Brent's method for the root of `f` between `lo` and `hi`, each given
+/// with its value, where `f` changes sign.
+fn brent<T, F>(mut f: F, lo: (T, T), hi: (T, T), tolerance: T, max_iterations: usize) -> Result<T>
+where
+    T: Float,
+    F: FnMut(T) -> Result<T>,
+{
+    let two = cast::<T>(2.0);
+    let ((mut a, mut fa), (mut b, mut fb)) = (lo, hi);
+    let (mut c, mut fc) = (a, fa);
+    let (mut d, mut e) = (b - a, b - a);
+    for _ in 0..max_iterations {
+        if (fb > T::zero()) == (fc > T::zero()) {
+            c = a;
+            fc = fa;
+            d = b - a;
+            e = d;
+        }
+        if fc.abs() < fb.abs() {
+            a = b;
+            b = c;
+            c = a;
+            fa = fb;
+            fb = fc;
+            fc = fa;
+        }
+        let slack = two * T::epsilon() * b.abs() + tolerance / two;
+        let mid = (c - b) / two;
+        if mid.abs() <= slack || fb == T::zero() {
+            return Ok(b);
+        }
+        if e.abs() >= slack && fa.abs() > fb.abs() {
+            // Inverse quadratic interpolation, or the secant when only two
+            // distinct points are known.
+            let s = fb / fa;
+            let (mut p, mut q) = if a == c {
+                (two * mid * s, T::one() - s)
+            } else {
+                let (qa, r) = (fa / fc, fb / fc);
+                (
+                    s * (two * mid * qa * (qa - r) - (b - a) * (r - T::one())),
+                    (qa - T::one()) * (r - T::one()) * (s - T::one()),
+                )
+            };
+            if p > T::zero() {
+                q = -q;
+            }
+            p = p.abs();
+            let bound = (cast::<T>(3.0) * mid * q - (slack * q).abs()).min((e * q).abs());
+            if two * p < bound {
+                e = d;
+                d = p / q;
+            } else {
+                d = mid;
+                e = d;
+            }
+        } else {
+            d = mid;
+            e = d;
+        }
+        a = b;
+        fa = fb;
+        b = if d.abs() > slack {
+            b + d
+        } else {
+            b + slack * mid.signum()
+        };
+        fb = f(b)?;
+    }
+    Err(OptOpsError::NonConvergence {
+        solver: "Brent implied vol",
+        iterations: max_iterations,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analytic::black_scholes_price;
+    use crate::units::{Rate, Spot, Strike, Vol};
+
+    /// A price at the lower bound is zero vol; one at the upper bound would
+    /// need an infinite vol and is rejected against the half-open range.
+    #[test]
+    fn prices_are_checked_against_a_half_open_range() {
+        let market: Market =
+            Market::new(Spot(100.0), Rate(0.05), Vol(0.3)).with_dividend_yield(Rate(0.02));
+        let solver = ImpliedVolSolver::default();
+        for option_type in [OptionType::Call, OptionType::Put] {
+            let spec = OptionSpec::european(option_type, Strike(90.0), 1.0);
+            let price = black_scholes_price(&spec, &market).unwrap();
+            let vol = solver.solve(price, &spec, &market).unwrap();
+            assert!((vol - 0.3).abs() < 1e-10, "{option_type:?}: {vol}");
+
+            let carried = 100.0 * (-0.02f64).exp();
+            let discounted = 90.0 * (-0.05f64).exp();
+            let (lower, upper) = match option_type {
+                OptionType::Call => (carried - discounted, carried),
+                OptionType::Put => (0.0, discounted),
+            };
+            assert_eq!(solver.solve(lower, &spec, &market).unwrap(), 0.0);
+            match solver.solve(upper, &spec, &market) {
+                Err(OptOpsError::InvalidParameter { name, domain, .. }) => {
+                    assert_eq!((name, domain), ("price", Domain::HalfOpen(lower, upper)))
+                }
+                other => panic!("{option_type:?}: {other:?}"),
+            }
+        }
+    }
+}
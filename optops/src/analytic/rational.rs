@@ -0,0 +1,580 @@
+//! Jäckel's "Let's Be Rational" inversion of the Black price: a transformed
+//! rational guess and two Householder steps reach machine precision
+//! everywhere, so whole chains invert without a tolerance loop.
+
+use super::implied::Quote;
+use crate::core::{erfcx, inverse_norm_cdf, norm_cdf, norm_pdf};
+use crate::error::Result;
+use crate::float::{cast, Float};
+use crate::instrument::{OptionSpec, OptionType};
+use crate::market::Market;
+
+/// Householder steps taken from the guess.
+const ITERATIONS: usize = 2;
+
+/// `h = x / s` below which the normalised price comes from its asymptotic
+/// expansion in `1 / h`.
+const ASYMPTOTIC_THRESHOLD: f64 = -10.0;
+
+/// Half the total vol, `t = s / 2`, below which the normalised price comes
+/// from its Taylor series in `t`.
+const SMALL_T_THRESHOLD: f64 = 0.21;
+
+/// Implied vol of `spec` at `price` by Jäckel's (2015) algorithm, the
+/// same quantity `implied_vol` searches for. The normalised price is
+/// split at the total vol of its inflection point and at the tangents
+/// there into four branches; each gets a rational cubic guess, in the
+/// outer two after mapping the price through an asymptotic form of it,
+/// and an objective function close enough to linear in total vol that
+/// two third-order Householder steps leave only round-off.
+///
+/// Bounds and unsupported inputs are checked exactly as `implied_vol`
+/// checks them.
+pub fn rational_implied_vol<T: Float>(
+    price: T,
+    spec: &OptionSpec<T>,
+    market: &Market<T>,
+) -> Result<T> {
+    let Quote {
+        option_type,
+        expiry,
+        carried,
+        discounted,
+        price,
+        lower,
+        ..
+    } = Quote::new("rational implied vol", price, spec, market)?;
+    if price == lower {
+        return Ok(T::zero());
+    }
+    let mut theta: T = match option_type {
+        OptionType::Call => T::one(),
+        OptionType::Put => -T::one(),
+    };
+    let mut x = (carried / discounted).ln();
+    // An in-the-money option less its intrinsic value is the out-of-the-
+    // money one on the other side, by put-call parity.
+    let mut time_value = price;
+    if theta * x > T::zero() {
+        time_value = (price - lower).max(T::zero());
+        theta = -theta;
+    }
+    // `b(x, s)` for a put is `b(-x, s)` for a call.
+    if theta < T::zero() {
+        x = -x;
+    }
+    let beta = time_value / (carried.sqrt() * discounted.sqrt());
+    Ok(normalised_implied_vol(beta, x) / expiry.sqrt())
+}
+
+/// Total vol `s` at which the out-of-the-money normalised call price
+/// `b(x, s)`, `x <= 0`, is `beta`.
+fn normalised_implied_vol<T: Float>(beta: T, x: T) -> T {
+    let (zero, one, half) = (T::zero(), T::one(), cast::<T>(0.5));
+    let b_max = (half * x).exp();
+    if beta <= zero {
+        return zero;
+    }
+    if beta >= b_max {
+        return T::infinity();
+    }
+    let s_c = (x.abs() + x.abs()).sqrt();
+    let b_c = normalised_black(x, s_c);
+    let v_c = normalised_vega(x, s_c);
+    let mut s_left = T::min_positive_value();
+    let mut s_right = T::max_value();
+    let s;
+
+    if beta < b_c {
+        let s_l = s_c - b_c / v_c;
+        let b_l = normalised_black(x, s_l);
+        if beta < b_l {
+            let (f_l, df_l, d2f_l) = lower_map(x, s_l);
+            let r = convex_control_right(zero, b_l, zero, f_l, one, df_l, d2f_l, true);
+            let mut f = rational_cubic(beta, zero, b_l, zero, f_l, one, df_l, r);
+            if f.is_nan() || f <= zero {
+                // Round-off at extreme `|x|`: fall back on the quadratic
+                // through `f(0) = 0` with unit slope and `f(b_l)`.
+                let t = beta / b_l;
+                f = (f_l * t + b_l * (one - t)) * t;
+            }
+            let guess = inverse_lower_map(x, f);
+            // `1 / ln b(s) - 1 / ln beta`, close to linear where the price
+            // is exponentially small.
+            let ln_beta = beta.ln();
+            let bracket = (s_left, s_l);
+            return householder(x, guess, bracket, true, beta, |b, vega, curvature, bend| {
+                if b <= zero {
+                    return None;
+                }
+                let ln_b = b.ln();
+                let vega_over_b = vega / b;
+                let newton = (ln_beta - ln_b) * ln_b / ln_beta / vega_over_b;
+                let twist = one + (one + one) / ln_b;
+                let halley = curvature - vega_over_b * twist;
+                let third = bend
+                    + cast::<T>(2.0)
+                        * vega_over_b
+                        * vega_over_b
+                        * (one + cast::<T>(3.0) / ln_b * (one + one / ln_b))
+                    - cast::<T>(3.0) * curvature * vega_over_b * twist;
+                Some((newton, halley, third))
+            });
+        }
+        let v_l = normalised_vega(x, s_l);
+        let r = convex_control_right(b_l, b_c, s_l, s_c, one / v_l, one / v_c, zero, false);
+        s = rational_cubic(beta, b_l, b_c, s_l, s_c, one / v_l, one / v_c, r);
+        s_left = s_l;
+        s_right = s_c;
+    } else {
+        let s_h = if v_c > T::min_positive_value() {
+            s_c + (b_max - b_c) / v_c
+        } else {
+            s_c
+        };
+        let b_h = normalised_black(x, s_h);
+        if beta <= b_h {
+            let v_h = normalised_vega(x, s_h);
+            let r = convex_control_left(b_c, b_h, s_c, s_h, one / v_c, one / v_h, zero, false);
+            s = rational_cubic(beta, b_c, b_h, s_c, s_h, one / v_c, one / v_h, r);
+            s_left = s_c;
+            s_right = s_h;
+        } else {
+            let (f_h, df_h, d2f_h) = upper_map(x, s_h);
+            let mut f = -one;
+            let limit = T::max_value().sqrt();
+            if d2f_h > -limit && d2f_h < limit {
+                let r = convex_control_left(b_h, b_max, f_h, zero, df_h, -half, d2f_h, true);
+                f = rational_cubic(beta, b_h, b_max, f_h, zero, df_h, -half, r);
+            }
+            if f <= zero {
+                let h = b_max - b_h;
+                let t = (beta - b_h) / h;
+                f = (f_h * (one - t) + half * h * t) * (one - t);
+            }
+            let guess = -(inverse_norm_cdf(f) + inverse_norm_cdf(f));
+            if beta > half * b_max {
+                // `ln(b_max - beta) - ln(b_max - b(s))`, close to linear as
+                // the price approaches its ceiling.
+                let gap = (b_max - beta).ln();
+                let bracket = (s_h, s_right);
+                return householder(x, guess, bracket, true, beta, |b, vega, curvature, bend| {
+                    if b >= b_max || vega <= T::min_positive_value() {
+                        return None;
+                    }
+                    let room = b_max - b;
+                    let slope = vega / room;
+                    let newton = (room.ln() - gap) / slope;
+                    let halley = curvature + slope;
+                    let third = bend + slope * (slope + slope + cast::<T>(3.0) * curvature);
+                    Some((newton, halley, third))
+                });
+            }
+            s = guess;
+            s_left = s_h;
+        }
+    }
+
+    // `b(s) - beta` between the tangents, where `b` is nearly linear.
+    householder(
+        x,
+        s,
+        (s_left, s_right),
+        false,
+        beta,
+        |b, vega, curvature, bend| Some(((beta - b) / vega, curvature, bend)),
+    )
+}
+
+/// Takes `ITERATIONS` third-order Householder steps in total vol from
+/// `guess` on the objective whose Newton step and second- and
+/// third-derivative ratios `step` returns from the price, its vega and the
+/// ratios of vega's first two derivatives to vega. Steps are kept inside
+/// the bracket the iterates build up when `nested`, and a `None` from
+/// `step` bisects it.
+fn householder<T, F>(x: T, guess: T, bracket: (T, T), nested: bool, beta: T, step: F) -> T
+where
+    T: Float,
+    F: Fn(T, T, T, T) -> Option<(T, T, T)>,
+{
+    let (mut s_left, mut s_right) = bracket;
+    let half = cast::<T>(0.5);
+    let mut s = guess;
+    let mut ds = T::max_value();
+    for iteration in 0..ITERATIONS {
+        if ds.abs() <= T::epsilon() * s {
+            break;
+        }
+        if nested && iteration > 0 && !(s > s_left && s < s_right) {
+            s = half * (s_left + s_right);
+        }
+        let b = normalised_black(x, s);
+        let vega = normalised_vega(x, s);
+        if b > beta && s < s_right {
+            s_right = s;
+        } else if b < beta && s > s_left {
+            s_left = s;
+        }
+        let h = x / s;
+        let curvature = h * h / s - s / cast(4.0);
+        let bend = curvature * curvature - cast::<T>(3.0) * (h / s) * (h / s) - cast(0.25);
+        ds = match step(b, vega, curvature, bend) {
+            Some((newton, halley, third)) => {
+                let factor = (T::one() + half * halley * newton)
+                    / (T::one() + newton * (halley + third * newton / cast(6.0)));
+                newton * factor
+            }
+            None => half * (s_left + s_right) - s,
+        };
+        ds = ds.max(-half * s);
+        s = s + ds;
+    }
+    s
+}
+
+/// Normalised Black call `b(x, s) = e^{x/2} N(x/s + s/2) - e^{-x/2} N(x/s -
+/// s/2)`, the undiscounted price over `sqrt(F K)` at log-moneyness
+/// `x = ln(F / K)` and total vol `s`. Out of the money, each region uses
+/// the form that keeps full relative precision there.
+fn normalised_black<T: Float>(x: T, s: T) -> T {
+    if x > T::zero() {
+        return normalised_intrinsic(x) + normalised_black(-x, s);
+    }
+    if s <= T::zero() {
+        return T::zero();
+    }
+    let half = cast::<T>(0.5);
+    let (h, t) = (x / s, half * s);
+    let asymptotic = cast::<T>(ASYMPTOTIC_THRESHOLD);
+    let small_t = cast::<T>(SMALL_T_THRESHOLD);
+    if h < asymptotic && h + t < asymptotic + small_t {
+        asymptotic_black(h, t)
+    } else if t < small_t {
+        small_t_black(h, t)
+    } else if h + t > cast(0.85) {
+        let b_max = (half * x).exp();
+        (norm_cdf(h + t) * b_max - norm_cdf(h - t) / b_max).max(T::zero())
+    } else {
+        let root_half = cast::<T>(core::f64::consts::FRAC_1_SQRT_2);
+        let scale = (-half * (h * h + t * t)).exp();
+        (half * scale * (erfcx(-(h + t) * root_half) - erfcx(-(h - t) * root_half))).max(T::zero())
+    }
+}
+
+/// `sqrt(2 pi) e^{z^2 / 2} N(z)`, the function both normalised terms share.
+fn mills<T: Float>(z: T) -> T {
+    cast::<T>(core::f64::consts::PI / 2.0).sqrt()
+        * erfcx(-z * cast(core::f64::consts::FRAC_1_SQRT_2))
+}
+
+/// `b(x, s)` as `e^{-(h^2 + t^2)/2} / sqrt(2 pi)` times the difference of
+/// `mills` at `h + t` and `h - t`, expanded in odd powers of `t` through
+/// the recurrence `Y_{n+1} = h Y_n + n Y_{n-1}` of its derivatives.
+fn small_t_black<T: Float>(h: T, t: T) -> T {
+    let y0 = mills(h);
+    let (mut previous, mut current) = (y0, T::one() + h * y0);
+    let (mut power, mut sum) = (t, t * current);
+    let t2 = t * t;
+    let mut n = 1;
+    while n < 17 {
+        // Two recurrence steps carry `Y_n` to `Y_{n+2}`.
+        let next = h * current + cast::<T>(n as f64) * previous;
+        let after = h * next + cast::<T>((n + 1) as f64) * current;
+        previous = next;
+        current = after;
+        power = power * t2 / cast::<T>(((n + 1) * (n + 2)) as f64);
+        let term = power * current;
+        sum = sum + term;
+        if term.abs() <= T::epsilon() * sum.abs() {
+            break;
+        }
+        n += 2;
+    }
+    let scale = (-(h * h + t * t) / cast(2.0)).exp() * cast(core::f64::consts::FRAC_2_SQRT_PI);
+    (scale * sum / cast(core::f64::consts::SQRT_2)).max(T::zero())
+}
+
+/// `b(x, s)` far out of the money, from the asymptotic series of `mills`
+/// in `1 / z`. With `u = -h` and `r = t / u` the difference of the two
+/// series is a sum of `(1 - r)^{-n} - (1 + r)^{-n}`, each expanded in odd
+/// powers of `r` so nothing cancels.
+fn asymptotic_black<T: Float>(h: T, t: T) -> T {
+    let u = -h;
+    let r = t / u;
+    let squeeze = T::one() / (T::one() - r * r);
+    let mut sum = T::zero();
+    // `(-1)^k (2k - 1)!! / u^{2k + 1}`.
+    let mut coefficient = T::one() / u;
+    let mut last = T::infinity();
+    for k in 0..40usize {
+        let n = 2 * k + 1;
+        // `((1 + r)^n - (1 - r)^n) / 2` by its odd binomial terms.
+        let (mut binomial, mut odd_sum) = (cast::<T>(n as f64), T::zero());
+        let mut r_power = r;
+        let mut j = 1;
+        while j <= n {
+            odd_sum = odd_sum + binomial * r_power;
+            let remaining = n - j;
+            binomial = binomial * cast::<T>((remaining * remaining.saturating_sub(1)) as f64)
+                / cast::<T>(((j + 1) * (j + 2)) as f64);
+            r_power = r_power * r * r;
+            j += 2;
+        }
+        let term = coefficient * (odd_sum + odd_sum) * squeeze.powi(n as i32);
+        if term.abs() >= last {
+            break;
+        }
+        sum = sum + term;
+        last = term.abs();
+        if last <= T::epsilon() * sum.abs() {
+            break;
+        }
+        coefficient = -coefficient * cast::<T>(n as f64) / (u * u);
+    }
+    let scale = (-(h * h + t * t) / cast(2.0)).exp();
+    (scale * sum * cast(core::f64::consts::FRAC_2_SQRT_PI) / cast(core::f64::consts::SQRT_2 * 2.0))
+        .max(T::zero())
+}
+
+/// `db / ds`, the normalised vega.
+fn normalised_vega<T: Float>(x: T, s: T) -> T {
+    if s <= T::zero() {
+        return T::zero();
+    }
+    let h = x / s;
+    let t = s / cast(2.0);
+    (-(h * h + t * t) / cast(2.0)).exp() * norm_pdf(T::zero())
+}
+
+/// `b(x, 0)` for `x > 0`, `e^{x/2} - e^{-x/2}`, by its Taylor series where
+/// the difference would cancel.
+fn normalised_intrinsic<T: Float>(x: T) -> T {
+    let x2 = x * x;
+    if x2 < cast::<T>(98.0) * T::epsilon().sqrt().sqrt() {
+        let series = cast::<T>(1.0 / 24.0)
+            + x2 * (cast::<T>(1.0 / 1920.0)
+                + x2 * (cast::<T>(1.0 / 322560.0) + x2 * cast::<T>(1.0 / 92897280.0)));
+        return (x * (T::one() + x2 * series)).max(T::zero());
+    }
+    let b_max = (x / cast(2.0)).exp();
+    (b_max - T::one() / b_max).max(T::zero())
+}
+
+/// Jäckel's lower map `f(s) = 2 pi |x| / sqrt(27) N(-|x| / (sqrt(3) s))^3`,
+/// which matches `b` to leading order as `s` falls to zero, with its first
+/// two derivatives with respect to `b`.
+fn lower_map<T: Float>(x: T, s: T) -> (T, T, T) {
+    let c = cast::<T>;
+    let ax = x.abs();
+    let z = ax / (c(3.0).sqrt() * s);
+    let y = z * z;
+    let s2 = s * s;
+    let phi_cdf = norm_cdf(-z);
+    let phi = norm_pdf(z);
+    let pi = c(core::f64::consts::PI);
+    let bracket = c(8.0) * c(3.0).sqrt() * s * ax
+        + (c(3.0) * s2 * (s2 - c(8.0)) - c(8.0) * x * x) * phi_cdf / phi;
+    let d2f = pi / c(6.0) * y / (s2 * s) * phi_cdf * bracket * (y + y + c(0.25) * s2).exp();
+    let phi2 = phi_cdf * phi_cdf;
+    let df = c(2.0) * pi * y * phi2 * (y + c(0.125) * s2).exp();
+    let f = c(2.0) * pi / c(27.0).sqrt() * ax * phi2 * phi_cdf;
+    (f, df, d2f)
+}
+
+fn inverse_lower_map<T: Float>(x: T, f: T) -> T {
+    let c = cast::<T>;
+    if f <= T::zero() {
+        return T::zero();
+    }
+    let scale = c(2.0) * c(core::f64::consts::PI) / c(27.0).sqrt() * x.abs();
+    (x / (c(3.0).sqrt() * inverse_norm_cdf((f / scale).powf(c(1.0 / 3.0))))).abs()
+}
+
+/// Jäckel's upper map `f(s) = N(-s / 2)`, which tracks how `b` approaches
+/// its ceiling as `s` grows, with its first two derivatives with respect
+/// to `b`.
+fn upper_map<T: Float>(x: T, s: T) -> (T, T, T) {
+    let c = cast::<T>;
+    let f = norm_cdf(-s / c(2.0));
+    let w = (x / s) * (x / s);
+    let df = -c(0.5) * (w / c(2.0)).exp();
+    let d2f = c(core::f64::consts::PI / 2.0).sqrt() * (w + c(0.125) * s * s).exp() * w / s;
+    (f, df, d2f)
+}
+
+/// Delbourgo and Gregory's rational cubic through `(x_l, y_l)` and
+/// `(x_r, y_r)` with end slopes `d_l` and `d_r`; the control `r` tends
+/// the curve from the Hermite cubic at three to linear as it grows.
+#[allow(clippy::too_many_arguments)]
+fn rational_cubic<T: Float>(x: T, x_l: T, x_r: T, y_l: T, y_r: T, d_l: T, d_r: T, r: T) -> T {
+    let h = x_r - x_l;
+    if h.abs() <= T::zero() {
+        return (y_l + y_r) / cast(2.0);
+    }
+    let t = (x - x_l) / h;
+    if r >= max_control() {
+        return y_r * t + y_l * (T::one() - t);
+    }
+    let omt = T::one() - t;
+    let (t2, omt2) = (t * t, omt * omt);
+    let numerator = y_r * t2 * t
+        + (r * y_r - h * d_r) * t2 * omt
+        + (r * y_l + h * d_l) * t * omt2
+        + y_l * omt2 * omt;
+    numerator / (T::one() + (r - cast(3.0)) * t * omt)
+}
+
+fn max_control<T: Float>() -> T {
+    cast::<T>(2.0) / (T::epsilon() * T::epsilon())
+}
+
+fn min_control<T: Float>() -> T {
+    -(T::one() - T::epsilon().sqrt())
+}
+
+/// Control at which `rational_cubic` has `second` for its second
+/// derivative at the left (`at_left`) or right end.
+#[allow(clippy::too_many_arguments)]
+fn fitted_control<T: Float>(
+    x_l: T,
+    x_r: T,
+    y_l: T,
+    y_r: T,
+    d_l: T,
+    d_r: T,
+    second: T,
+    at_left: bool,
+) -> T {
+    let h = x_r - x_l;
+    let numerator = second * h / cast(2.0) + (d_r - d_l);
+    if numerator.abs() < T::min_positive_value() {
+        return T::zero();
+    }
+    let secant = (y_r - y_l) / h;
+    let denominator = if at_left { secant - d_l } else { d_r - secant };
+    if denominator.abs() < T::min_positive_value() {
+        return if numerator > T::zero() {
+            max_control()
+        } else {
+            min_control()
+        };
+    }
+    numerator / denominator
+}
+
+/// Smallest control keeping `rational_cubic` monotone, convex or concave
+/// wherever its end slopes and secant allow it; `preserve_shape` falls back
+/// on straight lines rather than give up on shape.
+fn shape_control<T: Float>(d_l: T, d_r: T, secant: T, preserve_shape: bool) -> T {
+    let zero = T::zero();
+    let monotonic = d_l * secant >= zero && d_r * secant >= zero;
+    let convex = d_l <= secant && secant <= d_r;
+    let concave = d_l >= secant && secant >= d_r;
+    if !monotonic && !convex && !concave {
+        return min_control();
+    }
+    let tiny = |v: T| v.abs() < T::min_positive_value();
+    let (mut r1, mut r2) = (-T::max_value(), -T::max_value());
+    if monotonic {
+        if !tiny(secant) {
+            r1 = (d_r + d_l) / secant;
+        } else if preserve_shape {
+            r1 = max_control();
+        }
+    }
+    if convex || concave {
+        let (right_gap, left_gap) = (d_r - secant, secant - d_l);
+        if !(tiny(left_gap) || tiny(right_gap)) {
+            r2 = ((d_r - d_l) / right_gap)
+                .abs()
+                .max(((d_r - d_l) / left_gap).abs());
+        } else if preserve_shape {
+            r2 = max_control();
+        }
+    } else if monotonic && preserve_shape {
+        r2 = max_control();
+    }
+    min_control::<T>().max(r1).max(r2)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn convex_control_left<T: Float>(
+    x_l: T,
+    x_r: T,
+    y_l: T,
+    y_r: T,
+    d_l: T,
+    d_r: T,
+    second: T,
+    preserve_shape: bool,
+) -> T {
+    let r = fitted_control(x_l, x_r, y_l, y_r, d_l, d_r, second, true);
+    r.max(shape_control(
+        d_l,
+        d_r,
+        (y_r - y_l) / (x_r - x_l),
+        preserve_shape,
+    ))
+}
+
+#[allow(clippy::too_many_arguments)]
+fn convex_control_right<T: Float>(
+    x_l: T,
+    x_r: T,
+    y_l: T,
+    y_r: T,
+    d_l: T,
+    d_r: T,
+    second: T,
+    preserve_shape: bool,
+) -> T {
+    let r = fitted_control(x_l, x_r, y_l, y_r, d_l, d_r, second, false);
+    r.max(shape_control(
+        d_l,
+        d_r,
+        (y_r - y_l) / (x_r - x_l),
+        preserve_shape,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analytic::black_scholes_price;
+    use crate::units::{Rate, Spot, Strike, Vol};
+
+    /// Prices across moneyness, expiry and vol invert back to their vol.
+    #[test]
+    fn round_trips_black_scholes_prices() {
+        for vol in [0.05, 0.2, 0.6, 1.5] {
+            let market: Market =
+                Market::new(Spot(100.0), Rate(0.03), Vol(vol)).with_dividend_yield(Rate(0.01));
+            for option_type in [OptionType::Call, OptionType::Put] {
+                for strike in [50.0, 80.0, 100.0, 125.0, 200.0] {
+                    for expiry in [0.02, 0.5, 3.0] {
+                        let spec = OptionSpec::european(option_type, Strike(strike), expiry);
+                        let price = black_scholes_price(&spec, &market).unwrap();
+                        // The time value is the out-of-the-money price; one
+                        // lost in the round-off of the price carries no vol.
+                        let other = OptionSpec {
+                            option_type: match option_type {
+                                OptionType::Call => OptionType::Put,
+                                OptionType::Put => OptionType::Call,
+                            },
+                            ..spec
+                        };
+                        let time_value = price.min(black_scholes_price(&other, &market).unwrap());
+                        if time_value < 1e-8 * strike {
+                            continue;
+                        }
+                        let implied = rational_implied_vol(price, &spec, &market).unwrap();
+                        assert!(
+                            (implied - vol).abs() < 1e-9,
+                            "{option_type:?} K={strike} T={expiry} vol={vol}: {implied}"
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,101 @@
+//! Knock-in and knock-out barrier terms.
+
+use crate::float::{cast, Float};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BarrierKind {
+    UpAndOut,
+    DownAndOut,
+    UpAndIn,
+    DownAndIn,
+}
+
+impl BarrierKind {
+    pub fn is_up(self) -> bool {
+        matches!(self, BarrierKind::UpAndOut | BarrierKind::UpAndIn)
+    }
+
+    pub fn is_knock_out(self) -> bool {
+        matches!(self, BarrierKind::UpAndOut | BarrierKind::DownAndOut)
+    }
+}
+
+/// A barrier at `level`, monitored at every lattice step.
+///
+/// Knock-outs pay `rebate` as soon as the barrier is touched; knock-ins pay
+/// it at expiry if the barrier was never touched.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Barrier<T = f64> {
+    pub kind: BarrierKind,
+    pub level: T,
+    pub rebate: T,
+}
+
+impl<T: Float> Barrier<T> {
+    pub fn new(kind: BarrierKind, level: T) -> Self {
+        Barrier {
+            kind,
+            level,
+            rebate: T::zero(),
+        }
+    }
+
+    pub fn up_and_out(level: T) -> Self {
+        Barrier::new(BarrierKind::UpAndOut, level)
+    }
+
+    pub fn down_and_out(level: T) -> Self {
+        Barrier::new(BarrierKind::DownAndOut, level)
+    }
+
+    pub fn up_and_in(level: T) -> Self {
+        Barrier::new(BarrierKind::UpAndIn, level)
+    }
+
+    pub fn down_and_in(level: T) -> Self {
+        Barrier::new(BarrierKind::DownAndIn, level)
+    }
+
+    pub fn with_rebate(self, rebate: T) -> Self {
+        Barrier { rebate, ..self }
+    }
+
+    /// Whether spot `s` is at or beyond the barrier.
+    pub fn is_hit(&self, s: T) -> bool {
+        if self.kind.is_up() {
+            s >= self.level
+        } else {
+            s <= self.level
+        }
+    }
+
+    /// Smallest Boyle-Lau step count of at least `num_steps`: the counts
+    /// `floor(k² vol² T / ln²(spot / level))` put the barrier just inside a
+    /// layer of a Cox-Ross-Rubinstein tree, avoiding the bias of a barrier
+    /// that falls between layers.
+    pub fn boyle_lau_steps(&self, spot: T, vol: T, expiry: T, num_steps: usize) -> usize {
+        let distance = (spot / self.level).ln();
+        if distance == T::zero() || !distance.is_finite() {
+            return num_steps;
+        }
+        let scale = vol * vol * expiry / (distance * distance);
+        // n(k) grows like k², so start from the k that lands near `num_steps`.
+        let target = cast::<T>(num_steps as f64);
+        let mut k = (target / scale)
+            .sqrt()
+            .floor()
+            .to_usize()
+            .unwrap_or(1)
+            .max(1);
+        loop {
+            let steps = (cast::<T>((k * k) as f64) * scale).floor();
+            match steps.to_usize() {
+                Some(steps) if steps >= num_steps.max(1) => return steps,
+                None => return num_steps,
+                _ => k += 1,
+            }
+        }
+    }
+}
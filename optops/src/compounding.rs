@@ -0,0 +1,56 @@
+//! Conventions for turning a quoted interest rate into discount factors.
+
+use crate::error::{Domain, OptOpsError, Result};
+use crate::float::{to_f64, Float};
+use serde::{Deserialize, Serialize};
+
+/// How a quoted rate accrues over time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Compounding {
+    /// `exp(-r t)`.
+    #[default]
+    Continuous,
+    /// `(1 + r)^-t`.
+    Annual,
+    /// `1 / (1 + r t)`, as brokers quote money-market rates.
+    Simple,
+}
+
+impl Compounding {
+    pub fn is_continuous(&self) -> bool {
+        *self == Compounding::Continuous
+    }
+
+    pub fn discount_factor<T: Float>(self, rate: T, t: T) -> T {
+        let one = T::one();
+        match self {
+            Compounding::Continuous => (-rate * t).exp(),
+            Compounding::Annual => (one + rate).powf(-t),
+            Compounding::Simple => one / (one + rate * t),
+        }
+    }
+
+    /// Continuously compounded rate giving the same discount factor over
+    /// `horizon`; the models run on this equivalent.
+    pub fn continuous_rate<T: Float>(self, rate: T, horizon: T) -> Result<T> {
+        let domain = match self {
+            Compounding::Continuous => Domain::Finite,
+            Compounding::Annual => Domain::Above(-1.0),
+            Compounding::Simple => Domain::Above(-1.0 / to_f64(horizon)),
+        };
+        let rate_f64 = to_f64(rate);
+        if !domain.contains(rate_f64) {
+            return Err(OptOpsError::InvalidParameter {
+                name: "rate",
+                value: rate_f64,
+                domain,
+            });
+        }
+        Ok(match self {
+            Compounding::Continuous => rate,
+            Compounding::Annual => rate.ln_1p(),
+            Compounding::Simple => (rate * horizon).ln_1p() / horizon,
+        })
+    }
+}
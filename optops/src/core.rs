@@ -0,0 +1,1118 @@
+//! Allocation-only numerical kernel shared by the std-facing pricers.
+//!
+//! Nothing here touches `std`, so the lattice rollback and Black-Scholes
+//! formulas can be embedded in `no_std + alloc` targets such as WASM risk
+//! kernels. Inputs are assumed valid; checking them is the caller's job.
+
+use crate::float::{cast, Float};
+use crate::instrument::OptionType;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::ops::Index;
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// Standard normal density.
+pub fn norm_pdf<T: Float>(x: T) -> T {
+    let inv_sqrt_2pi = cast::<T>(0.398_942_280_401_432_7);
+    inv_sqrt_2pi * (-x * x / cast(2.0)).exp()
+}
+
+/// Standard normal cumulative distribution function.
+pub fn norm_cdf<T: Float>(x: T) -> T {
+    erfc(-x / cast(core::f64::consts::SQRT_2)) / cast(2.0)
+}
+
+/// Complementary error function, accurate to double precision (W. J. Cody's
+/// rational Chebyshev approximations, as in his CALERF routine).
+pub fn erfc<T: Float>(x: T) -> T {
+    calerf(x, false)
+}
+
+/// Scaled complementary error function `exp(x^2) erfc(x)`, which keeps its
+/// precision far into the right tail where `erfc` underflows; it overflows
+/// instead once `x` is below about -26.6.
+pub fn erfcx<T: Float>(x: T) -> T {
+    calerf(x, true)
+}
+
+/// `erfc`, or `erfcx` when `scaled`, by Cody's approximations.
+fn calerf<T: Float>(x: T, scaled: bool) -> T {
+    const A: [f64; 5] = [
+        3.1611237438705655,
+        1.1386415415105016e2,
+        3.77485237685302e2,
+        3.2093775891384694e3,
+        1.8577770618460315e-1,
+    ];
+    const B: [f64; 4] = [
+        2.3601290952344122e1,
+        2.4402463793444417e2,
+        1.2826165260773723e3,
+        2.844236833439171e3,
+    ];
+    const C: [f64; 9] = [
+        5.641884969886701e-1,
+        8.883149794388377,
+        6.611919063714163e1,
+        2.986351381974001e2,
+        8.81952221241769e2,
+        1.7120476126340707e3,
+        2.0510783778260716e3,
+        1.2303393547979972e3,
+        2.1531153547440383e-8,
+    ];
+    const D: [f64; 8] = [
+        1.5744926110709835e1,
+        1.176939508913125e2,
+        5.371811018620099e2,
+        1.6213895745666903e3,
+        3.2907992357334597e3,
+        4.362619090143247e3,
+        3.4393676741437216e3,
+        1.2303393548037493e3,
+    ];
+    const P: [f64; 6] = [
+        3.0532663496123236e-1,
+        3.603448999498044e-1,
+        1.2578172611122926e-1,
+        1.608378514874228e-2,
+        6.587491615298378e-4,
+        1.63153871373021e-2,
+    ];
+    const Q: [f64; 5] = [
+        2.568520192289822,
+        1.8729528499234673,
+        5.279051029514285e-1,
+        6.051834131244132e-2,
+        2.3352049762686918e-3,
+    ];
+    const FRAC_1_SQRT_PI: f64 = 5.641895835477563e-1;
+
+    let c = cast::<T>;
+    let y = x.abs();
+    // exp(-y^2) split as exp(-ysq^2) * exp(-del) to keep full precision.
+    let gaussian = |y: T| {
+        let ysq = (y * c(16.0)).trunc() / c(16.0);
+        let del = (y - ysq) * (y + ysq);
+        (-ysq * ysq).exp() * (-del).exp()
+    };
+
+    if y <= c(0.46875) {
+        let ysq = y * y;
+        let mut xnum = c(A[4]) * ysq;
+        let mut xden = ysq;
+        for i in 0..3 {
+            xnum = (xnum + c(A[i])) * ysq;
+            xden = (xden + c(B[i])) * ysq;
+        }
+        let value = T::one() - x * (xnum + c(A[3])) / (xden + c(B[3]));
+        return if scaled { (x * x).exp() * value } else { value };
+    }
+
+    // The tail of `erfc(y)` over `exp(-y^2)`.
+    let scaled_tail = if y <= c(4.0) {
+        let mut xnum = c(C[8]) * y;
+        let mut xden = y;
+        for i in 0..7 {
+            xnum = (xnum + c(C[i])) * y;
+            xden = (xden + c(D[i])) * y;
+        }
+        (xnum + c(C[7])) / (xden + c(D[7]))
+    } else if !scaled && y >= c(26.6) {
+        T::zero()
+    } else {
+        let ysq = T::one() / (y * y);
+        let mut xnum = c(P[5]) * ysq;
+        let mut xden = ysq;
+        for i in 0..4 {
+            xnum = (xnum + c(P[i])) * ysq;
+            xden = (xden + c(Q[i])) * ysq;
+        }
+        let r = ysq * (xnum + c(P[4])) / (xden + c(Q[4]));
+        (c(FRAC_1_SQRT_PI) - r) / y
+    };
+
+    match (scaled, x < T::zero()) {
+        (false, false) => gaussian(y) * scaled_tail,
+        (false, true) => c(2.0) - gaussian(y) * scaled_tail,
+        (true, false) => scaled_tail,
+        (true, true) => c(2.0) / gaussian(y) - scaled_tail,
+    }
+}
+
+/// Inverse of `norm_cdf`: Acklam's rational approximation, good to about
+/// 1e-9, polished to full precision by one Halley step. Returns minus or
+/// plus infinity at `p` of zero or one.
+pub fn inverse_norm_cdf<T: Float>(p: T) -> T {
+    const A: [f64; 6] = [
+        -3.969683028665376e1,
+        2.209460984245205e2,
+        -2.759285104469687e2,
+        1.38357751867269e2,
+        -3.066479806614716e1,
+        2.506628277459239,
+    ];
+    const B: [f64; 5] = [
+        -5.447609879822406e1,
+        1.615858368580409e2,
+        -1.556989798598866e2,
+        6.680131188771972e1,
+        -1.328068155288572e1,
+    ];
+    const C: [f64; 6] = [
+        -7.784894002430293e-3,
+        -3.223964580411365e-1,
+        -2.400758277161838,
+        -2.549671347679454,
+        4.374664141464968,
+        2.938163982698783,
+    ];
+    const D: [f64; 4] = [
+        7.784695709041462e-3,
+        3.224671290700398e-1,
+        2.445134137142996,
+        3.754408661907416,
+    ];
+    const P_LOW: f64 = 0.02425;
+
+    let c = cast::<T>;
+    if p <= T::zero() {
+        return T::neg_infinity();
+    }
+    if p >= T::one() {
+        return T::infinity();
+    }
+    let horner = |coefficients: &[f64], x: T| {
+        coefficients
+            .iter()
+            .fold(T::zero(), |sum, &k| sum * x + c(k))
+    };
+    // The lower tail, mirrored for the upper one through `1 - p`.
+    let tail = |p: T| {
+        let q = (-c(2.0) * p.ln()).sqrt();
+        horner(&C, q) / (horner(&D, q) * q + T::one())
+    };
+    let x = if p < c(P_LOW) {
+        tail(p)
+    } else if p <= c(1.0 - P_LOW) {
+        let q = p - c(0.5);
+        let r = q * q;
+        q * horner(&A, r) / (horner(&B, r) * r + T::one())
+    } else {
+        -tail(T::one() - p)
+    };
+    let miss = erfc(-x / c(core::f64::consts::SQRT_2)) / c(2.0) - p;
+    let u = miss * c(core::f64::consts::TAU).sqrt() * (x * x / c(2.0)).exp();
+    x - u / (T::one() + x * u / c(2.0))
+}
+
+/// Bivariate standard normal distribution function `P(X <= x, Y <= y)` at
+/// correlation `rho`, accurate to about 1e-15 (Genz's refinement of the
+/// Drezner-Wesolowsky quadrature, in West's formulation).
+pub fn bivariate_norm_cdf<T: Float>(x: T, y: T, rho: T) -> T {
+    // Gauss-Legendre half-nodes and weights for 6, 12 and 20 points.
+    const NODES: [&[f64]; 3] = [
+        &[-0.932469514203152, -0.661209386466265, -0.238619186083197],
+        &[
+            -0.981560634246719,
+            -0.904117256370475,
+            -0.769902674194305,
+            -0.587317954286617,
+            -0.36783149899818,
+            -0.125233408511469,
+        ],
+        &[
+            -0.993128599185095,
+            -0.963971927277914,
+            -0.912234428251326,
+            -0.839116971822219,
+            -0.746331906460151,
+            -0.636053680726515,
+            -0.510867001950827,
+            -0.37370608871542,
+            -0.227785851141645,
+            -0.0765265211334973,
+        ],
+    ];
+    const WEIGHTS: [&[f64]; 3] = [
+        &[0.17132449237917, 0.360761573048138, 0.46791393457269],
+        &[
+            0.0471753363865118,
+            0.106939325995318,
+            0.160078328543346,
+            0.203167426723066,
+            0.233492536538355,
+            0.249147045813403,
+        ],
+        &[
+            0.0176140071391521,
+            0.0406014298003869,
+            0.0626720483341091,
+            0.0832767415767048,
+            0.10193011981724,
+            0.118194531961518,
+            0.131688638449177,
+            0.142096109318382,
+            0.149172986472604,
+            0.152753387130726,
+        ],
+    ];
+    let c = cast::<T>;
+    let (one, two) = (T::one(), c(2.0));
+    let two_pi = c(core::f64::consts::TAU);
+    let order = if rho.abs() < c(0.3) {
+        0
+    } else if rho.abs() < c(0.75) {
+        1
+    } else {
+        2
+    };
+    let nodes = NODES[order]
+        .iter()
+        .zip(WEIGHTS[order])
+        .map(|(&x, &w)| (c(x), c(w)));
+
+    let (h, mut k) = (-x, -y);
+    let mut hk = h * k;
+    let mut bvn = T::zero();
+    if rho.abs() < c(0.925) {
+        if rho != T::zero() {
+            let hs = (h * h + k * k) / two;
+            let asr = rho.asin();
+            for (node, weight) in nodes {
+                for side in [-one, one] {
+                    let sn = (asr * (side * node + one) / two).sin();
+                    bvn = bvn + weight * ((sn * hk - hs) / (one - sn * sn)).exp();
+                }
+            }
+            bvn = bvn * asr / (two * two_pi);
+        }
+        return bvn + norm_cdf(-h) * norm_cdf(-k);
+    }
+
+    if rho < T::zero() {
+        k = -k;
+        hk = -hk;
+    }
+    if rho.abs() < one {
+        let ass = (one - rho) * (one + rho);
+        let mut a = ass.sqrt();
+        let bs = (h - k) * (h - k);
+        let cc = (c(4.0) - hk) / c(8.0);
+        let d = (c(12.0) - hk) / c(16.0);
+        let asr = -(bs / ass + hk) / two;
+        if asr > c(-100.0) {
+            bvn = a
+                * asr.exp()
+                * (one - cc * (bs - ass) * (one - d * bs / c(5.0)) / c(3.0)
+                    + cc * d * ass * ass / c(5.0));
+        }
+        if -hk < c(100.0) {
+            let b = bs.sqrt();
+            bvn = bvn
+                - (-hk / two).exp()
+                    * two_pi.sqrt()
+                    * norm_cdf(-b / a)
+                    * b
+                    * (one - cc * bs * (one - d * bs / c(5.0)) / c(3.0));
+        }
+        a = a / two;
+        for (node, weight) in nodes {
+            for side in [-one, one] {
+                let xs = (a * (side * node + one)) * (a * (side * node + one));
+                let rs = (one - xs).sqrt();
+                let asr = -(bs / xs + hk) / two;
+                if asr > c(-100.0) {
+                    let tail = (-hk * (one - rs) / (two * (one + rs))).exp() / rs;
+                    bvn = bvn + a * weight * asr.exp() * (tail - (one + cc * xs * (one + d * xs)));
+                }
+            }
+        }
+        bvn = -bvn / two_pi;
+    }
+    if rho > T::zero() {
+        bvn + norm_cdf(-h.max(k))
+    } else if k > h {
+        norm_cdf(k) - norm_cdf(h) - bvn
+    } else {
+        -bvn
+    }
+}
+
+/// The Black-Scholes `d1` and `d2` terms with a continuous dividend yield.
+pub fn d1_d2<T: Float>(
+    spot: T,
+    strike: T,
+    rate: T,
+    dividend_yield: T,
+    vol: T,
+    expiry: T,
+) -> (T, T) {
+    let sigma_sqrt = vol * expiry.sqrt();
+    let carry = rate - dividend_yield;
+    let d1 = ((spot / strike).ln() + (carry + vol * vol / cast(2.0)) * expiry) / sigma_sqrt;
+    let d2 = d1 - sigma_sqrt;
+    (d1, d2)
+}
+
+/// Black-Scholes-Merton price of a European call or put on an underlying
+/// paying a continuous dividend yield.
+pub fn black_scholes<T: Float>(
+    option_type: OptionType,
+    spot: T,
+    strike: T,
+    rate: T,
+    dividend_yield: T,
+    vol: T,
+    expiry: T,
+) -> T {
+    let (d1, d2) = d1_d2(spot, strike, rate, dividend_yield, vol, expiry);
+    let discount = (-rate * expiry).exp();
+    let forward_spot = spot * (-dividend_yield * expiry).exp();
+    match option_type {
+        OptionType::Call => forward_spot * norm_cdf(d1) - strike * discount * norm_cdf(d2),
+        OptionType::Put => strike * discount * norm_cdf(-d2) - forward_spot * norm_cdf(-d1),
+    }
+}
+
+/// Parameterisation of the binomial moves and probabilities.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TreeScheme {
+    /// Cox-Ross-Rubinstein: `u = exp(vol * sqrt(dt))`, `d = 1 / u`.
+    #[default]
+    CoxRossRubinstein,
+    /// Jarrow-Rudd: moves centred on the risk-neutral log drift, with equal
+    /// up and down probabilities.
+    JarrowRudd,
+    /// Tian: moves matching the first three moments of the lognormal step.
+    Tian,
+    /// Leisen-Reimer: Peizer-Pratt inversion of the Black-Scholes `d1` and
+    /// `d2`, centring the lattice on the strike. Converges smoothly and fast,
+    /// best with an odd number of steps.
+    LeisenReimer,
+}
+
+impl TreeScheme {
+    pub fn is_default(&self) -> bool {
+        *self == TreeScheme::default()
+    }
+}
+
+/// Per-step factors of the recombining tree.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StepFactors<T> {
+    pub up_factor: T,
+    pub down_factor: T,
+    pub up_prob: T,
+    pub discount: T,
+}
+
+/// Factors of one step of length `dt` under `scheme`, with a risk-neutral
+/// drift of `rate - dividend_yield` and discounting at `rate`.
+///
+/// Leisen-Reimer moves also depend on the strike and the step count; build
+/// those with `leisen_reimer_factors`. Here that scheme falls back to
+/// Cox-Ross-Rubinstein.
+pub fn step_factors<T: Float>(
+    scheme: TreeScheme,
+    rate: T,
+    dividend_yield: T,
+    vol: T,
+    dt: T,
+) -> StepFactors<T> {
+    let one = T::one();
+    let half = cast::<T>(0.5);
+    let growth = ((rate - dividend_yield) * dt).exp();
+    let discount = (-rate * dt).exp();
+    let (up_factor, down_factor) = match scheme {
+        TreeScheme::CoxRossRubinstein | TreeScheme::LeisenReimer => {
+            let up = (vol * dt.sqrt()).exp();
+            (up, one / up)
+        }
+        TreeScheme::JarrowRudd => {
+            let drift = (rate - dividend_yield - half * vol * vol) * dt;
+            let spread = vol * dt.sqrt();
+            ((drift + spread).exp(), (drift - spread).exp())
+        }
+        TreeScheme::Tian => {
+            let v = (vol * vol * dt).exp();
+            let root = (v * v + cast::<T>(2.0) * v - cast(3.0)).sqrt();
+            let scale = half * growth * v;
+            (scale * (v + one + root), scale * (v + one - root))
+        }
+    };
+    let up_prob = match scheme {
+        TreeScheme::JarrowRudd => half,
+        _ => (growth - down_factor) / (up_factor - down_factor),
+    };
+    StepFactors {
+        up_factor,
+        down_factor,
+        up_prob,
+        discount,
+    }
+}
+
+/// Peizer-Pratt method 2 inversion: the binomial probability whose
+/// `num_steps`-step distribution best matches `N(z)`.
+pub fn peizer_pratt<T: Float>(z: T, num_steps: usize) -> T {
+    let n = cast::<T>(num_steps as f64);
+    let half = cast::<T>(0.5);
+    let denom = n + cast(1.0 / 3.0) + cast::<T>(0.1) / (n + T::one());
+    let ratio = z / denom;
+    let root = (T::one() - (-ratio * ratio * (n + cast(1.0 / 6.0))).exp()).sqrt();
+    half + half * z.signum() * root
+}
+
+/// Leisen-Reimer factors for a `num_steps`-step tree over `expiry`, centred
+/// on `strike`.
+pub fn leisen_reimer_factors<T: Float>(
+    spot: T,
+    strike: T,
+    rate: T,
+    dividend_yield: T,
+    vol: T,
+    expiry: T,
+    num_steps: usize,
+) -> StepFactors<T> {
+    let dt = expiry / cast(num_steps as f64);
+    let (d1, d2) = d1_d2(spot, strike, rate, dividend_yield, vol, expiry);
+    let up_prob = peizer_pratt(d2, num_steps);
+    let up_prob_star = peizer_pratt(d1, num_steps);
+    let growth = ((rate - dividend_yield) * dt).exp();
+    let up_factor = growth * up_prob_star / up_prob;
+    StepFactors {
+        up_factor,
+        down_factor: (growth - up_prob * up_factor) / (T::one() - up_prob),
+        up_prob,
+        discount: (-rate * dt).exp(),
+    }
+}
+
+/// Backward induction over a recombining tree with one step per entry of
+/// `factors`, where `factors[i]` moves the lattice from step `i` to `i + 1`.
+///
+/// `exercise_value(i, j)` is the payoff at step `i` after `j` up-moves.
+/// When `early_exercise` is false the holder may only exercise at expiry.
+/// Returns the value function and exercise policy indexed `[(i, j)]`.
+pub fn rollback<T, F>(
+    factors: &[StepFactors<T>],
+    early_exercise: bool,
+    exercise_value: F,
+) -> (Triangle<T>, Triangle<bool>)
+where
+    T: Float,
+    F: FnMut(usize, usize) -> T,
+{
+    surface(factors.len(), |values, policy| {
+        induct(
+            factors,
+            early_exercise,
+            exercise_value,
+            |_, _, v_continue| v_continue,
+            |_, _| None,
+            Buffers::triangle(values, policy),
+        )
+    })
+}
+
+/// `rollback` with the continuation value one step before expiry replaced
+/// by `penultimate(j)`, typically the closed-form European value over the
+/// last step (the binomial Black-Scholes method).
+pub fn rollback_smoothed<T, F, G>(
+    factors: &[StepFactors<T>],
+    early_exercise: bool,
+    exercise_value: F,
+    penultimate: G,
+) -> (Triangle<T>, Triangle<bool>)
+where
+    T: Float,
+    F: FnMut(usize, usize) -> T,
+    G: Fn(usize) -> T,
+{
+    surface(factors.len(), |values, policy| {
+        induct(
+            factors,
+            early_exercise,
+            exercise_value,
+            penultimate_continuation(factors.len(), penultimate),
+            |_, _| None,
+            Buffers::triangle(values, policy),
+        )
+    })
+}
+
+/// `rollback` with the value at node `(i, j)` fixed to `pinned(i, j)`
+/// wherever that returns a value, such as the rebate at nodes beyond a
+/// knock-out barrier. Pinned nodes are never marked for exercise.
+pub fn rollback_pinned<T, F, P>(
+    factors: &[StepFactors<T>],
+    early_exercise: bool,
+    exercise_value: F,
+    pinned: P,
+) -> (Triangle<T>, Triangle<bool>)
+where
+    T: Float,
+    F: FnMut(usize, usize) -> T,
+    P: FnMut(usize, usize) -> Option<T>,
+{
+    surface(factors.len(), |values, policy| {
+        induct(
+            factors,
+            early_exercise,
+            exercise_value,
+            |_, _, v_continue| v_continue,
+            pinned,
+            Buffers::triangle(values, policy),
+        )
+    })
+}
+
+/// The continuation hook of `rollback_smoothed` for a `num_steps`-step tree.
+pub fn penultimate_continuation<T, G>(
+    num_steps: usize,
+    penultimate: G,
+) -> impl Fn(usize, usize, T) -> T
+where
+    G: Fn(usize) -> T,
+{
+    let last = num_steps.saturating_sub(1);
+    move |i, j, v_continue| {
+        if i == last {
+            penultimate(j)
+        } else {
+            v_continue
+        }
+    }
+}
+
+/// How `induct` arranges the layers of the lattice in its buffers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Layout {
+    /// A single layer overwritten in place; only the root value survives,
+    /// so pricing a 50,000-step tree needs O(n) space.
+    Layer,
+    /// Every layer back to back in one triangular buffer, layer `i` starting
+    /// at `i * (i + 1) / 2`.
+    Triangle,
+}
+
+impl Layout {
+    /// Buffer length needed for a `num_steps`-step tree.
+    pub fn len(self, num_steps: usize) -> usize {
+        match self {
+            Layout::Layer => num_steps + 1,
+            Layout::Triangle => (num_steps + 1) * (num_steps + 2) / 2,
+        }
+    }
+
+    /// Position of node `(i, j)` in the buffer.
+    pub fn index(self, i: usize, j: usize) -> usize {
+        match self {
+            Layout::Layer => j,
+            Layout::Triangle => i * (i + 1) / 2 + j,
+        }
+    }
+}
+
+/// Caller-owned value and policy storage for `induct`, each at least
+/// `layout.len(num_steps)` long, so nothing is allocated per layer and the
+/// buffers can be reused across runs.
+pub struct Buffers<'a, T> {
+    pub layout: Layout,
+    pub values: &'a mut [T],
+    pub policy: &'a mut [bool],
+    /// When set, the European value of the same contract is rolled back
+    /// alongside, so the early-exercise premium at each node is
+    /// `values - european`.
+    pub european: Option<&'a mut [T]>,
+    /// When set, the values of the first layers are copied out in triangle
+    /// order as they are rolled back, as many whole layers as fit; tree
+    /// Greeks read the first three from six slots.
+    pub head: Option<&'a mut [T]>,
+}
+
+impl<'a, T> Buffers<'a, T> {
+    pub fn layer(values: &'a mut [T], policy: &'a mut [bool]) -> Self {
+        Buffers {
+            layout: Layout::Layer,
+            values,
+            policy,
+            european: None,
+            head: None,
+        }
+    }
+
+    pub fn triangle(values: &'a mut [T], policy: &'a mut [bool]) -> Self {
+        Buffers {
+            layout: Layout::Triangle,
+            values,
+            policy,
+            european: None,
+            head: None,
+        }
+    }
+
+    /// Also rolls the European value back into `european`, laid out like
+    /// `values`.
+    pub fn with_european(self, european: &'a mut [T]) -> Self {
+        Buffers {
+            european: Some(european),
+            ..self
+        }
+    }
+
+    /// Also copies the first layers of values into `head`.
+    pub fn with_head(self, head: &'a mut [T]) -> Self {
+        Buffers {
+            head: Some(head),
+            ..self
+        }
+    }
+}
+
+/// Copies layer `i`, `level`, into `head` when the whole layer fits.
+fn copy_head<T: Copy>(head: Option<&mut [T]>, i: usize, level: &[T]) {
+    let start = Layout::Triangle.index(i, 0);
+    if let Some(head) = head.filter(|head| head.len() > start + i) {
+        head[start..=start + i].copy_from_slice(level);
+    }
+}
+
+/// The backward induction behind the `rollback` family, writing every node
+/// straight into `buffers`.
+///
+/// `continuation(i, j, v)` may replace the discounted expectation `v` at
+/// node `(i, j)` and `pinned(i, j)` may fix the node's value outright; both
+/// hooks also apply to the European value when it is rolled back, so
+/// `continuation` may be called twice per node. Returns the root value.
+pub fn induct<T, F, C, P>(
+    factors: &[StepFactors<T>],
+    early_exercise: bool,
+    exercise_value: F,
+    continuation: C,
+    pinned: P,
+    buffers: Buffers<'_, T>,
+) -> T
+where
+    T: Float,
+    F: FnMut(usize, usize) -> T,
+    C: FnMut(usize, usize, T) -> T,
+    P: FnMut(usize, usize) -> Option<T>,
+{
+    let num_steps = factors.len();
+    let Buffers {
+        layout,
+        values,
+        policy,
+        mut european,
+        mut head,
+    } = buffers;
+    let len = layout.len(num_steps);
+    assert!(values.len() >= len && policy.len() >= len);
+    assert!(european
+        .as_ref()
+        .is_none_or(|european| european.len() >= len));
+    let mut hooks = Hooks {
+        exercise_value,
+        continuation,
+        pinned,
+    };
+
+    for i in (0..=num_steps).rev() {
+        let here = layout.index(i, 0);
+        // In the single-layer layout `next == here`: node `j` reads slots
+        // `j` and `j + 1`, and slot `j + 1` is only overwritten afterwards.
+        let next = if i == num_steps {
+            here
+        } else {
+            layout.index(i + 1, 0)
+        };
+        for j in 0..=i {
+            let successors = (i < num_steps).then(|| Successors {
+                values: [values[next + j], values[next + j + 1]],
+                european: european
+                    .as_deref()
+                    .map(|european| [european[next + j], european[next + j + 1]]),
+            });
+            let (value, exercise, european_value) =
+                hooks.node(factors, early_exercise, i, j, successors);
+            values[here + j] = value;
+            policy[here + j] = exercise;
+            if let Some(european) = european.as_deref_mut() {
+                european[here + j] = european_value;
+            }
+        }
+        copy_head(head.as_deref_mut(), i, &values[here..=here + i]);
+    }
+
+    values[0]
+}
+
+/// Nodes per task of `induct_parallel`; long contiguous runs keep each
+/// task's inner loop cheap to schedule and easy to vectorise.
+#[cfg(feature = "rayon")]
+const PARALLEL_CHUNK: usize = 1024;
+
+/// `induct` with every layer of at least `min_parallel` nodes split across
+/// the rayon thread pool, in chunks of consecutive nodes; smaller layers,
+/// and so small trees, stay on the calling thread. The hooks must be
+/// shareable across threads.
+#[cfg(feature = "rayon")]
+pub fn induct_parallel<T, F, C, P>(
+    factors: &[StepFactors<T>],
+    early_exercise: bool,
+    exercise_value: F,
+    continuation: C,
+    pinned: P,
+    buffers: Buffers<'_, T>,
+    min_parallel: usize,
+) -> T
+where
+    T: Float,
+    F: Fn(usize, usize) -> T + Sync,
+    C: Fn(usize, usize, T) -> T + Sync,
+    P: Fn(usize, usize) -> Option<T> + Sync,
+{
+    use rayon::prelude::*;
+
+    let num_steps = factors.len();
+    let Buffers {
+        layout,
+        values,
+        policy,
+        mut european,
+        mut head,
+    } = buffers;
+    let len = layout.len(num_steps);
+    assert!(values.len() >= len && policy.len() >= len);
+    assert!(european
+        .as_ref()
+        .is_none_or(|european| european.len() >= len));
+    let hooks = Hooks {
+        exercise_value,
+        continuation,
+        pinned,
+    };
+    // Layer `i + 1` is copied aside so layer `i` can be written in parallel
+    // in either layout.
+    let mut next_values = vec![T::zero(); num_steps + 2];
+    let mut next_european = vec![T::zero(); if european.is_some() { num_steps + 2 } else { 0 }];
+
+    for i in (0..=num_steps).rev() {
+        let here = layout.index(i, 0);
+        if i < num_steps {
+            let next = layout.index(i + 1, 0);
+            next_values[..i + 2].copy_from_slice(&values[next..next + i + 2]);
+            if let Some(european) = european.as_deref() {
+                next_european[..i + 2].copy_from_slice(&european[next..next + i + 2]);
+            }
+        }
+        let (next_values, next_european) = (&next_values, &next_european);
+        let with_european = european.is_some();
+        let hooks = &hooks;
+        let run =
+            |start: usize, values: &mut [T], policy: &mut [bool], european: Option<&mut [T]>| {
+                let mut hooks = Hooks {
+                    exercise_value: &hooks.exercise_value,
+                    continuation: &hooks.continuation,
+                    pinned: &hooks.pinned,
+                };
+                let mut european = european;
+                for (k, (value, exercise)) in values.iter_mut().zip(policy.iter_mut()).enumerate() {
+                    let j = start + k;
+                    let successors = (i < num_steps).then(|| Successors {
+                        values: [next_values[j], next_values[j + 1]],
+                        european: with_european.then(|| [next_european[j], next_european[j + 1]]),
+                    });
+                    let node = hooks.node(factors, early_exercise, i, j, successors);
+                    (*value, *exercise) = (node.0, node.1);
+                    if let Some(european) = european.as_deref_mut() {
+                        european[k] = node.2;
+                    }
+                }
+            };
+
+        let values = &mut values[here..=here + i];
+        let policy = &mut policy[here..=here + i];
+        let european = european
+            .as_deref_mut()
+            .map(|european| &mut european[here..=here + i]);
+        if i + 1 < min_parallel {
+            run(0, values, policy, european);
+            copy_head(head.as_deref_mut(), i, values);
+            continue;
+        }
+        let chunks = values
+            .par_chunks_mut(PARALLEL_CHUNK)
+            .zip(policy.par_chunks_mut(PARALLEL_CHUNK));
+        match european {
+            Some(european) => chunks
+                .zip(european.par_chunks_mut(PARALLEL_CHUNK))
+                .enumerate()
+                .for_each(|(c, ((values, policy), european))| {
+                    run(c * PARALLEL_CHUNK, values, policy, Some(european))
+                }),
+            None => chunks
+                .enumerate()
+                .for_each(|(c, (values, policy))| run(c * PARALLEL_CHUNK, values, policy, None)),
+        }
+        copy_head(head.as_deref_mut(), i, values);
+    }
+
+    values[0]
+}
+
+/// `induct` for two payoffs on one lattice in a single pass. The hooks take
+/// and return both values of a node, so work such as the node spot is done
+/// once; there are no pinned nodes, European values or head.
+pub fn induct_pair<T, F, C>(
+    factors: &[StepFactors<T>],
+    early_exercise: bool,
+    mut exercise_values: F,
+    mut continuation: C,
+    buffers: [Buffers<'_, T>; 2],
+) -> [T; 2]
+where
+    T: Float,
+    F: FnMut(usize, usize) -> [T; 2],
+    C: FnMut(usize, usize, [T; 2]) -> [T; 2],
+{
+    let num_steps = factors.len();
+    let layout = buffers[0].layout;
+    assert!(buffers[1].layout == layout);
+    let len = layout.len(num_steps);
+    let [first, second] = buffers;
+    let mut sides = [(first.values, first.policy), (second.values, second.policy)];
+    assert!(sides
+        .iter()
+        .all(|(values, policy)| values.len() >= len && policy.len() >= len));
+
+    for i in (0..=num_steps).rev() {
+        let here = layout.index(i, 0);
+        let next = if i == num_steps {
+            here
+        } else {
+            layout.index(i + 1, 0)
+        };
+        for j in 0..=i {
+            let v_exercise = exercise_values(i, j);
+            if i == num_steps {
+                for ((values, policy), v_exercise) in sides.iter_mut().zip(v_exercise) {
+                    values[here + j] = v_exercise.max(T::zero());
+                    policy[here + j] = v_exercise >= T::zero();
+                }
+                continue;
+            }
+            let StepFactors {
+                up_prob, discount, ..
+            } = factors[i];
+            let expectation = |values: &[T]| {
+                let (down, up) = (values[next + j], values[next + j + 1]);
+                discount * (up_prob * up + (T::one() - up_prob) * down)
+            };
+            let v_continue =
+                continuation(i, j, [expectation(&*sides[0].0), expectation(&*sides[1].0)]);
+            for (((values, policy), v_exercise), v_continue) in
+                sides.iter_mut().zip(v_exercise).zip(v_continue)
+            {
+                let v_exercise = if early_exercise {
+                    v_exercise
+                } else {
+                    T::neg_infinity()
+                };
+                let exercise = v_exercise >= v_continue;
+                values[here + j] = if exercise { v_exercise } else { v_continue };
+                policy[here + j] = exercise;
+            }
+        }
+    }
+
+    [sides[0].0[0], sides[1].0[0]]
+}
+
+/// Values at the down and up successors of a node.
+struct Successors<T> {
+    values: [T; 2],
+    european: Option<[T; 2]>,
+}
+
+/// The per-node hooks of `induct`.
+struct Hooks<F, C, P> {
+    exercise_value: F,
+    continuation: C,
+    pinned: P,
+}
+
+impl<F, C, P> Hooks<F, C, P> {
+    /// Value, exercise decision and European value of node `(i, j)`, from
+    /// its successors or, at expiry, `None`. The European value is the
+    /// node value when the successors carry none.
+    fn node<T>(
+        &mut self,
+        factors: &[StepFactors<T>],
+        early_exercise: bool,
+        i: usize,
+        j: usize,
+        successors: Option<Successors<T>>,
+    ) -> (T, bool, T)
+    where
+        T: Float,
+        F: FnMut(usize, usize) -> T,
+        C: FnMut(usize, usize, T) -> T,
+        P: FnMut(usize, usize) -> Option<T>,
+    {
+        if let Some(value) = (self.pinned)(i, j) {
+            return (value, false, value);
+        }
+        let Some(successors) = successors else {
+            let v_exercise = (self.exercise_value)(i, j);
+            let value = v_exercise.max(T::zero());
+            return (value, v_exercise >= T::zero(), value);
+        };
+        let StepFactors {
+            up_prob, discount, ..
+        } = factors[i];
+        let expectation =
+            |[down, up]: [T; 2]| discount * (up_prob * up + (T::one() - up_prob) * down);
+
+        let v_exercise = if early_exercise {
+            (self.exercise_value)(i, j)
+        } else {
+            T::neg_infinity()
+        };
+        let v_continue = (self.continuation)(i, j, expectation(successors.values));
+        let exercise = v_exercise >= v_continue;
+        let value = if exercise { v_exercise } else { v_continue };
+        let european = successors.european.map_or(value, |european| {
+            (self.continuation)(i, j, expectation(european))
+        });
+        (value, exercise, european)
+    }
+}
+
+/// Runs `induct` through `run` over fresh triangular buffers.
+fn surface<T, I>(num_steps: usize, run: I) -> (Triangle<T>, Triangle<bool>)
+where
+    T: Float,
+    I: FnOnce(&mut [T], &mut [bool]) -> T,
+{
+    let mut values = Triangle::filled(num_steps, T::zero());
+    let mut policy = Triangle::filled(num_steps, false);
+    run(values.as_mut_slice(), policy.as_mut_slice());
+    (values, policy)
+}
+
+/// Per-node data of a `num_steps`-step lattice in one contiguous buffer,
+/// step by step from the root, node `(i, j)` at `i * (i + 1) / 2 + j`.
+///
+/// The flat buffer keeps rollbacks cache-friendly and can be handed to
+/// array libraries without copying through `as_slice` or `into_vec`. Serde
+/// still reads and writes it as one array per step.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Triangle<X> {
+    num_steps: usize,
+    data: Vec<X>,
+}
+
+impl<X> Triangle<X> {
+    pub fn filled(num_steps: usize, value: X) -> Self
+    where
+        X: Clone,
+    {
+        Triangle {
+            num_steps,
+            data: vec![value; Layout::Triangle.len(num_steps)],
+        }
+    }
+
+    /// Wraps a flat buffer; `None` unless it holds exactly the nodes of a
+    /// `num_steps`-step lattice.
+    pub fn from_vec(num_steps: usize, data: Vec<X>) -> Option<Self> {
+        (data.len() == Layout::Triangle.len(num_steps)).then_some(Triangle { num_steps, data })
+    }
+
+    /// Collects per-step layers; `None` unless layer `i` has `i + 1` nodes.
+    pub fn from_levels(levels: Vec<Vec<X>>) -> Option<Self> {
+        let num_steps = levels.len().checked_sub(1)?;
+        if levels
+            .iter()
+            .enumerate()
+            .any(|(i, level)| level.len() != i + 1)
+        {
+            return None;
+        }
+        Some(Triangle {
+            num_steps,
+            data: levels.into_iter().flatten().collect(),
+        })
+    }
+
+    pub fn num_steps(&self) -> usize {
+        self.num_steps
+    }
+
+    /// Position of node `(i, j)` in the flat buffer.
+    pub fn offset(i: usize, j: usize) -> usize {
+        Layout::Triangle.index(i, j)
+    }
+
+    pub fn get(&self, i: usize, j: usize) -> Option<&X> {
+        if i > self.num_steps || j > i {
+            return None;
+        }
+        self.data.get(Self::offset(i, j))
+    }
+
+    pub fn root(&self) -> &X {
+        &self.data[0]
+    }
+
+    /// The `i + 1` nodes at step `i`, ordered by up-move count.
+    pub fn level(&self, i: usize) -> &[X] {
+        let start = Self::offset(i, 0);
+        &self.data[start..=start + i]
+    }
+
+    /// Levels from the root to expiry.
+    pub fn levels(&self) -> impl Iterator<Item = &[X]> {
+        (0..=self.num_steps).map(move |i| self.level(i))
+    }
+
+    pub fn as_slice(&self) -> &[X] {
+        &self.data
+    }
+
+    pub fn as_mut_slice(&mut self) -> &mut [X] {
+        &mut self.data
+    }
+
+    pub fn into_vec(self) -> Vec<X> {
+        self.data
+    }
+}
+
+impl<X> Index<(usize, usize)> for Triangle<X> {
+    type Output = X;
+
+    fn index(&self, (i, j): (usize, usize)) -> &X {
+        assert!(j <= i, "node ({i}, {j}) is outside the lattice");
+        &self.data[Self::offset(i, j)]
+    }
+}
+
+impl<X: Serialize> Serialize for Triangle<X> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.levels())
+    }
+}
+
+impl<'de, X: Deserialize<'de>> Deserialize<'de> for Triangle<X> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let levels = Vec::<Vec<X>>::deserialize(deserializer)?;
+        Triangle::from_levels(levels)
+            .ok_or_else(|| D::Error::custom("expected step i to hold i + 1 nodes"))
+    }
+}
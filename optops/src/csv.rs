@@ -0,0 +1,80 @@
+//! Minimal reading and writing of numeric CSV tables, enough for the
+//! crate's own exports: comma separated, one header row, no embedded
+//! commas.
+
+use crate::error::{OptOpsError, Result};
+use std::fmt::Write;
+
+/// Each data row of `csv` as the numbers under `columns`, which the header
+/// row names in any order and case among any others. Blank lines are
+/// skipped.
+pub(crate) fn read(csv: &str, columns: &[&'static str]) -> Result<Vec<Vec<f64>>> {
+    let mut lines = csv
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty());
+    let (_, header) = lines
+        .next()
+        .ok_or(OptOpsError::MissingParameter("csv header"))?;
+    let header: Vec<String> = header
+        .split(',')
+        .map(|field| unquote(field).to_ascii_lowercase())
+        .collect();
+    let indices = columns
+        .iter()
+        .map(|&name| {
+            header
+                .iter()
+                .position(|field| field == name)
+                .ok_or(OptOpsError::MissingParameter(name))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    lines
+        .map(|(line_number, line)| {
+            let fields: Vec<&str> = line.split(',').collect();
+            indices
+                .iter()
+                .map(|&index| {
+                    let field = fields.get(index).map_or("", |field| unquote(field));
+                    field.parse().map_err(|err| {
+                        OptOpsError::Persistence(format!(
+                            "csv line {}, column {}: `{}`: {}",
+                            line_number + 1,
+                            index + 1,
+                            field,
+                            err
+                        ))
+                    })
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Whether the header row of `csv` names `column`.
+pub(crate) fn has_column(csv: &str, column: &str) -> bool {
+    csv.lines()
+        .find(|line| !line.trim().is_empty())
+        .is_some_and(|header| {
+            header
+                .split(',')
+                .any(|field| unquote(field).eq_ignore_ascii_case(column))
+        })
+}
+
+/// `rows` under a header row of `columns`, each number written in the
+/// shortest form that reads back exactly.
+pub(crate) fn write(columns: &[&str], rows: impl IntoIterator<Item = Vec<f64>>) -> String {
+    let mut csv = columns.join(",");
+    csv.push('\n');
+    for row in rows {
+        let fields: Vec<String> = row.iter().map(f64::to_string).collect();
+        // Writing to a String cannot fail.
+        let _ = writeln!(csv, "{}", fields.join(","));
+    }
+    csv
+}
+
+fn unquote(field: &str) -> &str {
+    field.trim().trim_matches('"').trim()
+}
@@ -0,0 +1,190 @@
+//! Deterministic interest-rate and volatility term structures.
+
+use crate::error::{Domain, OptOpsError, Result};
+use crate::float::{to_f64, Float};
+use crate::validate;
+use serde::{Deserialize, Serialize};
+
+/// Piecewise-constant, continuously compounded forward rates.
+///
+/// Each pillar `(end, forward)` sets the instantaneous rate from the previous
+/// pillar's end (or zero) up to `end`; the last forward extends flat beyond
+/// its pillar.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct RateCurve<T = f64> {
+    pillars: Vec<(T, T)>,
+}
+
+impl<T: Float> RateCurve<T> {
+    pub fn flat(rate: T) -> Self {
+        RateCurve {
+            pillars: vec![(T::zero(), rate)],
+        }
+    }
+
+    /// Curve from `(end time, forward rate)` pillars with strictly
+    /// increasing end times.
+    pub fn from_forwards(pillars: impl IntoIterator<Item = (T, T)>) -> Result<Self> {
+        let mut previous = T::zero();
+        let mut checked = Vec::new();
+        for (end, forward) in pillars {
+            check_after("pillar time", end, previous)?;
+            validate::finite("forward rate", forward)?;
+            checked.push((end, forward));
+            previous = end;
+        }
+        if checked.is_empty() {
+            return Err(OptOpsError::MissingParameter("pillars"));
+        }
+        Ok(RateCurve { pillars: checked })
+    }
+
+    /// Curve bootstrapped from `(time, discount factor)` pillars, i.e. a
+    /// discount curve with log-linear interpolation between pillars.
+    pub fn from_discount_factors(pillars: impl IntoIterator<Item = (T, T)>) -> Result<Self> {
+        let (mut previous_time, mut previous_df) = (T::zero(), T::one());
+        let mut forwards = Vec::new();
+        for (time, df) in pillars {
+            check_after("pillar time", time, previous_time)?;
+            validate::positive("discount factor", df)?;
+            forwards.push((time, (previous_df / df).ln() / (time - previous_time)));
+            (previous_time, previous_df) = (time, df);
+        }
+        RateCurve::from_forwards(forwards)
+    }
+
+    pub fn pillars(&self) -> &[(T, T)] {
+        &self.pillars
+    }
+
+    /// `∫₀ᵗ r(u) du`.
+    fn integral(&self, t: T) -> T {
+        integrate(&self.pillars, t, |forward| forward)
+    }
+
+    pub fn discount_factor(&self, t: T) -> T {
+        (-self.integral(t)).exp()
+    }
+
+    /// Continuously compounded zero rate to `t`.
+    pub fn zero_rate(&self, t: T) -> T {
+        if t > T::zero() {
+            self.integral(t) / t
+        } else {
+            self.forward(T::zero(), T::zero())
+        }
+    }
+
+    /// Average forward rate over `[t0, t1]`, or the instantaneous forward at
+    /// `t0` when the interval is empty.
+    pub fn forward(&self, t0: T, t1: T) -> T {
+        if t1 > t0 {
+            (self.integral(t1) - self.integral(t0)) / (t1 - t0)
+        } else {
+            self.pillars
+                .iter()
+                .find(|&&(end, _)| t0 < end)
+                .or(self.pillars.last())
+                .map_or(T::zero(), |&(_, forward)| forward)
+        }
+    }
+}
+
+/// Piecewise-constant forward volatility `sigma(t)`, with pillars laid out
+/// as in `RateCurve`: each `(end, vol)` applies up to `end` and the last vol
+/// extends flat.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct VolCurve<T = f64> {
+    pillars: Vec<(T, T)>,
+}
+
+impl<T: Float> VolCurve<T> {
+    /// Curve from `(end time, forward vol)` pillars with strictly increasing
+    /// end times.
+    pub fn from_forwards(pillars: impl IntoIterator<Item = (T, T)>) -> Result<Self> {
+        let mut previous = T::zero();
+        let mut checked = Vec::new();
+        for (end, vol) in pillars {
+            check_after("pillar time", end, previous)?;
+            validate::positive("forward vol", vol)?;
+            checked.push((end, vol));
+            previous = end;
+        }
+        if checked.is_empty() {
+            return Err(OptOpsError::MissingParameter("pillars"));
+        }
+        Ok(VolCurve { pillars: checked })
+    }
+
+    /// Curve bootstrapped from `(expiry, implied vol)` quotes; fails when the
+    /// total variance decreases between expiries (calendar arbitrage).
+    pub fn from_implied(quotes: impl IntoIterator<Item = (T, T)>) -> Result<Self> {
+        let (mut previous_time, mut previous_variance) = (T::zero(), T::zero());
+        let mut forwards = Vec::new();
+        for (time, vol) in quotes {
+            check_after("pillar time", time, previous_time)?;
+            validate::positive("implied vol", vol)?;
+            let variance = vol * vol * time;
+            let forward_variance = (variance - previous_variance) / (time - previous_time);
+            validate::positive("forward variance", forward_variance)?;
+            forwards.push((time, forward_variance.sqrt()));
+            (previous_time, previous_variance) = (time, variance);
+        }
+        VolCurve::from_forwards(forwards)
+    }
+
+    pub fn pillars(&self) -> &[(T, T)] {
+        &self.pillars
+    }
+
+    /// `∫₀ᵗ sigma(u)² du`.
+    pub fn total_variance(&self, t: T) -> T {
+        integrate(&self.pillars, t, |vol| vol * vol)
+    }
+
+    /// Flat vol with the same total variance to `t`, as Black-Scholes uses.
+    pub fn implied_vol(&self, t: T) -> T {
+        if t > T::zero() {
+            (self.total_variance(t) / t).sqrt()
+        } else {
+            self.pillars[0].1
+        }
+    }
+
+    /// Earliest time by which `variance` has accumulated.
+    pub fn time_for_variance(&self, variance: T) -> T {
+        let mut acc = T::zero();
+        let mut start = T::zero();
+        for &(end, vol) in &self.pillars {
+            let segment = vol * vol * (end - start);
+            if variance <= acc + segment {
+                return start + (variance - acc) / (vol * vol);
+            }
+            acc = acc + segment;
+            start = end;
+        }
+        let last = self.pillars[self.pillars.len() - 1].1;
+        start + (variance - acc) / (last * last)
+    }
+}
+
+/// `∫₀ᵗ f(x(u)) du` for the piecewise-constant `x` described by `pillars`.
+fn integrate<T: Float>(pillars: &[(T, T)], t: T, f: impl Fn(T) -> T) -> T {
+    let mut acc = T::zero();
+    let mut start = T::zero();
+    for &(end, value) in pillars {
+        if t <= end {
+            return acc + f(value) * (t - start);
+        }
+        acc = acc + f(value) * (end - start);
+        start = end;
+    }
+    let last = pillars.last().map_or(T::zero(), |&(_, value)| f(value));
+    acc + last * (t - start)
+}
+
+fn check_after<T: Float>(name: &'static str, value: T, bound: T) -> Result<T> {
+    validate::check(name, value, Domain::Above(to_f64(bound)))
+}
@@ -0,0 +1,131 @@
+//! Calendar-date expiries: day-count conventions and date-driven step counts.
+
+use crate::dividend::DividendSchedule;
+use crate::error::{OptOpsError, Result};
+use crate::float::{cast, Float};
+use crate::instrument::{ExerciseStyle, OptionSpec, OptionType};
+use crate::payoff::Payoff;
+use crate::tree::OptimalExerciseBinTreeBuilder;
+use crate::units::Strike;
+use chrono::{Datelike, NaiveDate, Weekday};
+use serde::{Deserialize, Serialize};
+
+/// Convention turning a pair of dates into a year fraction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DayCount {
+    /// Actual days over 365.
+    #[default]
+    Act365Fixed,
+    /// Actual days over 360.
+    Act360,
+    /// 30/360 bond basis: every month counts as 30 days.
+    Thirty360,
+}
+
+impl DayCount {
+    pub fn year_fraction(self, start: NaiveDate, end: NaiveDate) -> f64 {
+        match self {
+            DayCount::Act365Fixed => (end - start).num_days() as f64 / 365.0,
+            DayCount::Act360 => (end - start).num_days() as f64 / 360.0,
+            DayCount::Thirty360 => {
+                let d1 = start.day().min(30);
+                let d2 = if d1 == 30 {
+                    end.day().min(30)
+                } else {
+                    end.day()
+                };
+                let days = 360 * (end.year() - start.year())
+                    + 30 * (end.month() as i32 - start.month() as i32)
+                    + (d2 as i32 - d1 as i32);
+                days as f64 / 360.0
+            }
+        }
+    }
+}
+
+/// How many lattice steps to place between two dates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StepMode {
+    /// One step per calendar day.
+    CalendarDays,
+    /// One step per weekday; no holiday calendar is applied.
+    BusinessDays,
+}
+
+impl StepMode {
+    /// Number of days in `(start, end]` counted under this mode.
+    pub fn num_steps(self, start: NaiveDate, end: NaiveDate) -> usize {
+        match self {
+            StepMode::CalendarDays => (end - start).num_days().max(0) as usize,
+            StepMode::BusinessDays => start
+                .iter_days()
+                .skip(1)
+                .take_while(|d| *d <= end)
+                .filter(|d| !matches!(d.weekday(), Weekday::Sat | Weekday::Sun))
+                .count(),
+        }
+    }
+}
+
+fn check_order(valuation: NaiveDate, expiry: NaiveDate) -> Result<()> {
+    if expiry <= valuation {
+        return Err(OptOpsError::InvalidDates {
+            valuation: valuation.to_string(),
+            expiry: expiry.to_string(),
+        });
+    }
+    Ok(())
+}
+
+impl OptionSpec<f64> {
+    /// Contract whose expiry is the `day_count` year fraction between dates.
+    pub fn from_dates(
+        option_type: OptionType,
+        strike: Strike,
+        valuation: NaiveDate,
+        expiry: NaiveDate,
+        day_count: DayCount,
+        exercise: ExerciseStyle,
+    ) -> Result<Self> {
+        check_order(valuation, expiry)?;
+        let expiry = day_count.year_fraction(valuation, expiry);
+        Ok(OptionSpec::new(option_type, strike, expiry, exercise))
+    }
+}
+
+impl DividendSchedule<f64> {
+    /// Schedule from `(ex-date, cash amount)` pairs, timed from `valuation`
+    /// under `day_count`; every ex-date must fall after valuation.
+    pub fn from_dates(
+        valuation: NaiveDate,
+        dividends: impl IntoIterator<Item = (NaiveDate, f64)>,
+        day_count: DayCount,
+    ) -> Result<Self> {
+        let mut schedule = DividendSchedule::default();
+        for (ex_date, amount) in dividends {
+            check_order(valuation, ex_date)?;
+            schedule = schedule.with(day_count.year_fraction(valuation, ex_date), amount)?;
+        }
+        Ok(schedule)
+    }
+}
+
+impl<P: Payoff<T>, T: Float> OptimalExerciseBinTreeBuilder<P, T> {
+    /// Sets expiry from the dates under `day_count` and places one step per
+    /// day counted by `step_mode`.
+    pub fn dates(
+        self,
+        valuation: NaiveDate,
+        expiry: NaiveDate,
+        day_count: DayCount,
+        step_mode: StepMode,
+    ) -> Result<Self> {
+        check_order(valuation, expiry)?;
+        let year_fraction = day_count.year_fraction(valuation, expiry);
+        Ok(self
+            .expiry(cast(year_fraction))
+            .num_steps(step_mode.num_steps(valuation, expiry)))
+    }
+}
@@ -0,0 +1,130 @@
+//! Discrete dividends paid by the underlying before expiry.
+
+use crate::curve::RateCurve;
+use crate::error::{Domain, OptOpsError, Result};
+use crate::float::{to_f64, Float};
+use crate::validate;
+use serde::{Deserialize, Serialize};
+
+/// How a dividend's `amount` is expressed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DividendKind {
+    /// A fixed cash amount.
+    #[default]
+    Cash,
+    /// A fraction of the spot prevailing at the ex-time.
+    Proportional,
+}
+
+impl DividendKind {
+    pub fn is_cash(&self) -> bool {
+        *self == DividendKind::Cash
+    }
+}
+
+/// A dividend going ex at `time` years from valuation.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Dividend<T = f64> {
+    pub time: T,
+    pub amount: T,
+    #[serde(default, skip_serializing_if = "DividendKind::is_cash")]
+    pub kind: DividendKind,
+}
+
+/// Dividends ordered by ex-time.
+///
+/// Cash dividends are priced with the escrowed-dividend model: the tree
+/// diffuses the spot net of their present value, and each node adds back the
+/// value of those still to come before expiry. Proportional dividends scale
+/// the diffused spot down by `1 - amount` from their ex-time on, which keeps
+/// the lattice recombining. A dividend whose ex-time equals a node's time
+/// counts as already paid at that node.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct DividendSchedule<T = f64> {
+    dividends: Vec<Dividend<T>>,
+}
+
+impl<T> Default for DividendSchedule<T> {
+    fn default() -> Self {
+        DividendSchedule {
+            dividends: Vec::new(),
+        }
+    }
+}
+
+impl<T: Float> DividendSchedule<T> {
+    /// Builds a schedule from `(ex-time, cash amount)` pairs in any order.
+    pub fn new(dividends: impl IntoIterator<Item = (T, T)>) -> Result<Self> {
+        let mut schedule = DividendSchedule::default();
+        for (time, amount) in dividends {
+            schedule = schedule.with(time, amount)?;
+        }
+        Ok(schedule)
+    }
+
+    /// Adds one cash dividend, keeping the schedule sorted.
+    pub fn with(self, time: T, amount: T) -> Result<Self> {
+        validate::check("dividend amount", amount, Domain::NonNegative)?;
+        self.insert(time, amount, DividendKind::Cash)
+    }
+
+    /// Adds a dividend of `fraction` of the spot at `time`, e.g. `0.02` for 2%.
+    pub fn with_proportional(self, time: T, fraction: T) -> Result<Self> {
+        validate::check("dividend fraction", fraction, Domain::Between(0.0, 1.0))?;
+        self.insert(time, fraction, DividendKind::Proportional)
+    }
+
+    fn insert(mut self, time: T, amount: T, kind: DividendKind) -> Result<Self> {
+        validate::check("dividend time", time, Domain::NonNegative)?;
+        let at = self.dividends.partition_point(|d| d.time <= time);
+        self.dividends.insert(at, Dividend { time, amount, kind });
+        Ok(self)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.dividends.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Dividend<T>> {
+        self.dividends.iter()
+    }
+
+    /// Value at time `from` of the cash dividends going ex in `(from, to]`,
+    /// discounted along `curve`.
+    pub fn present_value(&self, from: T, to: T, curve: &RateCurve<T>) -> T {
+        let df_from = curve.discount_factor(from);
+        self.dividends
+            .iter()
+            .filter(|d| d.kind.is_cash() && d.time > from && d.time <= to)
+            .fold(T::zero(), |pv, d| {
+                pv + d.amount * curve.discount_factor(d.time) / df_from
+            })
+    }
+
+    /// Fraction of the spot left after the proportional dividends going ex
+    /// in `(0, to]`.
+    pub fn retained_fraction(&self, to: T) -> T {
+        self.dividends
+            .iter()
+            .filter(|d| !d.kind.is_cash() && d.time > T::zero() && d.time <= to)
+            .fold(T::one(), |retained, d| retained * (T::one() - d.amount))
+    }
+
+    /// `spot` net of the dividends paid before `expiry`, which must stay
+    /// positive for the escrowed process to make sense.
+    pub fn escrowed_spot(&self, spot: T, curve: &RateCurve<T>, expiry: T) -> Result<T> {
+        let pv = self.present_value(T::zero(), expiry, curve);
+        let escrowed = spot - pv;
+        if escrowed > T::zero() {
+            Ok(escrowed)
+        } else {
+            Err(OptOpsError::InvalidParameter {
+                name: "spot",
+                value: to_f64(spot),
+                domain: Domain::Above(to_f64(pv)),
+            })
+        }
+    }
+}
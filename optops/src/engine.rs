@@ -0,0 +1,111 @@
+//! Common interface over the tree, closed-form and future pricing engines.
+
+use crate::american::{BaroneAdesiWhaleyEngine, BjerksundStenslandEngine, JuZhongEngine};
+use crate::analytic::BlackScholesEngine;
+use crate::error::Result;
+use crate::instrument::OptionSpec;
+use crate::market::Market;
+use crate::mc::{LongstaffSchwartz, MonteCarloEngine};
+use crate::pde::FiniteDifference;
+use crate::tree::{BinomialEngine, ValueFunction};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Price plus the metadata needed to log and compare runs.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PricingResult {
+    pub price: f64,
+    /// American price minus the matching European price, when the engine
+    /// can compute both.
+    pub early_exercise_premium: Option<f64>,
+    pub engine: String,
+    /// Lattice or time-grid resolution, for engines that have one.
+    pub num_steps: Option<usize>,
+    /// The engine's own estimate of its discretisation or sampling error,
+    /// if it has one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error_estimate: Option<f64>,
+    /// 95% confidence interval of a sampled price.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confidence_interval: Option<(f64, f64)>,
+    pub elapsed: Duration,
+    /// Sensitivities, for engines asked to compute them.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub greeks: Option<Greeks>,
+    /// Full value surface, only kept when the engine is asked to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value_function: Option<ValueFunction>,
+}
+
+impl PricingResult {
+    pub fn new(engine: &str, price: f64, elapsed: Duration) -> Self {
+        PricingResult {
+            price,
+            early_exercise_premium: None,
+            engine: engine.to_string(),
+            num_steps: None,
+            error_estimate: None,
+            confidence_interval: None,
+            elapsed,
+            greeks: None,
+            value_function: None,
+        }
+    }
+
+    /// Price of the European counterpart implied by the premium, if known.
+    pub fn european_price(&self) -> Option<f64> {
+        self.early_exercise_premium
+            .map(|premium| self.price - premium)
+    }
+}
+
+/// Price sensitivities per unit of spot, year of time, vol and rate. Vega
+/// and rho are `None` where the engine does not compute them.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Greeks<T = f64> {
+    pub delta: T,
+    pub gamma: T,
+    /// Change in value as calendar time passes, per year.
+    pub theta: T,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vega: Option<T>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rho: Option<T>,
+}
+
+pub type PriceResult = Result<PricingResult>;
+
+/// Anything that can turn a contract and a market snapshot into a price.
+pub trait PricingEngine {
+    fn price(&self, instrument: &OptionSpec, market: &Market) -> PriceResult;
+}
+
+/// Serialisable choice of engine and its settings, dispatching to the
+/// concrete engine so runs can be recorded and replayed.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "engine", rename_all = "snake_case")]
+pub enum EngineConfig {
+    Binomial(BinomialEngine),
+    BlackScholes,
+    BaroneAdesiWhaley,
+    BjerksundStensland(BjerksundStenslandEngine),
+    JuZhong,
+    MonteCarlo(MonteCarloEngine),
+    LongstaffSchwartz(LongstaffSchwartz),
+    FiniteDifference(FiniteDifference),
+}
+
+impl PricingEngine for EngineConfig {
+    fn price(&self, instrument: &OptionSpec, market: &Market) -> PriceResult {
+        match self {
+            EngineConfig::Binomial(engine) => engine.price(instrument, market),
+            EngineConfig::BlackScholes => BlackScholesEngine.price(instrument, market),
+            EngineConfig::BaroneAdesiWhaley => BaroneAdesiWhaleyEngine.price(instrument, market),
+            EngineConfig::BjerksundStensland(engine) => engine.price(instrument, market),
+            EngineConfig::JuZhong => JuZhongEngine.price(instrument, market),
+            EngineConfig::MonteCarlo(engine) => engine.price(instrument, market),
+            EngineConfig::LongstaffSchwartz(engine) => engine.price(instrument, market),
+            EngineConfig::FiniteDifference(engine) => engine.price(instrument, market),
+        }
+    }
+}
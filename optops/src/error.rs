@@ -0,0 +1,149 @@
+//! Error type shared by every pricer in the crate.
+
+use std::fmt;
+
+/// Range of values a parameter is allowed to take.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Domain {
+    /// Any finite number.
+    Finite,
+    /// Finite and strictly greater than zero.
+    Positive,
+    /// Finite and greater than or equal to zero.
+    NonNegative,
+    /// Finite and at least the given bound.
+    AtLeast(f64),
+    /// Finite and strictly greater than the given bound.
+    Above(f64),
+    /// Finite and inside the closed interval.
+    Between(f64, f64),
+    /// Finite and strictly inside the open interval.
+    Inside(f64, f64),
+    /// Finite, at least the first bound and strictly below the second.
+    HalfOpen(f64, f64),
+}
+
+impl Domain {
+    pub fn contains(&self, value: f64) -> bool {
+        value.is_finite()
+            && match *self {
+                Domain::Finite => true,
+                Domain::Positive => value > 0.0,
+                Domain::NonNegative => value >= 0.0,
+                Domain::AtLeast(min) => value >= min,
+                Domain::Above(min) => value > min,
+                Domain::Between(lo, hi) => (lo..=hi).contains(&value),
+                Domain::Inside(lo, hi) => lo < value && value < hi,
+                Domain::HalfOpen(lo, hi) => (lo..hi).contains(&value),
+            }
+    }
+}
+
+impl fmt::Display for Domain {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Domain::Finite => write!(f, "a finite number"),
+            Domain::Positive => write!(f, "a finite number > 0"),
+            Domain::NonNegative => write!(f, "a finite number >= 0"),
+            Domain::AtLeast(min) => write!(f, "a finite number >= {}", min),
+            Domain::Above(min) => write!(f, "a finite number > {}", min),
+            Domain::Between(lo, hi) => write!(f, "a finite number in [{}, {}]", lo, hi),
+            Domain::Inside(lo, hi) => write!(f, "a finite number in ({}, {})", lo, hi),
+            Domain::HalfOpen(lo, hi) => write!(f, "a finite number in [{}, {})", lo, hi),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum OptOpsError {
+    /// A required input was never supplied.
+    MissingParameter(&'static str),
+    /// An input is outside the range the model can handle.
+    InvalidParameter {
+        name: &'static str,
+        value: f64,
+        domain: Domain,
+    },
+    /// Chart rendering failed; carries the backend's message.
+    Plotting(String),
+    /// An iterative solver stopped before reaching its tolerance.
+    NonConvergence {
+        solver: &'static str,
+        iterations: usize,
+    },
+    /// The expiry date does not fall after the valuation date.
+    InvalidDates { valuation: String, expiry: String },
+    /// The engine cannot price this kind of instrument.
+    Unsupported {
+        engine: &'static str,
+        reason: &'static str,
+    },
+    /// Reading, writing or (de)serialising a record, export or data file
+    /// failed.
+    Persistence(String),
+    /// A replayed run did not reproduce its recorded output.
+    ReplayMismatch {
+        recorded: f64,
+        replayed: f64,
+        tolerance: f64,
+    },
+    /// A record's inputs no longer hash to the value stored with them.
+    HashMismatch { recorded: u64, computed: u64 },
+}
+
+impl fmt::Display for OptOpsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OptOpsError::MissingParameter(name) => {
+                write!(f, "missing required parameter `{}`", name)
+            }
+            OptOpsError::InvalidParameter {
+                name,
+                value,
+                domain,
+            } => write!(
+                f,
+                "`{}` = {} is out of range; expected {}",
+                name, value, domain
+            ),
+            OptOpsError::Plotting(msg) => write!(f, "failed to draw chart: {}", msg),
+            OptOpsError::NonConvergence { solver, iterations } => write!(
+                f,
+                "{} did not converge after {} iterations",
+                solver, iterations
+            ),
+            OptOpsError::InvalidDates { valuation, expiry } => write!(
+                f,
+                "expiry {} must be after valuation date {}",
+                expiry, valuation
+            ),
+            OptOpsError::Unsupported { engine, reason } => {
+                write!(f, "{} engine does not support {}", engine, reason)
+            }
+            OptOpsError::Persistence(msg) => write!(f, "failed to persist record: {}", msg),
+            OptOpsError::ReplayMismatch {
+                recorded,
+                replayed,
+                tolerance,
+            } => write!(
+                f,
+                "replayed price {} differs from recorded {} by more than {:e}",
+                replayed, recorded, tolerance
+            ),
+            OptOpsError::HashMismatch { recorded, computed } => write!(
+                f,
+                "record hash {:016x} does not match its inputs ({:016x})",
+                recorded, computed
+            ),
+        }
+    }
+}
+
+impl std::error::Error for OptOpsError {}
+
+pub type Result<T> = std::result::Result<T, OptOpsError>;
+
+/// Wraps an I/O or serialisation failure.
+pub(crate) fn persistence<E: fmt::Display>(err: E) -> OptOpsError {
+    OptOpsError::Persistence(err.to_string())
+}
@@ -0,0 +1,19 @@
+//! Floating-point abstraction so the pricers run in `f32`, `f64` or any
+//! other `num_traits::Float` implementation (e.g. dual numbers).
+
+/// Scalar the pricers are generic over: any `num_traits::Float` that can be
+/// shared across threads.
+pub trait Float: num_traits::Float + Send + Sync {}
+
+impl<T: num_traits::Float + Send + Sync> Float for T {}
+
+/// Converts an `f64` constant into `T`, yielding NaN if it is unrepresentable.
+pub(crate) fn cast<T: Float>(x: f64) -> T {
+    T::from(x).unwrap_or_else(T::nan)
+}
+
+/// Lossy view of `value` as `f64`, used for diagnostics and plotting.
+#[cfg(feature = "std")]
+pub(crate) fn to_f64<T: Float>(value: T) -> f64 {
+    value.to_f64().unwrap_or(f64::NAN)
+}
@@ -0,0 +1,295 @@
+//! Contract terms of the options the engines price.
+
+use crate::barrier::Barrier;
+use crate::float::Float;
+use crate::payoff::{Payoff, VanillaCall, VanillaPut};
+use crate::units::Strike;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OptionType {
+    Call,
+    Put,
+}
+
+impl OptionType {
+    /// `max(s - strike, 0)` for calls, `max(strike - s, 0)` for puts.
+    pub fn intrinsic<T: Float>(self, s: T, strike: T) -> T {
+        match self {
+            OptionType::Call => (s - strike).max(T::zero()),
+            OptionType::Put => (strike - s).max(T::zero()),
+        }
+    }
+}
+
+/// When the holder is allowed to exercise.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExerciseStyle {
+    /// Only at expiry.
+    European,
+    /// At any step up to and including expiry.
+    #[default]
+    American,
+}
+
+/// A vanilla option contract, optionally with a knock-in or knock-out
+/// barrier.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct OptionSpec<T = f64> {
+    pub option_type: OptionType,
+    pub strike: T,
+    /// Time to expiry as a year fraction.
+    pub expiry: T,
+    pub exercise: ExerciseStyle,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub barrier: Option<Barrier<T>>,
+}
+
+impl<T: Float> OptionSpec<T> {
+    pub fn new(
+        option_type: OptionType,
+        strike: Strike<T>,
+        expiry: T,
+        exercise: ExerciseStyle,
+    ) -> Self {
+        OptionSpec {
+            option_type,
+            strike: strike.0,
+            expiry,
+            exercise,
+            barrier: None,
+        }
+    }
+
+    pub fn american(option_type: OptionType, strike: Strike<T>, expiry: T) -> Self {
+        OptionSpec::new(option_type, strike, expiry, ExerciseStyle::American)
+    }
+
+    pub fn european(option_type: OptionType, strike: Strike<T>, expiry: T) -> Self {
+        OptionSpec::new(option_type, strike, expiry, ExerciseStyle::European)
+    }
+
+    pub fn is_call(&self) -> bool {
+        self.option_type == OptionType::Call
+    }
+
+    /// The same contract with its exercise style replaced.
+    pub fn with_exercise(self, exercise: ExerciseStyle) -> Self {
+        OptionSpec { exercise, ..self }
+    }
+
+    pub fn with_barrier(self, barrier: Barrier<T>) -> Self {
+        OptionSpec {
+            barrier: Some(barrier),
+            ..self
+        }
+    }
+}
+
+/// A spec is its own exercise payoff: the vanilla intrinsic value.
+impl<T: Float> Payoff<T> for OptionSpec<T> {
+    fn value(&self, _t: T, s: T) -> T {
+        self.option_type.intrinsic(s, self.strike)
+    }
+
+    fn strike(&self) -> Option<T> {
+        Some(self.strike)
+    }
+
+    fn option_type(&self) -> Option<OptionType> {
+        Some(self.option_type)
+    }
+
+    fn cell_average(&self, t: T, lower: T, upper: T) -> T {
+        let strike = self.strike;
+        match self.option_type {
+            OptionType::Call => VanillaCall { strike }.cell_average(t, lower, upper),
+            OptionType::Put => VanillaPut { strike }.cell_average(t, lower, upper),
+        }
+    }
+}
+
+/// Which mean of the sampled spots an Asian option settles on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Average {
+    Arithmetic,
+    Geometric,
+}
+
+/// When an Asian option samples the spot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Sampling {
+    /// Over the whole life, continuously.
+    Continuous,
+    /// At this many evenly spaced fixings, the last at expiry.
+    Discrete(usize),
+}
+
+/// A European average-price option paying the vanilla intrinsic value of
+/// the average spot, sampled from today to expiry.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct AsianSpec<T = f64> {
+    pub option_type: OptionType,
+    pub strike: T,
+    /// Time to expiry as a year fraction.
+    pub expiry: T,
+    pub average: Average,
+    pub sampling: Sampling,
+}
+
+impl<T: Float> AsianSpec<T> {
+    pub fn new(
+        option_type: OptionType,
+        strike: Strike<T>,
+        expiry: T,
+        average: Average,
+        sampling: Sampling,
+    ) -> Self {
+        AsianSpec {
+            option_type,
+            strike: strike.0,
+            expiry,
+            average,
+            sampling,
+        }
+    }
+
+    /// The same contract settling on the other mean, as a control variate
+    /// pairs them.
+    pub fn with_average(self, average: Average) -> Self {
+        AsianSpec { average, ..self }
+    }
+}
+
+/// What a lookback option is struck at.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LookbackStrike<T = f64> {
+    /// The path's minimum for calls and its maximum for puts, so the
+    /// holder buys at the low or sells at the high.
+    Floating,
+    /// A fixed strike, paid against the path's maximum for calls and its
+    /// minimum for puts.
+    Fixed(T),
+}
+
+/// A European lookback option on the continuously monitored extreme of
+/// the spot.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct LookbackSpec<T = f64> {
+    pub option_type: OptionType,
+    pub strike: LookbackStrike<T>,
+    /// Time to expiry as a year fraction.
+    pub expiry: T,
+    /// The extreme the contract settles on as observed so far: the minimum
+    /// for floating calls and fixed puts, the maximum otherwise. `None`
+    /// starts monitoring today, at the spot.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extreme: Option<T>,
+}
+
+impl<T: Float> LookbackSpec<T> {
+    pub fn floating(option_type: OptionType, expiry: T) -> Self {
+        LookbackSpec {
+            option_type,
+            strike: LookbackStrike::Floating,
+            expiry,
+            extreme: None,
+        }
+    }
+
+    pub fn fixed(option_type: OptionType, strike: Strike<T>, expiry: T) -> Self {
+        LookbackSpec {
+            option_type,
+            strike: LookbackStrike::Fixed(strike.0),
+            expiry,
+            extreme: None,
+        }
+    }
+
+    /// The same contract part-way through its life, with `extreme` already
+    /// observed.
+    pub fn with_extreme(self, extreme: T) -> Self {
+        LookbackSpec {
+            extreme: Some(extreme),
+            ..self
+        }
+    }
+}
+
+/// A European option expiring at `expiry` to buy (calls) or sell (puts)
+/// the European option `underlying` for `strike`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(bound(deserialize = "T: Default + Deserialize<'de>"))]
+pub struct CompoundSpec<T = f64> {
+    pub option_type: OptionType,
+    pub strike: T,
+    /// Time to the compound's own expiry, before the underlying's.
+    pub expiry: T,
+    pub underlying: OptionSpec<T>,
+}
+
+impl<T: Float> CompoundSpec<T> {
+    pub fn new(
+        option_type: OptionType,
+        strike: Strike<T>,
+        expiry: T,
+        underlying: OptionSpec<T>,
+    ) -> Self {
+        CompoundSpec {
+            option_type,
+            strike: strike.0,
+            expiry,
+            underlying,
+        }
+    }
+}
+
+/// A European option whose strike is set at `start` to `moneyness` times
+/// the spot then, expiring at `expiry`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ForwardStartSpec<T = f64> {
+    pub option_type: OptionType,
+    /// Strike as a fraction of the spot at `start`; one for at the money.
+    pub moneyness: T,
+    /// Time to the strike being set as a year fraction.
+    pub start: T,
+    /// Time to expiry as a year fraction.
+    pub expiry: T,
+}
+
+impl<T: Float> ForwardStartSpec<T> {
+    pub fn new(option_type: OptionType, moneyness: T, start: T, expiry: T) -> Self {
+        ForwardStartSpec {
+            option_type,
+            moneyness,
+            start,
+            expiry,
+        }
+    }
+}
+
+/// A simple chooser: at `choose_at` the holder picks a European call or
+/// put, both struck at `strike` and expiring at `expiry`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ChooserSpec<T = f64> {
+    pub strike: T,
+    /// Time to the choice as a year fraction, at most `expiry`.
+    pub choose_at: T,
+    /// Time to expiry as a year fraction.
+    pub expiry: T,
+}
+
+impl<T: Float> ChooserSpec<T> {
+    pub fn new(strike: Strike<T>, choose_at: T, expiry: T) -> Self {
+        ChooserSpec {
+            strike: strike.0,
+            choose_at,
+            expiry,
+        }
+    }
+}
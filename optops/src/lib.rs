@@ -0,0 +1,128 @@
+//! Option pricing generic over the float type: closed forms and American
+//! approximations in `analytic` and `american`, binomial lattices that
+//! roll the exercise decision back as a finite-horizon MDP in `tree`,
+//! Monte Carlo path engines in `mc` and finite-difference schemes in
+//! `pde`, all priced against a `market::Market` whose vol may be a term
+//! structure or one of the fitted smiles and surfaces of `surface`. Every
+//! pricer generic over `Float` also runs on the `adjoint` tape, which
+//! returns the price's sensitivity to each market input from one backward
+//! sweep.
+//!
+//! With the default `std` feature disabled only the [`core`] kernel and the
+//! contract and payoff types it consumes are built; they need nothing
+//! beyond `alloc`.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+pub mod barrier;
+pub mod core;
+pub mod float;
+pub mod instrument;
+pub mod payoff;
+pub mod units;
+
+#[cfg(feature = "std")]
+pub mod adjoint;
+#[cfg(feature = "std")]
+pub mod american;
+#[cfg(feature = "std")]
+pub mod analytic;
+#[cfg(feature = "std")]
+pub mod compounding;
+#[cfg(feature = "std")]
+mod csv;
+#[cfg(feature = "std")]
+pub mod curve;
+#[cfg(feature = "std")]
+pub mod daycount;
+#[cfg(feature = "std")]
+pub mod dividend;
+#[cfg(feature = "std")]
+pub mod engine;
+#[cfg(feature = "std")]
+pub mod error;
+#[cfg(feature = "std")]
+pub mod market;
+#[cfg(feature = "std")]
+pub mod mc;
+#[cfg(feature = "std")]
+mod optimize;
+#[cfg(feature = "std")]
+pub mod pde;
+#[cfg(feature = "plots")]
+pub mod plot;
+#[cfg(feature = "std")]
+pub mod realized_vol;
+#[cfg(feature = "std")]
+pub mod record;
+#[cfg(feature = "decimal")]
+pub mod report;
+#[cfg(feature = "std")]
+pub mod surface;
+#[cfg(feature = "std")]
+pub mod tree;
+#[cfg(feature = "std")]
+mod validate;
+
+pub use crate::core::{TreeScheme, Triangle};
+pub use barrier::{Barrier, BarrierKind};
+pub use float::Float;
+pub use instrument::{
+    AsianSpec, Average, ChooserSpec, CompoundSpec, ExerciseStyle, ForwardStartSpec, LookbackSpec,
+    LookbackStrike, OptionSpec, OptionType, Sampling,
+};
+pub use payoff::{Payoff, PayoffExt};
+pub use units::{Rate, Spot, Strike, Vol};
+
+#[cfg(feature = "std")]
+pub use adjoint::{Adjoint, Gradient, MarketGradient, Tape};
+#[cfg(feature = "std")]
+pub use american::{
+    AmericanApprox, BaroneAdesiWhaleyEngine, BjerksundStenslandEngine, JuZhongEngine,
+};
+#[cfg(feature = "std")]
+pub use analytic::{
+    BlackScholesEngine, ChainQuote, ChainVol, Digital, HigherOrderGreeks, ImpliedVolSolver, Model,
+    QuoteFlag,
+};
+#[cfg(feature = "std")]
+pub use compounding::Compounding;
+#[cfg(feature = "std")]
+pub use curve::{RateCurve, VolCurve};
+#[cfg(feature = "std")]
+pub use daycount::{DayCount, StepMode};
+#[cfg(feature = "std")]
+pub use dividend::{Dividend, DividendKind, DividendSchedule};
+#[cfg(feature = "std")]
+pub use engine::{EngineConfig, Greeks, PriceResult, PricingEngine, PricingResult};
+#[cfg(feature = "std")]
+pub use error::{Domain, OptOpsError};
+#[cfg(feature = "std")]
+pub use market::{AssetPair, Market, Quanto, Underlying};
+#[cfg(feature = "std")]
+pub use mc::{
+    AsianMonteCarlo, AtExpiry, Basis, BrownianBridge, Combination, ControlVariate, Convergence,
+    ConvergenceBatch, ConvergenceReport, DriftShift, ExerciseRegion, Gbm, GreekMethod, Heston,
+    HestonScheme, JumpDiffusion, JumpSizes, LongstaffSchwartz, LsmExercise, MonteCarloEngine,
+    MonteCarloEstimate, MonteCarloGreeks, MultiAssetOption, MultiGbm, PathGenerator, PathModel,
+    PathPayoff, Paths, Philox, RandomSource, Rng, Sequence, Sobol,
+};
+#[cfg(feature = "std")]
+pub use pde::{EarlyExercise, FiniteDifference, PdeScheme, PdeSolution};
+#[cfg(feature = "std")]
+pub use realized_vol::{Bar, Estimator, Ewma, Garch, RealizedVol};
+#[cfg(feature = "std")]
+pub use record::{replay, RunRecord};
+#[cfg(feature = "decimal")]
+pub use report::{DecimalGreeks, DecimalResult, Rounding};
+#[cfg(feature = "std")]
+pub use surface::{
+    Arbitrage, Extrapolation, LocalVol, Sabr, SmileInterpolation, StrikeAxis, Svi, VolSurface,
+};
+#[cfg(feature = "std")]
+pub use tree::{
+    BinomialEngine, CallPut, ImpliedTree, Node, OptimalExerciseBinTree,
+    OptimalExerciseBinTreeBuilder, PremiumDecomposition, TreeSolution, TreeView,
+};
@@ -0,0 +1,338 @@
+//! Market observables an instrument is priced against.
+
+use crate::compounding::Compounding;
+use crate::curve::{RateCurve, VolCurve};
+use crate::dividend::DividendSchedule;
+use crate::error::{Domain, Result};
+use crate::float::Float;
+use crate::surface::VolSurface;
+use crate::units::{Rate, Spot, Vol};
+use crate::validate;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+
+/// Market snapshot: spot, rate quoted under `compounding` (or a full rate
+/// curve), volatility (or a vol term structure or surface), a continuously
+/// compounded dividend (or foreign-rate) yield, any discrete dividends and,
+/// for options paid in another currency, quanto terms.
+///
+/// Kept apart from contract terms so the same instrument or tree can be
+/// repriced under many snapshots.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(bound(
+    serialize = "T: Float + Serialize",
+    deserialize = "T: Float + Deserialize<'de>"
+))]
+pub struct Market<T = f64> {
+    pub spot: T,
+    pub rate: T,
+    pub vol: T,
+    /// Zero unless set; omitted from serialised snapshots when zero.
+    #[serde(default = "zero", skip_serializing_if = "is_zero")]
+    pub dividend_yield: T,
+    #[serde(default, skip_serializing_if = "DividendSchedule::is_empty")]
+    pub dividends: DividendSchedule<T>,
+    /// Omitted from serialised snapshots when continuous, so older records
+    /// keep their hashes.
+    #[serde(default, skip_serializing_if = "Compounding::is_continuous")]
+    pub compounding: Compounding,
+    /// Term structure that, when set, replaces the flat `rate` and its
+    /// `compounding` everywhere.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rate_curve: Option<RateCurve<T>>,
+    /// Deterministic `sigma(t)` that, when set, replaces the flat `vol`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vol_curve: Option<VolCurve<T>>,
+    /// Implied vols by expiry and strike that, when set, replace `vol` and
+    /// `vol_curve` wherever the strike is known; see `vol_for`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vol_surface: Option<VolSurface<T>>,
+    /// Set when the underlying is quoted in a foreign currency but the
+    /// option pays in the domestic one, whose rate `rate` then is.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quanto: Option<Quanto<T>>,
+}
+
+impl<T: Float> Market<T> {
+    pub fn new(spot: Spot<T>, rate: Rate<T>, vol: Vol<T>) -> Self {
+        Market {
+            spot: spot.0,
+            rate: rate.0,
+            vol: vol.0,
+            dividend_yield: T::zero(),
+            dividends: DividendSchedule::default(),
+            compounding: Compounding::Continuous,
+            rate_curve: None,
+            vol_curve: None,
+            vol_surface: None,
+            quanto: None,
+        }
+    }
+
+    /// Checks every field against its accepted domain.
+    pub fn validate(&self) -> Result<()> {
+        validate::positive("spot", self.spot)?;
+        validate::finite("rate", self.rate)?;
+        validate::positive("vol", self.vol)?;
+        validate::finite("dividend_yield", self.dividend_yield)?;
+        if let Some(quanto) = &self.quanto {
+            quanto.validate()?;
+        }
+        Ok(())
+    }
+
+    pub fn with_spot(self, spot: Spot<T>) -> Self {
+        Market {
+            spot: spot.0,
+            ..self
+        }
+    }
+
+    pub fn with_rate(self, rate: Rate<T>) -> Self {
+        Market {
+            rate: rate.0,
+            ..self
+        }
+    }
+
+    pub fn with_vol(self, vol: Vol<T>) -> Self {
+        Market { vol: vol.0, ..self }
+    }
+
+    /// Sets a continuously compounded yield; a `Rate` so quotes in percent
+    /// or basis points convert the same way as the interest rate.
+    pub fn with_dividend_yield(self, dividend_yield: Rate<T>) -> Self {
+        Market {
+            dividend_yield: dividend_yield.0,
+            ..self
+        }
+    }
+
+    /// Adds a stock-borrow cost to the continuous yield: a hard-to-borrow
+    /// fee lowers the carry of the stock exactly as a dividend yield does.
+    pub fn with_borrow_cost(self, borrow_cost: Rate<T>) -> Self {
+        Market {
+            dividend_yield: self.dividend_yield + borrow_cost.0,
+            ..self
+        }
+    }
+
+    pub fn with_dividends(self, dividends: DividendSchedule<T>) -> Self {
+        Market { dividends, ..self }
+    }
+
+    pub fn with_compounding(self, compounding: Compounding) -> Self {
+        Market {
+            compounding,
+            ..self
+        }
+    }
+
+    pub fn with_rate_curve(self, rate_curve: RateCurve<T>) -> Self {
+        Market {
+            rate_curve: Some(rate_curve),
+            ..self
+        }
+    }
+
+    pub fn with_vol_curve(self, vol_curve: VolCurve<T>) -> Self {
+        Market {
+            vol_curve: Some(vol_curve),
+            ..self
+        }
+    }
+
+    pub fn with_vol_surface(self, vol_surface: VolSurface<T>) -> Self {
+        Market {
+            vol_surface: Some(vol_surface),
+            ..self
+        }
+    }
+
+    /// Pays the option in domestic currency at a fixed exchange rate.
+    pub fn with_quanto(self, quanto: Quanto<T>) -> Self {
+        Market {
+            quanto: Some(quanto),
+            ..self
+        }
+    }
+
+    /// Continuous yield that, against the domestic `rate`, sets the drift of
+    /// an underlying with vol `vol`: the dividend yield itself, or under a
+    /// quanto whatever turns the drift into `r_f - q - rho vol fx_vol`.
+    pub fn carry_yield(&self, rate: T, vol: T) -> T {
+        match &self.quanto {
+            Some(quanto) => {
+                self.dividend_yield + rate - quanto.foreign_rate
+                    + quanto.correlation * vol * quanto.fx_vol
+            }
+            None => self.dividend_yield,
+        }
+    }
+
+    /// Flat vol carrying the same total variance to `horizon` as the vol
+    /// curve, or the flat `vol` when there is none.
+    pub fn effective_vol(&self, horizon: T) -> T {
+        match &self.vol_curve {
+            Some(curve) => curve.implied_vol(horizon),
+            None => self.vol,
+        }
+    }
+
+    /// Vol for an option struck at `strike` expiring at `expiry`: read off
+    /// the vol surface if there is one, otherwise `effective_vol`.
+    pub fn vol_for(&self, strike: T, expiry: T) -> Result<T> {
+        match &self.vol_surface {
+            Some(surface) => surface.vol(expiry, strike, self.forward(expiry)?),
+            None => Ok(self.effective_vol(expiry)),
+        }
+    }
+
+    /// The underlying's forward to `expiry` in its own currency, net of
+    /// dividends, as vol surfaces measure moneyness against it.
+    pub fn forward(&self, expiry: T) -> Result<T> {
+        let rate = self.continuous_rate(expiry)?;
+        let spot = self.escrowed_spot(expiry)? * self.dividends.retained_fraction(expiry);
+        Ok(spot * ((rate - self.carry_yield(rate, T::zero())) * expiry).exp())
+    }
+
+    /// Continuously compounded zero rate to `horizon`: read off the curve if
+    /// there is one, otherwise converted from the quoted flat rate.
+    pub fn continuous_rate(&self, horizon: T) -> Result<T> {
+        match &self.rate_curve {
+            Some(curve) => Ok(curve.zero_rate(horizon)),
+            None => self.compounding.continuous_rate(self.rate, horizon),
+        }
+    }
+
+    /// The rate curve, or a flat curve at the quoted rate's continuous
+    /// equivalent over `horizon`.
+    pub fn discount_curve(&self, horizon: T) -> Result<Cow<'_, RateCurve<T>>> {
+        match &self.rate_curve {
+            Some(curve) => Ok(Cow::Borrowed(curve)),
+            None => Ok(Cow::Owned(RateCurve::flat(self.continuous_rate(horizon)?))),
+        }
+    }
+
+    /// Spot net of the present value of cash dividends paid up to `expiry`.
+    pub fn escrowed_spot(&self, expiry: T) -> Result<T> {
+        let curve = self.discount_curve(expiry)?;
+        self.dividends.escrowed_spot(self.spot, &curve, expiry)
+    }
+}
+
+/// Currency terms of a quanto option: the underlying's own currency rate,
+/// the vol of the exchange rate (domestic per unit of foreign currency) and
+/// its correlation with the underlying's log returns.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Quanto<T = f64> {
+    pub foreign_rate: T,
+    pub fx_vol: T,
+    pub correlation: T,
+}
+
+impl<T: Float> Quanto<T> {
+    pub fn new(foreign_rate: Rate<T>, fx_vol: Vol<T>, correlation: T) -> Self {
+        Quanto {
+            foreign_rate: foreign_rate.0,
+            fx_vol: fx_vol.0,
+            correlation,
+        }
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        validate::finite("foreign_rate", self.foreign_rate)?;
+        validate::check("fx_vol", self.fx_vol, Domain::NonNegative)?;
+        validate::check("correlation", self.correlation, Domain::Between(-1.0, 1.0))?;
+        Ok(())
+    }
+}
+
+/// One asset of a multi-asset market: its spot, flat vol and continuously
+/// compounded yield.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(bound(
+    serialize = "T: Float + Serialize",
+    deserialize = "T: Float + Deserialize<'de>"
+))]
+pub struct Underlying<T = f64> {
+    pub spot: T,
+    pub vol: T,
+    #[serde(default = "zero", skip_serializing_if = "is_zero")]
+    pub dividend_yield: T,
+}
+
+impl<T: Float> Underlying<T> {
+    pub fn new(spot: Spot<T>, vol: Vol<T>) -> Self {
+        Underlying {
+            spot: spot.0,
+            vol: vol.0,
+            dividend_yield: T::zero(),
+        }
+    }
+
+    pub fn with_dividend_yield(self, dividend_yield: Rate<T>) -> Self {
+        Underlying {
+            dividend_yield: dividend_yield.0,
+            ..self
+        }
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        validate::positive("spot", self.spot)?;
+        validate::positive("vol", self.vol)?;
+        validate::finite("dividend_yield", self.dividend_yield)?;
+        Ok(())
+    }
+}
+
+/// Two correlated lognormal assets under one continuously compounded rate,
+/// for exchange and spread options.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(bound(
+    serialize = "T: Float + Serialize",
+    deserialize = "T: Float + Deserialize<'de>"
+))]
+pub struct AssetPair<T = f64> {
+    pub first: Underlying<T>,
+    pub second: Underlying<T>,
+    /// Correlation of the two assets' log returns.
+    pub correlation: T,
+    pub rate: T,
+}
+
+impl<T: Float> AssetPair<T> {
+    pub fn new(first: Underlying<T>, second: Underlying<T>, correlation: T, rate: Rate<T>) -> Self {
+        AssetPair {
+            first,
+            second,
+            correlation,
+            rate: rate.0,
+        }
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        self.first.validate()?;
+        self.second.validate()?;
+        validate::check("correlation", self.correlation, Domain::Between(-1.0, 1.0))?;
+        validate::finite("rate", self.rate)?;
+        Ok(())
+    }
+
+    /// Vol of the ratio of the two assets, `sqrt(v1^2 + v2^2 - 2 rho v1 v2)`.
+    pub fn ratio_vol(&self) -> T {
+        let (v1, v2) = (self.first.vol, self.second.vol);
+        let two = T::one() + T::one();
+        (v1 * v1 + v2 * v2 - two * self.correlation * v1 * v2)
+            .max(T::zero())
+            .sqrt()
+    }
+}
+
+fn zero<T: Float>() -> T {
+    T::zero()
+}
+
+fn is_zero<T: Float>(value: &T) -> bool {
+    value.is_zero()
+}
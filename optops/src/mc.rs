@@ -0,0 +1,34 @@
+//! Monte Carlo simulation: seeded random and quasi-random streams, path
+//! generation and the pricers built on them.
+
+mod asian;
+mod bridge;
+mod control;
+mod convergence;
+mod european;
+mod exotic;
+mod greeks;
+mod heston;
+mod importance;
+mod jumps;
+mod lsm;
+mod multi;
+mod paths;
+mod rng;
+mod sobol;
+
+pub use asian::AsianMonteCarlo;
+pub use bridge::BrownianBridge;
+pub use control::ControlVariate;
+pub use convergence::{Convergence, ConvergenceBatch, ConvergenceReport};
+pub use european::{MonteCarloEngine, MonteCarloEstimate};
+pub use exotic::{price_exotic, price_exotic_controlled, AtExpiry, PathPayoff};
+pub use greeks::{greeks, GreekMethod, MonteCarloGreeks};
+pub use heston::{Heston, HestonScheme};
+pub use importance::{price_importance, DriftShift};
+pub use jumps::{JumpDiffusion, JumpSizes};
+pub use lsm::{compare_boundaries, Basis, ExerciseRegion, LongstaffSchwartz, LsmExercise};
+pub use multi::{Combination, MultiAssetOption, MultiGbm};
+pub use paths::{Gbm, PathGenerator, PathModel, Paths, Sequence};
+pub use rng::{Philox, RandomSource, Rng};
+pub use sobol::{Sobol, MAX_DIMENSIONS};
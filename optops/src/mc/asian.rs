@@ -0,0 +1,143 @@
+//! Monte Carlo prices of Asian options, controlled by the geometric
+//! average's closed form.
+
+use super::{price_exotic, price_exotic_controlled};
+use super::{ControlVariate, Gbm, MonteCarloEstimate, PathGenerator, Sequence};
+use crate::analytic::geometric_asian;
+use crate::error::{Domain, Result};
+use crate::float::Float;
+use crate::instrument::{AsianSpec, Average, Sampling};
+use crate::market::Market;
+use crate::validate;
+use serde::{Deserialize, Serialize};
+
+/// Monte Carlo engine for Asian options under the risk-neutral GBM of
+/// `Gbm::risk_neutral`, with one time step per fixing. Arithmetic averages
+/// have no closed form; with `control_variate` set their price is
+/// corrected by the simulated error in the geometric average on the same
+/// paths, which `geometric_asian` prices exactly and which moves almost
+/// one for one with the arithmetic average, cutting the standard error by
+/// an order of magnitude or more.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AsianMonteCarlo {
+    pub num_paths: usize,
+    /// Fixings that stand in for continuous sampling; discrete sampling
+    /// simulates its own.
+    pub num_steps: usize,
+    pub seed: u64,
+    /// Simulate antithetic pairs, as `PathGenerator::antithetic` does.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub antithetic: bool,
+    /// Pseudo-random or Sobol draws, as `PathGenerator::sequence` takes.
+    #[serde(default)]
+    pub sequence: Sequence,
+    /// Order the draws by `BrownianBridge`, as
+    /// `PathGenerator::brownian_bridge` does.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub brownian_bridge: bool,
+    pub control_variate: bool,
+}
+
+impl Default for AsianMonteCarlo {
+    fn default() -> Self {
+        AsianMonteCarlo {
+            num_paths: 100_000,
+            num_steps: 252,
+            seed: 0,
+            antithetic: false,
+            sequence: Sequence::Pseudorandom,
+            brownian_bridge: false,
+            control_variate: true,
+        }
+    }
+}
+
+impl AsianMonteCarlo {
+    pub fn with_num_paths(self, num_paths: usize) -> Self {
+        AsianMonteCarlo { num_paths, ..self }
+    }
+
+    pub fn with_num_steps(self, num_steps: usize) -> Self {
+        AsianMonteCarlo { num_steps, ..self }
+    }
+
+    pub fn with_seed(self, seed: u64) -> Self {
+        AsianMonteCarlo { seed, ..self }
+    }
+
+    pub fn with_antithetic(self, antithetic: bool) -> Self {
+        AsianMonteCarlo { antithetic, ..self }
+    }
+
+    pub fn with_sequence(self, sequence: Sequence) -> Self {
+        AsianMonteCarlo { sequence, ..self }
+    }
+
+    pub fn with_brownian_bridge(self, brownian_bridge: bool) -> Self {
+        AsianMonteCarlo {
+            brownian_bridge,
+            ..self
+        }
+    }
+
+    pub fn with_control_variate(self, control_variate: bool) -> Self {
+        AsianMonteCarlo {
+            control_variate,
+            ..self
+        }
+    }
+
+    /// Price and standard error of `spec`. The control needs the closed
+    /// form, so controlled prices reject discrete dividends as
+    /// `geometric_asian` does.
+    pub fn estimate<T: Float>(
+        &self,
+        spec: &AsianSpec<T>,
+        market: &Market<T>,
+    ) -> Result<MonteCarloEstimate<T>> {
+        Ok(self.estimate_controlled(spec, market)?.estimate)
+    }
+
+    /// `estimate` with the control's weight and the uncontrolled standard
+    /// error alongside; without `control_variate` the weight is zero.
+    pub fn estimate_controlled<T: Float>(
+        &self,
+        spec: &AsianSpec<T>,
+        market: &Market<T>,
+    ) -> Result<ControlVariate<T>> {
+        let strike = validate::positive("strike", spec.strike)?;
+        let expiry = validate::positive("expiry", spec.expiry)?;
+        let fixings = match spec.sampling {
+            Sampling::Continuous => self.num_steps,
+            Sampling::Discrete(fixings) => fixings,
+        };
+        validate::check("fixings", fixings as f64, Domain::AtLeast(1.0))?;
+        let model = Gbm::risk_neutral(market, strike, expiry)?;
+        let paths = PathGenerator {
+            num_paths: self.num_paths,
+            num_steps: fixings,
+            seed: self.seed,
+            antithetic: self.antithetic,
+            sequence: self.sequence,
+            brownian_bridge: self.brownian_bridge,
+        }
+        .generate(&model, expiry)?;
+
+        let rate = market.continuous_rate(expiry)?;
+        if !self.control_variate {
+            let estimate = price_exotic(&paths, spec, rate)?;
+            return Ok(ControlVariate {
+                estimate,
+                beta: T::zero(),
+                uncontrolled_error: estimate.standard_error,
+            });
+        }
+        let geometric = AsianSpec {
+            average: Average::Geometric,
+            sampling: Sampling::Discrete(fixings),
+            ..*spec
+        };
+        let control_price = geometric_asian(&geometric, market)?;
+        price_exotic_controlled(&paths, spec, rate, &geometric, control_price)
+    }
+}
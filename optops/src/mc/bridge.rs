@@ -0,0 +1,96 @@
+//! Brownian-bridge construction of paths from their most important draws
+//! outward.
+
+use crate::float::{cast, Float};
+
+/// Builds a Brownian motion on a uniform grid of `n` steps from normals
+/// taken in order of importance: the first fixes the terminal value, the
+/// second the midpoint given both ends, and each later one the midpoint of
+/// the widest interval still open, conditioned on its ends (Caflisch,
+/// Morokoff and Owen, 1997). The paths have exactly the distribution of
+/// consecutive increments, but with quasi-random points the leading
+/// coordinates, the best distributed, now carry most of the variance of
+/// the path, which lowers the effective dimension of path-dependent
+/// payoffs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BrownianBridge {
+    /// In construction order: the step filled, the fixed steps either side
+    /// of it (`None` for time zero), their weights and the conditional
+    /// standard deviation, all in units of one step.
+    plan: Vec<Bridge>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Bridge {
+    point: usize,
+    left: Option<usize>,
+    right: usize,
+    left_weight: f64,
+    right_weight: f64,
+    std_dev: f64,
+}
+
+impl BrownianBridge {
+    /// The construction over `num_steps` equal steps, at least one.
+    pub fn new(num_steps: usize) -> Self {
+        let n = num_steps.max(1);
+        // `filled[i]`: whether the motion at the end of step `i` is built.
+        let mut filled = vec![false; n];
+        filled[n - 1] = true;
+        let mut plan = Vec::with_capacity(n);
+        plan.push(Bridge {
+            point: n - 1,
+            left: None,
+            right: n - 1,
+            left_weight: 0.0,
+            right_weight: 0.0,
+            std_dev: (n as f64).sqrt(),
+        });
+        // Sweeps across the grid, halving every open interval in turn.
+        let mut j = 0;
+        for _ in 1..n {
+            while filled[j] {
+                j += 1;
+            }
+            let mut k = j;
+            while !filled[k] {
+                k += 1;
+            }
+            // Points `j..k` are open, `j - 1` (or time zero) and `k` fixed.
+            let point = j + (k - 1 - j) / 2;
+            filled[point] = true;
+            let start = j as f64;
+            let (t, end) = (point as f64 + 1.0, k as f64 + 1.0);
+            plan.push(Bridge {
+                point,
+                left: j.checked_sub(1),
+                right: k,
+                left_weight: (end - t) / (end - start),
+                right_weight: (t - start) / (end - start),
+                std_dev: ((t - start) * (end - t) / (end - start)).sqrt(),
+            });
+            j = if k + 1 >= n { 0 } else { k + 1 };
+        }
+        BrownianBridge { plan }
+    }
+
+    pub fn num_steps(&self) -> usize {
+        self.plan.len()
+    }
+
+    /// Standard normal increments of each step, in time order, into
+    /// `increments` from the normals `draws` in order of importance. Both
+    /// must hold `num_steps` values.
+    pub fn transform<T: Float>(&self, draws: &[T], increments: &mut [T]) {
+        for (bridge, &z) in self.plan.iter().zip(draws) {
+            let left = bridge.left.map_or(T::zero(), |left| increments[left]);
+            increments[bridge.point] = left * cast(bridge.left_weight)
+                + increments[bridge.right] * cast(bridge.right_weight)
+                + z * cast(bridge.std_dev);
+        }
+        // From the motion at each step's end to the step's increment.
+        for i in (1..increments.len()).rev() {
+            increments[i] = increments[i] - increments[i - 1];
+        }
+    }
+}
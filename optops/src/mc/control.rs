@@ -0,0 +1,74 @@
+//! Control variates: simulated prices corrected by the error the same
+//! paths make on a related contract whose price is known exactly.
+
+use super::MonteCarloEstimate;
+use crate::error::{Domain, Result};
+use crate::float::{cast, Float};
+use crate::validate;
+use serde::{Deserialize, Serialize};
+
+/// An estimate adjusted by a control variate, with the weight it was given.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ControlVariate<T = f64> {
+    pub estimate: MonteCarloEstimate<T>,
+    /// Regression slope of the samples on the controls, the weight of
+    /// least variance.
+    pub beta: T,
+    /// Standard error the samples had on their own, for comparison.
+    pub uncontrolled_error: T,
+}
+
+impl<T: Float> ControlVariate<T> {
+    /// `samples` of a discounted payoff corrected by `controls`, the
+    /// discounted payoff of the related contract on the same paths, whose
+    /// exact price is `control_price`: each sample becomes `Y - beta (C -
+    /// control_price)`, with `beta = cov(Y, C) / var(C)` estimated from the
+    /// sample itself. The bias this leaves is of order `1 / n`, far below
+    /// the standard error. Each `batch` of consecutive paths, as
+    /// `Paths::batch` groups them, is averaged first.
+    pub fn apply(samples: &[T], controls: &[T], control_price: T, batch: usize) -> Result<Self> {
+        validate::check("samples", samples.len() as f64, Domain::AtLeast(2.0))?;
+        validate::check(
+            "controls",
+            controls.len() as f64,
+            Domain::Between(samples.len() as f64, samples.len() as f64),
+        )?;
+        let control_price = validate::finite("control_price", control_price)?;
+        let batch = batch.max(1);
+        let independent = |values: &[T]| -> Vec<T> { values.chunks(batch).map(mean).collect() };
+        let (ys, cs) = (independent(samples), independent(controls));
+        let (mean_y, mean_c) = (mean(&ys), mean(&cs));
+        let (covariance, variance) = ys.iter().zip(&cs).fold(
+            (T::zero(), T::zero()),
+            |(covariance, variance), (&y, &c)| {
+                let dc = c - mean_c;
+                (covariance + (y - mean_y) * dc, variance + dc * dc)
+            },
+        );
+        // A constant control, such as a payoff out of the money on every
+        // path, carries no information.
+        let beta = if variance > T::zero() {
+            covariance / variance
+        } else {
+            T::zero()
+        };
+        let adjusted: Vec<T> = ys
+            .iter()
+            .zip(&cs)
+            .map(|(&y, &c)| y - beta * (c - control_price))
+            .collect();
+        let estimate = MonteCarloEstimate {
+            num_paths: samples.len(),
+            ..MonteCarloEstimate::from_samples(&adjusted, 1)
+        };
+        Ok(ControlVariate {
+            estimate,
+            beta,
+            uncontrolled_error: MonteCarloEstimate::from_samples(samples, batch).standard_error,
+        })
+    }
+}
+
+fn mean<T: Float>(values: &[T]) -> T {
+    values.iter().fold(T::zero(), |sum, &x| sum + x) / cast(values.len() as f64)
+}
@@ -0,0 +1,149 @@
+//! Monte Carlo runs grown batch by batch until their error is small
+//! enough.
+
+use super::{MonteCarloEstimate, PathGenerator, PathModel, PathPayoff};
+use crate::error::{Domain, Result};
+use crate::float::{cast, Float};
+use crate::validate;
+use serde::{Deserialize, Serialize};
+
+/// When to stop adding batches of paths.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(bound(
+    serialize = "T: Float + Serialize",
+    deserialize = "T: Float + Deserialize<'de>"
+))]
+pub struct Convergence<T = f64> {
+    /// Paths simulated per batch, rounded up to an even count for
+    /// antithetic pairs.
+    pub batch_paths: usize,
+    /// Paths after which to stop whether or not the tolerance is met.
+    pub max_paths: usize,
+    /// Half-width of the confidence interval at which to stop early, after
+    /// at least two batches; `None` runs to `max_paths`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tolerance: Option<T>,
+    /// Probability the reported intervals hold the price with.
+    pub level: T,
+}
+
+impl<T: Float> Default for Convergence<T> {
+    fn default() -> Self {
+        Convergence {
+            batch_paths: 10_000,
+            max_paths: 1_000_000,
+            tolerance: None,
+            level: cast(0.95),
+        }
+    }
+}
+
+impl<T: Float> Convergence<T> {
+    pub fn with_batch_paths(self, batch_paths: usize) -> Self {
+        Convergence {
+            batch_paths,
+            ..self
+        }
+    }
+
+    pub fn with_max_paths(self, max_paths: usize) -> Self {
+        Convergence { max_paths, ..self }
+    }
+
+    pub fn with_tolerance(self, tolerance: T) -> Self {
+        Convergence {
+            tolerance: Some(tolerance),
+            ..self
+        }
+    }
+
+    pub fn with_level(self, level: T) -> Self {
+        Convergence { level, ..self }
+    }
+
+    /// `payoff` priced over paths of `model` out to `expiry`, discounting
+    /// at the flat continuous `rate`, one batch of `generator`'s paths
+    /// after another by `PathGenerator::generate_block`, so the paths are
+    /// those of one run of the same size however early it stops. The
+    /// standard error is taken over the means of the generator's own
+    /// batches, antithetic pairs or Sobol shifts, pooled across the
+    /// batches of the run.
+    pub fn run<M, P>(
+        &self,
+        generator: &PathGenerator,
+        model: &M,
+        expiry: T,
+        payoff: &P,
+        rate: T,
+    ) -> Result<ConvergenceReport<T>>
+    where
+        M: PathModel<T>,
+        P: PathPayoff<T>,
+    {
+        validate::check("batch_paths", self.batch_paths as f64, Domain::AtLeast(1.0))?;
+        validate::check("max_paths", self.max_paths as f64, Domain::AtLeast(1.0))?;
+        if let Some(tolerance) = self.tolerance {
+            validate::positive("tolerance", tolerance)?;
+        }
+        let rate = validate::finite("rate", rate)?;
+        let batch_paths = self.batch_paths + self.batch_paths % 2;
+        let generator = generator.with_num_paths(batch_paths);
+        let mut means = Vec::new();
+        let mut batches = Vec::new();
+        let mut num_paths = 0;
+        let converged = loop {
+            let paths = generator.generate_block(model, expiry, batches.len())?;
+            let times = paths.times();
+            let samples = paths.evaluate(|path| payoff.present_value(times, path, rate));
+            let mean = |values: &[T]| {
+                values.iter().fold(T::zero(), |sum, &x| sum + x) / cast(values.len() as f64)
+            };
+            means.extend(samples.chunks(paths.batch()).map(mean));
+            num_paths += paths.num_paths();
+            let estimate = MonteCarloEstimate {
+                num_paths,
+                ..MonteCarloEstimate::from_samples(&means, 1)
+            };
+            let confidence_interval = estimate.confidence_interval(self.level)?;
+            batches.push(ConvergenceBatch {
+                estimate,
+                confidence_interval,
+            });
+            let half_width = (confidence_interval.1 - confidence_interval.0) / cast(2.0);
+            if batches.len() >= 2
+                && self
+                    .tolerance
+                    .is_some_and(|tolerance| half_width <= tolerance)
+            {
+                break true;
+            }
+            if num_paths >= self.max_paths {
+                break false;
+            }
+        };
+        Ok(ConvergenceReport { batches, converged })
+    }
+}
+
+/// The running estimate once one more batch is in.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ConvergenceBatch<T = f64> {
+    /// Over every path so far.
+    pub estimate: MonteCarloEstimate<T>,
+    pub confidence_interval: (T, T),
+}
+
+/// The running estimate after each batch of a `Convergence::run`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConvergenceReport<T = f64> {
+    pub batches: Vec<ConvergenceBatch<T>>,
+    /// Whether the run stopped at its tolerance rather than its path limit.
+    pub converged: bool,
+}
+
+impl<T: Float> ConvergenceReport<T> {
+    /// The estimate over every path.
+    pub fn estimate(&self) -> MonteCarloEstimate<T> {
+        self.batches[self.batches.len() - 1].estimate
+    }
+}
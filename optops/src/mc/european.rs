@@ -0,0 +1,299 @@
+//! Monte Carlo prices of European vanillas, with their sampling error.
+
+use super::{
+    greeks, price_importance, Convergence, ConvergenceReport, DriftShift, Gbm, GreekMethod,
+    MonteCarloGreeks, PathGenerator, Paths, Sequence,
+};
+use crate::core::inverse_norm_cdf;
+use crate::engine::{PriceResult, PricingEngine, PricingResult};
+use crate::error::{Domain, OptOpsError, Result};
+use crate::float::{cast, Float};
+use crate::instrument::{ExerciseStyle, OptionSpec};
+use crate::market::Market;
+use crate::payoff::Payoff;
+use crate::validate;
+use serde::{Deserialize, Serialize};
+use std::time::Instant;
+
+/// A price averaged over simulated paths.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct MonteCarloEstimate<T = f64> {
+    pub price: T,
+    /// Standard deviation of the discounted payoffs over the root of the
+    /// number of paths.
+    pub standard_error: T,
+    pub num_paths: usize,
+}
+
+impl<T: Float> MonteCarloEstimate<T> {
+    /// Mean and standard error of `samples`, discounted payoffs one per
+    /// path, averaged over each `batch` of consecutive paths, as
+    /// `Paths::batch` groups them, before their spread is taken.
+    pub(crate) fn from_samples(samples: &[T], batch: usize) -> Self {
+        let mean = |values: &[T]| {
+            values.iter().fold(T::zero(), |sum, &x| sum + x) / cast(values.len() as f64)
+        };
+        let price = mean(samples);
+        let independent: Vec<T> = samples.chunks(batch).map(mean).collect();
+        let n = cast::<T>(independent.len() as f64);
+        let squares = independent
+            .iter()
+            .fold(T::zero(), |sum, &x| sum + (x - price) * (x - price));
+        let variance = if independent.len() > 1 {
+            squares / (n - T::one())
+        } else {
+            T::zero()
+        };
+        MonteCarloEstimate {
+            price,
+            standard_error: (variance / n).sqrt(),
+            num_paths: samples.len(),
+        }
+    }
+
+    /// Normal-approximation interval holding the true price with
+    /// probability `level`, e.g. 0.95.
+    pub fn confidence_interval(&self, level: T) -> Result<(T, T)> {
+        let level = validate::check("level", level, Domain::Inside(0.0, 1.0))?;
+        let z = inverse_norm_cdf((T::one() + level) / cast(2.0));
+        let half_width = z * self.standard_error;
+        Ok((self.price - half_width, self.price + half_width))
+    }
+}
+
+/// Monte Carlo engine for European calls and puts under the risk-neutral
+/// GBM `Gbm::risk_neutral` builds, drawing each terminal spot in one exact
+/// step. Slower and noisier than Black-Scholes for these contracts, it is
+/// the benchmark the path-dependent simulations are checked against.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MonteCarloEngine {
+    pub num_paths: usize,
+    pub seed: u64,
+    /// Simulate antithetic pairs, as `PathGenerator::antithetic` does.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub antithetic: bool,
+    /// Pseudo-random or Sobol draws, as `PathGenerator::sequence` takes.
+    #[serde(default)]
+    pub sequence: Sequence,
+    /// Draw the paths with their median at expiry on the strike and weight
+    /// them back, as `DriftShift::Median` does, for options far out of the
+    /// money; used by `estimate`.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub importance_sampling: bool,
+}
+
+impl Default for MonteCarloEngine {
+    fn default() -> Self {
+        MonteCarloEngine {
+            num_paths: 100_000,
+            seed: 0,
+            antithetic: false,
+            sequence: Sequence::Pseudorandom,
+            importance_sampling: false,
+        }
+    }
+}
+
+impl MonteCarloEngine {
+    pub fn with_num_paths(self, num_paths: usize) -> Self {
+        MonteCarloEngine { num_paths, ..self }
+    }
+
+    pub fn with_seed(self, seed: u64) -> Self {
+        MonteCarloEngine { seed, ..self }
+    }
+
+    pub fn with_antithetic(self, antithetic: bool) -> Self {
+        MonteCarloEngine { antithetic, ..self }
+    }
+
+    pub fn with_sequence(self, sequence: Sequence) -> Self {
+        MonteCarloEngine { sequence, ..self }
+    }
+
+    pub fn with_importance_sampling(self, importance_sampling: bool) -> Self {
+        MonteCarloEngine {
+            importance_sampling,
+            ..self
+        }
+    }
+
+    /// Price and standard error of receiving `payoff` on the spot at the
+    /// end of `paths`, simulated under any risk-neutral model, such as
+    /// `Heston`, discounting at the flat continuous `rate`.
+    pub fn price_paths<T: Float, P: Payoff<T>>(
+        paths: &Paths<T>,
+        payoff: &P,
+        rate: T,
+    ) -> Result<MonteCarloEstimate<T>> {
+        let rate = validate::finite("rate", rate)?;
+        let expiry = paths.times()[paths.num_steps()];
+        let discount = (-rate * expiry).exp();
+        let samples = paths.evaluate(|path| discount * payoff.value(expiry, path[path.len() - 1]));
+        Ok(MonteCarloEstimate::from_samples(&samples, paths.batch()))
+    }
+
+    /// Price and standard error of `spec`, which must be a European
+    /// vanilla without a barrier.
+    pub fn estimate<T: Float>(
+        &self,
+        spec: &OptionSpec<T>,
+        market: &Market<T>,
+    ) -> Result<MonteCarloEstimate<T>> {
+        if self.importance_sampling {
+            let model = self.model(spec, market)?;
+            let rate = market.continuous_rate(spec.expiry)?;
+            let shift = DriftShift::Median(spec.strike);
+            return price_importance(&self.generator(), &model, spec.expiry, spec, rate, shift);
+        }
+        let (_, paths) = self.simulate(spec, market)?;
+        let rate = market.continuous_rate(spec.expiry)?;
+        let discount = (-rate * spec.expiry).exp();
+        let samples = paths.evaluate(|path| {
+            discount
+                * spec
+                    .option_type
+                    .intrinsic(path[path.len() - 1], spec.strike)
+        });
+        Ok(MonteCarloEstimate::from_samples(&samples, paths.batch()))
+    }
+
+    /// `estimate` together with delta and vega from the same paths, taken
+    /// pathwise as `greeks` takes them.
+    pub fn greeks<T: Float>(
+        &self,
+        spec: &OptionSpec<T>,
+        market: &Market<T>,
+    ) -> Result<MonteCarloGreeks<T>> {
+        let (model, paths) = self.simulate(spec, market)?;
+        let rate = market.continuous_rate(spec.expiry)?;
+        greeks(&paths, &model, spec, rate, GreekMethod::Pathwise)
+    }
+
+    /// `estimate` grown batch by batch as `convergence` directs, in place
+    /// of a fixed `num_paths`.
+    pub fn converge<T: Float>(
+        &self,
+        spec: &OptionSpec<T>,
+        market: &Market<T>,
+        convergence: &Convergence<T>,
+    ) -> Result<ConvergenceReport<T>> {
+        let model = self.model(spec, market)?;
+        let rate = market.continuous_rate(spec.expiry)?;
+        convergence.run(&self.generator(), &model, spec.expiry, spec, rate)
+    }
+
+    /// The risk-neutral model of `spec`, which must be a European vanilla
+    /// without a barrier, and its terminal spots.
+    fn simulate<T: Float>(
+        &self,
+        spec: &OptionSpec<T>,
+        market: &Market<T>,
+    ) -> Result<(Gbm<T>, Paths<T>)> {
+        let model = self.model(spec, market)?;
+        let paths = self.generator().generate(&model, spec.expiry)?;
+        Ok((model, paths))
+    }
+
+    fn model<T: Float>(&self, spec: &OptionSpec<T>, market: &Market<T>) -> Result<Gbm<T>> {
+        if spec.exercise != ExerciseStyle::European {
+            return Err(OptOpsError::Unsupported {
+                engine: "Monte Carlo",
+                reason: "early exercise",
+            });
+        }
+        if spec.barrier.is_some() {
+            return Err(OptOpsError::Unsupported {
+                engine: "Monte Carlo",
+                reason: "barrier options",
+            });
+        }
+        let strike = validate::positive("strike", spec.strike)?;
+        let expiry = validate::positive("expiry", spec.expiry)?;
+        Gbm::risk_neutral(market, strike, expiry)
+    }
+
+    /// One exact step to expiry per path.
+    fn generator(&self) -> PathGenerator {
+        PathGenerator {
+            num_paths: self.num_paths,
+            num_steps: 1,
+            seed: self.seed,
+            antithetic: self.antithetic,
+            sequence: self.sequence,
+            brownian_bridge: false,
+        }
+    }
+}
+
+impl PricingEngine for MonteCarloEngine {
+    /// `estimate` as a result whose `error_estimate` is the standard error
+    /// and `confidence_interval` its 95% interval.
+    fn price(&self, instrument: &OptionSpec, market: &Market) -> PriceResult {
+        let start = Instant::now();
+        let estimate = self.estimate(instrument, market)?;
+        let mut result = PricingResult::new("Monte Carlo", estimate.price, start.elapsed());
+        result.early_exercise_premium = Some(0.0);
+        result.error_estimate = Some(estimate.standard_error);
+        result.confidence_interval = Some(estimate.confidence_interval(0.95)?);
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analytic::black_scholes_price;
+    use crate::instrument::OptionType;
+    use crate::units::{Rate, Spot, Strike, Vol};
+
+    #[test]
+    fn estimates_lie_within_a_few_standard_errors_of_black_scholes() {
+        let market: Market =
+            Market::new(Spot(100.0), Rate(0.05), Vol(0.25)).with_dividend_yield(Rate(0.01));
+        let engines = [
+            MonteCarloEngine::default().with_num_paths(50_000),
+            MonteCarloEngine::default()
+                .with_num_paths(50_000)
+                .with_antithetic(true),
+            MonteCarloEngine::default()
+                .with_num_paths(50_000)
+                .with_seed(11)
+                .with_importance_sampling(true),
+        ];
+        for engine in engines {
+            for option_type in [OptionType::Call, OptionType::Put] {
+                for strike in [80.0, 100.0, 125.0] {
+                    let spec = OptionSpec::european(option_type, Strike(strike), 1.0);
+                    let exact = black_scholes_price(&spec, &market).unwrap();
+                    let estimate = engine.estimate(&spec, &market).unwrap();
+                    let gap = (estimate.price - exact).abs();
+                    assert!(
+                        gap < 4.0 * estimate.standard_error,
+                        "{engine:?} {option_type:?} {strike}: {estimate:?} vs {exact}"
+                    );
+                }
+            }
+        }
+    }
+
+    /// Intervals are open at both ends of the level.
+    #[test]
+    fn confidence_levels_lie_strictly_between_zero_and_one() {
+        let estimate: MonteCarloEstimate = MonteCarloEstimate {
+            price: 10.0,
+            standard_error: 0.1,
+            num_paths: 1000,
+        };
+        let (low, high) = estimate.confidence_interval(0.95).unwrap();
+        assert!((high - low - 2.0 * 1.959_964 * 0.1).abs() < 1e-6);
+        for level in [0.0, 1.0, f64::NAN] {
+            match estimate.confidence_interval(level) {
+                Err(OptOpsError::InvalidParameter { name, domain, .. }) => {
+                    assert_eq!((name, domain), ("level", Domain::Inside(0.0, 1.0)))
+                }
+                other => panic!("{other:?}"),
+            }
+        }
+    }
+}
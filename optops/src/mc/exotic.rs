@@ -0,0 +1,142 @@
+//! Path-dependent payoffs evaluated over simulated paths.
+
+use super::{ControlVariate, MonteCarloEstimate, Paths};
+use crate::error::{Domain, Result};
+use crate::float::{cast, Float};
+use crate::instrument::{
+    AsianSpec, Average, LookbackSpec, LookbackStrike, OptionSpec, OptionType, Sampling,
+};
+use crate::payoff::Payoff;
+use crate::validate;
+
+/// A payoff read off a whole path rather than the spot at exercise. Paths
+/// are monitored only at their grid times, so barriers and extremes are
+/// discrete and fixings fall on the nearest grid time: simulate a multiple
+/// of the fixings to sample them exactly.
+pub trait PathPayoff<T: Float>: Sync {
+    /// What the payoff on `path`, sampled at `times`, is worth at time
+    /// zero, discounting at the flat continuous `rate`.
+    fn present_value(&self, times: &[T], path: &[T], rate: T) -> T;
+}
+
+/// Settles on the average of the spot at its fixings, or at every grid time
+/// after zero for continuous sampling.
+impl<T: Float> PathPayoff<T> for AsianSpec<T> {
+    fn present_value(&self, times: &[T], path: &[T], rate: T) -> T {
+        let steps = path.len() - 1;
+        let fixings = match self.sampling {
+            Sampling::Continuous => steps,
+            Sampling::Discrete(fixings) => fixings.max(1),
+        };
+        let fixing = |i: usize| path[((i * steps) as f64 / fixings as f64).round() as usize];
+        let n = cast::<T>(fixings as f64);
+        let average = match self.average {
+            Average::Arithmetic => (1..=fixings).fold(T::zero(), |sum, i| sum + fixing(i)) / n,
+            Average::Geometric => {
+                ((1..=fixings).fold(T::zero(), |sum, i| sum + fixing(i).ln()) / n).exp()
+            }
+        };
+        let expiry = times[steps];
+        (-rate * expiry).exp() * self.option_type.intrinsic(average, self.strike)
+    }
+}
+
+/// Settles on the path's extreme over its grid times, together with any
+/// extreme already observed.
+impl<T: Float> PathPayoff<T> for LookbackSpec<T> {
+    fn present_value(&self, times: &[T], path: &[T], rate: T) -> T {
+        let steps = path.len() - 1;
+        let (high, low) = path.iter().fold((path[0], path[0]), |(high, low), &s| {
+            (high.max(s), low.min(s))
+        });
+        let (high, low) = match self.extreme {
+            Some(extreme) => (high.max(extreme), low.min(extreme)),
+            None => (high, low),
+        };
+        let last = path[steps];
+        let value = match (self.strike, self.option_type) {
+            (LookbackStrike::Floating, OptionType::Call) => last - low,
+            (LookbackStrike::Floating, OptionType::Put) => high - last,
+            (LookbackStrike::Fixed(strike), OptionType::Call) => (high - strike).max(T::zero()),
+            (LookbackStrike::Fixed(strike), OptionType::Put) => (strike - low).max(T::zero()),
+        };
+        (-rate * times[steps]).exp() * value
+    }
+}
+
+/// The vanilla exercised at expiry, whatever the spec's exercise style,
+/// with its barrier monitored at every grid time from zero. A knock-out
+/// pays its rebate at the first grid time the barrier is hit; a knock-in
+/// never hit pays it at expiry.
+impl<T: Float> PathPayoff<T> for OptionSpec<T> {
+    fn present_value(&self, times: &[T], path: &[T], rate: T) -> T {
+        let steps = path.len() - 1;
+        let settle = |t: T, value: T| (-rate * t).exp() * value;
+        let vanilla = self.option_type.intrinsic(path[steps], self.strike);
+        let Some(barrier) = self.barrier else {
+            return settle(times[steps], vanilla);
+        };
+        let hit = path.iter().position(|&s| barrier.is_hit(s));
+        match (hit, barrier.kind.is_knock_out()) {
+            (Some(k), true) => settle(times[k], barrier.rebate),
+            (None, true) | (Some(_), false) => settle(times[steps], vanilla),
+            (None, false) => settle(times[steps], barrier.rebate),
+        }
+    }
+}
+
+/// Any lattice payoff received on the spot at the end of the path, such as
+/// a `DigitalCall`, as a path payoff.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AtExpiry<P>(pub P);
+
+impl<T: Float, P: Payoff<T>> PathPayoff<T> for AtExpiry<P> {
+    fn present_value(&self, times: &[T], path: &[T], rate: T) -> T {
+        let expiry = times[times.len() - 1];
+        (-rate * expiry).exp() * self.0.value(expiry, path[path.len() - 1])
+    }
+}
+
+/// Price and standard error of `payoff` over `paths`, simulated under any
+/// risk-neutral model, discounting at the flat continuous `rate`.
+pub fn price_exotic<T: Float, P: PathPayoff<T>>(
+    paths: &Paths<T>,
+    payoff: &P,
+    rate: T,
+) -> Result<MonteCarloEstimate<T>> {
+    let rate = validate::finite("rate", rate)?;
+    let times = paths.times();
+    let samples = paths.evaluate(|path| payoff.present_value(times, path, rate));
+    Ok(MonteCarloEstimate::from_samples(&samples, paths.batch()))
+}
+
+/// `price_exotic` corrected by `control`, a related payoff whose exact
+/// price on the same grid is `control_price`, by `ControlVariate::apply`:
+/// the geometric Asian for the arithmetic one, with the fixings simulated
+/// exactly, or the vanilla for a barrier.
+pub fn price_exotic_controlled<T, P, C>(
+    paths: &Paths<T>,
+    payoff: &P,
+    rate: T,
+    control: &C,
+    control_price: T,
+) -> Result<ControlVariate<T>>
+where
+    T: Float,
+    P: PathPayoff<T>,
+    C: PathPayoff<T>,
+{
+    let rate = validate::finite("rate", rate)?;
+    validate::check("paths", paths.num_paths() as f64, Domain::AtLeast(2.0))?;
+    let times = paths.times();
+    let (samples, controls): (Vec<T>, Vec<T>) = paths
+        .evaluate(|path| {
+            (
+                payoff.present_value(times, path, rate),
+                control.present_value(times, path, rate),
+            )
+        })
+        .into_iter()
+        .unzip();
+    ControlVariate::apply(&samples, &controls, control_price, paths.batch())
+}
@@ -0,0 +1,118 @@
+//! Delta and vega estimated within one simulation, without bumping and
+//! resimulating.
+
+use super::{Gbm, MonteCarloEstimate, PathPayoff, Paths};
+use crate::error::{OptOpsError, Result};
+use crate::float::{cast, Float};
+use crate::validate;
+use serde::{Deserialize, Serialize};
+
+/// How a Greek is read off the simulated paths.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GreekMethod {
+    /// Differentiate each path's payoff along the path's own derivative in
+    /// the parameter. Unbiased and of low variance for payoffs continuous
+    /// in the path, such as vanillas, Asians and lookbacks, but blind to
+    /// the jump of a digital or a barrier and so wrong for them.
+    #[default]
+    Pathwise,
+    /// Weight each path's payoff by the derivative of the log density of
+    /// its Brownian increments, leaving the payoff itself alone. Right for
+    /// any payoff, digitals and barriers included, at the cost of a larger
+    /// variance that grows as the steps shrink.
+    LikelihoodRatio,
+}
+
+/// Price, delta and vega from one set of paths, each with its standard
+/// error over the same batches.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct MonteCarloGreeks<T = f64> {
+    pub price: MonteCarloEstimate<T>,
+    /// Per unit of the model's spot.
+    pub delta: MonteCarloEstimate<T>,
+    /// Per unit of vol, not per point.
+    pub vega: MonteCarloEstimate<T>,
+}
+
+/// Price, delta and vega of `payoff` over `paths`, which must have been
+/// simulated from `model` with its vol positive, discounting at the flat
+/// continuous `rate`.
+///
+/// Under GBM the path is `S(t) = S(0) exp((mu - sigma^2 / 2) t + sigma
+/// W(t))`, so the Brownian path, and with it both estimators, can be
+/// recovered from the spots however they were drawn: pathwise, the
+/// tangents are `S(t) / S(0)` in spot and `S(t) (W(t) - sigma t)` in vol,
+/// along which the payoff is differenced; by likelihood ratio, the weights
+/// are `Z_1 / (S(0) sigma sqrt(dt_1))` in spot and the sum over steps of
+/// `(Z^2 - 1) / sigma - Z sqrt(dt)` in vol, with `Z` each step's normal.
+pub fn greeks<T: Float, P: PathPayoff<T>>(
+    paths: &Paths<T>,
+    model: &Gbm<T>,
+    payoff: &P,
+    rate: T,
+    method: GreekMethod,
+) -> Result<MonteCarloGreeks<T>> {
+    let rate = validate::finite("rate", rate)?;
+    let vol = validate::positive("vol", model.vol)?;
+    if paths.assets() != 1 {
+        return Err(OptOpsError::Unsupported {
+            engine: "Monte Carlo Greeks",
+            reason: "multi-asset paths",
+        });
+    }
+    let times = paths.times();
+    let (spot, half) = (model.spot, cast::<T>(0.5));
+    let log_drift = model.drift - half * vol * vol;
+    // The Brownian motion driving `path` at each of its times.
+    let brownian = |path: &[T]| -> Vec<T> {
+        path.iter()
+            .zip(times)
+            .map(|(&s, &t)| ((s / spot).ln() - log_drift * t) / vol)
+            .collect()
+    };
+    let samples = paths.evaluate(|path| {
+        let value = payoff.present_value(times, path, rate);
+        let w = brownian(path);
+        let (delta, vega) = match method {
+            GreekMethod::Pathwise => {
+                let step = T::epsilon().cbrt();
+                let along = |tangent: &dyn Fn(usize) -> T| {
+                    let shifted = |sign: T| -> Vec<T> {
+                        (0..path.len())
+                            .map(|k| path[k] + sign * step * tangent(k))
+                            .collect()
+                    };
+                    let (up, down) = (shifted(T::one()), shifted(-T::one()));
+                    let difference = payoff.present_value(times, &up, rate)
+                        - payoff.present_value(times, &down, rate);
+                    difference / (step + step)
+                };
+                (
+                    along(&|k| path[k] / spot),
+                    along(&|k| path[k] * (w[k] - vol * times[k])),
+                )
+            }
+            GreekMethod::LikelihoodRatio => {
+                let normal = |k: usize| (w[k] - w[k - 1]) / (times[k] - times[k - 1]).sqrt();
+                let score = (1..path.len()).fold(T::zero(), |sum, k| {
+                    let z = normal(k);
+                    sum + (z * z - T::one()) / vol - z * (times[k] - times[k - 1]).sqrt()
+                });
+                let first = normal(1) / (spot * vol * times[1].sqrt());
+                (value * first, value * score)
+            }
+        };
+        (value, delta, vega)
+    });
+    let batch = paths.batch();
+    let estimate = |pick: fn(&(T, T, T)) -> T| {
+        let values: Vec<T> = samples.iter().map(pick).collect();
+        MonteCarloEstimate::from_samples(&values, batch)
+    };
+    Ok(MonteCarloGreeks {
+        price: estimate(|s| s.0),
+        delta: estimate(|s| s.1),
+        vega: estimate(|s| s.2),
+    })
+}
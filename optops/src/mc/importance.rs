@@ -0,0 +1,68 @@
+//! Importance sampling by a shift of the Brownian drift, for prices driven
+//! by paths the plain simulation rarely draws.
+
+use super::{Gbm, MonteCarloEstimate, PathGenerator, PathPayoff};
+use crate::error::Result;
+use crate::float::{cast, Float};
+use crate::validate;
+use serde::{Deserialize, Serialize};
+
+/// How far to push the Brownian motion driving a `Gbm` simulation.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DriftShift<T = f64> {
+    /// Extra drift `theta` of the Brownian motion per year, so the spot
+    /// drifts at `mu + sigma theta`.
+    Brownian(T),
+    /// The shift that moves the median spot at expiry to this level: the
+    /// strike of a deep out-of-the-money option, or the barrier whose
+    /// crossing carries the price.
+    Median(T),
+}
+
+impl<T: Float> DriftShift<T> {
+    /// `theta` for `model` out to `expiry`.
+    pub fn theta(&self, model: &Gbm<T>, expiry: T) -> Result<T> {
+        match *self {
+            DriftShift::Brownian(theta) => validate::finite("theta", theta),
+            DriftShift::Median(level) => {
+                let level = validate::positive("level", level)?;
+                let vol = validate::positive("vol", model.vol)?;
+                let drift = model.drift - cast::<T>(0.5) * vol * vol;
+                Ok(((level / model.spot).ln() - drift * expiry) / (vol * expiry))
+            }
+        }
+    }
+}
+
+/// Price and standard error of `payoff` over `generator`'s paths of
+/// `model` out to `expiry`, discounting at the flat continuous `rate`,
+/// with the paths drawn under the Brownian drift `shift` and each payoff
+/// weighted back by the likelihood ratio `exp(-theta B(T) - theta^2 T / 2)`,
+/// `B` the motion simulated. The estimate stays unbiased for any shift;
+/// one that sends the paths where the payoff is earned can cut the
+/// variance of a deep out-of-the-money price by orders of magnitude, and
+/// one that sends them elsewhere raises it.
+pub fn price_importance<T: Float, P: PathPayoff<T>>(
+    generator: &PathGenerator,
+    model: &Gbm<T>,
+    expiry: T,
+    payoff: &P,
+    rate: T,
+    shift: DriftShift<T>,
+) -> Result<MonteCarloEstimate<T>> {
+    let rate = validate::finite("rate", rate)?;
+    let vol = validate::positive("vol", model.vol)?;
+    let theta = shift.theta(model, expiry)?;
+    let shifted = Gbm::new(model.spot, model.drift + vol * theta, vol)?;
+    let paths = generator.generate(&shifted, expiry)?;
+    let times = paths.times();
+    let half = cast::<T>(0.5);
+    let log_drift = shifted.drift - half * vol * vol;
+    let samples = paths.evaluate(|path| {
+        let brownian = ((path[path.len() - 1] / model.spot).ln() - log_drift * expiry) / vol;
+        let weight = (-theta * brownian - half * theta * theta * expiry).exp();
+        payoff.present_value(times, path, rate) * weight
+    });
+    Ok(MonteCarloEstimate::from_samples(&samples, paths.batch()))
+}
@@ -0,0 +1,473 @@
+//! Longstaff-Schwartz least-squares Monte Carlo for early exercise.
+
+use super::{Gbm, MonteCarloEstimate, PathGenerator, Paths, Sequence};
+use crate::engine::{PriceResult, PricingEngine, PricingResult};
+use crate::error::{Domain, OptOpsError, Result};
+use crate::float::{cast, Float};
+use crate::instrument::{ExerciseStyle, OptionSpec, OptionType};
+use crate::market::Market;
+use crate::optimize;
+use crate::payoff::Payoff;
+use crate::tree::ExerciseBoundary;
+use crate::validate;
+use serde::{Deserialize, Serialize};
+use std::time::Instant;
+
+/// Polynomials the continuation value is regressed on, in the spot over
+/// its value at time zero.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Basis {
+    /// `1, x, x^2, ...`.
+    Monomial,
+    /// Longstaff and Schwartz's weighted Laguerre polynomials `exp(-x / 2)
+    /// L_n(x)`, with a constant.
+    #[default]
+    Laguerre,
+    /// Probabilists' Hermite polynomials `He_n(x)`.
+    Hermite,
+}
+
+impl Basis {
+    /// The first `degree + 1` functions at `x`.
+    fn values<T: Float>(self, x: T, degree: usize, out: &mut Vec<T>) {
+        out.clear();
+        let one = T::one();
+        match self {
+            Basis::Monomial => {
+                let mut power = one;
+                for _ in 0..=degree {
+                    out.push(power);
+                    power = power * x;
+                }
+            }
+            Basis::Laguerre => {
+                // `(n + 1) L_{n+1} = (2n + 1 - x) L_n - n L_{n-1}`.
+                let weight = (-x / cast(2.0)).exp();
+                out.push(one);
+                let (mut previous, mut current) = (one, one - x);
+                for n in 1..=degree {
+                    out.push(weight * current);
+                    let k = cast::<T>(n as f64);
+                    let next = ((k + k + one - x) * current - k * previous) / (k + one);
+                    (previous, current) = (current, next);
+                }
+            }
+            Basis::Hermite => {
+                // `He_{n+1} = x He_n - n He_{n-1}`.
+                let (mut previous, mut current) = (T::zero(), one);
+                for n in 0..=degree {
+                    out.push(current);
+                    let next = x * current - cast::<T>(n as f64) * previous;
+                    (previous, current) = (current, next);
+                }
+            }
+        }
+    }
+}
+
+/// Longstaff and Schwartz's (2001) American Monte Carlo: exercise at any
+/// time step, and so Bermudan on the `num_steps` dates of the grid, by
+/// comparing the payoff with a continuation value regressed on `basis` up
+/// to `degree` across the in-the-money paths. Pricing on the paths the
+/// rule was fitted to leaves a small bias either way; more steps approach
+/// the American price.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LongstaffSchwartz {
+    pub num_paths: usize,
+    /// Exercise dates, equally spaced up to and including expiry.
+    pub num_steps: usize,
+    pub seed: u64,
+    pub basis: Basis,
+    pub degree: usize,
+    /// Simulate antithetic pairs, as `PathGenerator::antithetic` does.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub antithetic: bool,
+    /// Pseudo-random or Sobol draws, as `PathGenerator::sequence` takes.
+    #[serde(default)]
+    pub sequence: Sequence,
+    /// Order the draws by `BrownianBridge`, as
+    /// `PathGenerator::brownian_bridge` does.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub brownian_bridge: bool,
+}
+
+impl Default for LongstaffSchwartz {
+    fn default() -> Self {
+        LongstaffSchwartz {
+            num_paths: 50_000,
+            num_steps: 50,
+            seed: 0,
+            basis: Basis::default(),
+            degree: 3,
+            antithetic: false,
+            sequence: Sequence::Pseudorandom,
+            brownian_bridge: false,
+        }
+    }
+}
+
+impl LongstaffSchwartz {
+    pub fn with_num_paths(self, num_paths: usize) -> Self {
+        LongstaffSchwartz { num_paths, ..self }
+    }
+
+    pub fn with_num_steps(self, num_steps: usize) -> Self {
+        LongstaffSchwartz { num_steps, ..self }
+    }
+
+    pub fn with_seed(self, seed: u64) -> Self {
+        LongstaffSchwartz { seed, ..self }
+    }
+
+    pub fn with_basis(self, basis: Basis) -> Self {
+        LongstaffSchwartz { basis, ..self }
+    }
+
+    pub fn with_degree(self, degree: usize) -> Self {
+        LongstaffSchwartz { degree, ..self }
+    }
+
+    pub fn with_antithetic(self, antithetic: bool) -> Self {
+        LongstaffSchwartz { antithetic, ..self }
+    }
+
+    pub fn with_sequence(self, sequence: Sequence) -> Self {
+        LongstaffSchwartz { sequence, ..self }
+    }
+
+    pub fn with_brownian_bridge(self, brownian_bridge: bool) -> Self {
+        LongstaffSchwartz {
+            brownian_bridge,
+            ..self
+        }
+    }
+
+    /// Price of exercising `payoff` optimally at the times of `paths`,
+    /// simulated under any risk-neutral model, discounting at the flat
+    /// continuous `rate`. Exercise at time zero is allowed too.
+    pub fn price_paths<T: Float, P: Payoff<T>>(
+        &self,
+        paths: &Paths<T>,
+        payoff: &P,
+        rate: T,
+    ) -> Result<MonteCarloEstimate<T>> {
+        Ok(self.exercise_paths(paths, payoff, rate)?.estimate)
+    }
+
+    /// `price_paths` together with the exercise rule it fitted: where and
+    /// when each path is exercised.
+    pub fn exercise_paths<T: Float, P: Payoff<T>>(
+        &self,
+        paths: &Paths<T>,
+        payoff: &P,
+        rate: T,
+    ) -> Result<LsmExercise<T>> {
+        let rate = validate::finite("rate", rate)?;
+        let times = paths.times();
+        let num_steps = paths.num_steps();
+        let size = self.degree + 1;
+        let scale = paths.path(0)[0];
+        let mut cash = paths.evaluate(|path| payoff.value(times[num_steps], path[num_steps]));
+        let mut exercise_steps: Vec<Option<usize>> = cash
+            .iter()
+            .map(|&value| (value > T::zero()).then_some(num_steps))
+            .collect();
+        let expiring = paths
+            .iter()
+            .zip(&cash)
+            .filter(|(_, &value)| value > T::zero());
+        let expiring = expiring.map(|(path, _)| path[num_steps]);
+        let mut regions = Vec::from_iter(ExerciseRegion::of(times[num_steps], expiring));
+        let mut basis = Vec::with_capacity(size);
+        let mut in_the_money = Vec::new();
+        for j in (1..num_steps).rev() {
+            let discount = (-rate * (times[j + 1] - times[j])).exp();
+            cash.iter_mut().for_each(|value| *value = *value * discount);
+            in_the_money.clear();
+            in_the_money.extend(paths.iter().enumerate().filter_map(|(i, path)| {
+                let exercise = payoff.value(times[j], path[j]);
+                (exercise > T::zero()).then_some((i, path[j] / scale, exercise))
+            }));
+            if in_the_money.len() <= size {
+                continue;
+            }
+            let mut normal = vec![vec![T::zero(); size]; size];
+            let mut rhs = vec![T::zero(); size];
+            for &(i, x, _) in &in_the_money {
+                self.basis.values(x, self.degree, &mut basis);
+                for (row, &b) in normal.iter_mut().zip(&basis) {
+                    for (entry, &c) in row.iter_mut().zip(&basis) {
+                        *entry = *entry + b * c;
+                    }
+                }
+                for (entry, &b) in rhs.iter_mut().zip(&basis) {
+                    *entry = *entry + b * cash[i];
+                }
+            }
+            let Some(coefficients) = optimize::solve_linear(normal, rhs) else {
+                continue;
+            };
+            let mut exercised = Vec::new();
+            for &(i, x, exercise) in &in_the_money {
+                self.basis.values(x, self.degree, &mut basis);
+                let continuation = basis
+                    .iter()
+                    .zip(&coefficients)
+                    .fold(T::zero(), |sum, (&b, &c)| sum + b * c);
+                if exercise > continuation {
+                    cash[i] = exercise;
+                    exercise_steps[i] = Some(j);
+                    exercised.push(x * scale);
+                }
+            }
+            regions.extend(ExerciseRegion::of(times[j], exercised.into_iter()));
+        }
+        regions.reverse();
+        let discount = (-rate * (times[1] - times[0])).exp();
+        cash.iter_mut().for_each(|value| *value = *value * discount);
+        let mut estimate = MonteCarloEstimate::from_samples(&cash, paths.batch());
+        let immediate = payoff.value(times[0], scale);
+        if immediate > estimate.price {
+            estimate = MonteCarloEstimate {
+                price: immediate,
+                standard_error: T::zero(),
+                num_paths: estimate.num_paths,
+            };
+            exercise_steps.iter_mut().for_each(|step| *step = Some(0));
+            regions = vec![ExerciseRegion {
+                time: times[0],
+                lowest: scale,
+                highest: scale,
+            }];
+        }
+        Ok(LsmExercise {
+            estimate,
+            regions,
+            exercise_steps,
+        })
+    }
+
+    /// Price and standard error of `spec`, exercisable at every step unless
+    /// it is European, under the risk-neutral GBM of `Gbm::risk_neutral`.
+    /// Barrier contracts are rejected.
+    pub fn estimate<T: Float>(
+        &self,
+        spec: &OptionSpec<T>,
+        market: &Market<T>,
+    ) -> Result<MonteCarloEstimate<T>> {
+        let (paths, rate) = self.simulate(spec, market)?;
+        self.price_paths(&paths, spec, rate)
+    }
+
+    /// `estimate` together with the exercise rule fitted to its paths, as
+    /// `exercise_paths` gives it.
+    pub fn exercise<T: Float>(
+        &self,
+        spec: &OptionSpec<T>,
+        market: &Market<T>,
+    ) -> Result<LsmExercise<T>> {
+        let (paths, rate) = self.simulate(spec, market)?;
+        self.exercise_paths(&paths, spec, rate)
+    }
+
+    /// Paths of `spec`'s risk-neutral GBM on its exercise dates, with the
+    /// rate to discount them at.
+    fn simulate<T: Float>(
+        &self,
+        spec: &OptionSpec<T>,
+        market: &Market<T>,
+    ) -> Result<(Paths<T>, T)> {
+        if spec.barrier.is_some() {
+            return Err(OptOpsError::Unsupported {
+                engine: "Longstaff-Schwartz",
+                reason: "barrier options",
+            });
+        }
+        validate::check("degree", self.degree as f64, Domain::AtLeast(1.0))?;
+        let strike = validate::positive("strike", spec.strike)?;
+        let expiry = validate::positive("expiry", spec.expiry)?;
+        let model = Gbm::risk_neutral(market, strike, expiry)?;
+        let rate = market.continuous_rate(expiry)?;
+        let num_steps = match spec.exercise {
+            ExerciseStyle::American => self.num_steps,
+            ExerciseStyle::European => 1,
+        };
+        let paths = PathGenerator {
+            num_paths: self.num_paths,
+            num_steps,
+            seed: self.seed,
+            antithetic: self.antithetic,
+            sequence: self.sequence,
+            brownian_bridge: self.brownian_bridge,
+        }
+        .generate(&model, expiry)?;
+        Ok((paths, rate))
+    }
+}
+
+/// The spots at which paths were exercised on one exercise date.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ExerciseRegion<T = f64> {
+    pub time: T,
+    pub lowest: T,
+    pub highest: T,
+}
+
+impl<T: Float> ExerciseRegion<T> {
+    /// The range of `spots` at `time`, if there are any.
+    fn of(time: T, mut spots: impl Iterator<Item = T>) -> Option<Self> {
+        let first = spots.next()?;
+        let (lowest, highest) = spots.fold((first, first), |(lo, hi), s| (lo.min(s), hi.max(s)));
+        Some(ExerciseRegion {
+            time,
+            lowest,
+            highest,
+        })
+    }
+}
+
+/// The exercise rule a Longstaff-Schwartz regression fitted, read off its
+/// paths.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LsmExercise<T = f64> {
+    pub estimate: MonteCarloEstimate<T>,
+    /// Exercise dates on which some path was exercised, earliest first,
+    /// expiry among them.
+    pub regions: Vec<ExerciseRegion<T>>,
+    /// The step at which each path, in path order, is exercised, if it is.
+    pub exercise_steps: Vec<Option<usize>>,
+}
+
+impl<T: Float> LsmExercise<T> {
+    /// The edge of each region facing the money, as the lattice's
+    /// `option_exercise_boundary` draws it: the highest spot exercised for
+    /// a put, the lowest for a call.
+    pub fn boundary(&self, option_type: OptionType) -> ExerciseBoundary<T> {
+        self.regions
+            .iter()
+            .map(|region| match option_type {
+                OptionType::Call => (region.time, region.lowest),
+                OptionType::Put => (region.time, region.highest),
+            })
+            .collect()
+    }
+}
+
+/// `(time, lsm, lattice)` at each time of `lsm` within the span of
+/// `lattice`, the lattice's boundary interpolated linearly to it, so the
+/// two American engines can check each other.
+pub fn compare_boundaries<T: Float>(lsm: &[(T, T)], lattice: &[(T, T)]) -> Vec<(T, T, T)> {
+    lsm.iter()
+        .filter_map(|&(time, spot)| {
+            let above = lattice.partition_point(|&(t, _)| t < time);
+            let (t1, s1) = *lattice.get(above)?;
+            if t1 == time {
+                return Some((time, spot, s1));
+            }
+            let (t0, s0) = *lattice.get(above.checked_sub(1)?)?;
+            Some((time, spot, s0 + (s1 - s0) * (time - t0) / (t1 - t0)))
+        })
+        .collect()
+}
+
+impl PricingEngine for LongstaffSchwartz {
+    /// `estimate`, with the standard error as `error_estimate`, its 95%
+    /// interval as `confidence_interval` and the steps simulated, one for a
+    /// European spec, as `num_steps`.
+    fn price(&self, instrument: &OptionSpec, market: &Market) -> PriceResult {
+        let start = Instant::now();
+        let (paths, rate) = self.simulate(instrument, market)?;
+        let estimate = self.price_paths(&paths, instrument, rate)?;
+        let mut result = PricingResult::new("Longstaff-Schwartz", estimate.price, start.elapsed());
+        result.num_steps = Some(paths.num_steps());
+        result.error_estimate = Some(estimate.standard_error);
+        result.confidence_interval = Some(estimate.confidence_interval(0.95)?);
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analytic::black_scholes_price;
+    use crate::units::{Rate, Spot, Strike, Vol};
+
+    /// Longstaff and Schwartz's (2001) Table 1: puts struck at 40 with 50
+    /// exercise dates a year, against the finite-difference American price.
+    #[test]
+    fn american_puts_match_longstaff_schwartz() {
+        let rows = [
+            (36.0, 0.2, 1.0, 4.478),
+            (36.0, 0.4, 2.0, 8.508),
+            (40.0, 0.2, 1.0, 2.314),
+            (44.0, 0.4, 1.0, 3.948),
+        ];
+        for (spot, vol, expiry, american) in rows {
+            let market: Market = Market::new(Spot(spot), Rate(0.06), Vol(vol));
+            let spec = OptionSpec::american(OptionType::Put, Strike(40.0), expiry);
+            let lsm = LongstaffSchwartz::default()
+                .with_num_paths(20_000)
+                .with_num_steps((50.0 * expiry) as usize)
+                .with_seed(82)
+                .with_antithetic(true);
+            let estimate = lsm.estimate(&spec, &market).unwrap();
+            let gap = (estimate.price - american).abs();
+            assert!(
+                gap < 0.02 + 3.0 * estimate.standard_error,
+                "{spot} {vol} {expiry}: {estimate:?} vs {american}"
+            );
+        }
+    }
+
+    /// Every basis prices the same Bermudan put, above the European.
+    #[test]
+    fn bases_agree_and_early_exercise_adds_value() {
+        let market: Market = Market::new(Spot(100.0), Rate(0.05), Vol(0.3));
+        let spec = OptionSpec::american(OptionType::Put, Strike(100.0), 1.0);
+        let european = OptionSpec {
+            exercise: ExerciseStyle::European,
+            ..spec
+        };
+        let bs = black_scholes_price(&european, &market).unwrap();
+        let prices: Vec<MonteCarloEstimate> = [Basis::Monomial, Basis::Laguerre, Basis::Hermite]
+            .into_iter()
+            .map(|basis| {
+                LongstaffSchwartz::default()
+                    .with_num_steps(25)
+                    .with_basis(basis)
+                    .estimate(&spec, &market)
+                    .unwrap()
+            })
+            .collect();
+        for estimate in &prices {
+            assert!(
+                estimate.price > bs + 3.0 * estimate.standard_error,
+                "{estimate:?}"
+            );
+            assert!(
+                (estimate.price - prices[0].price).abs() < 0.02,
+                "{prices:?}"
+            );
+        }
+    }
+
+    /// A European spec needs no exercise rule, simulates a single step and
+    /// prices at Black-Scholes.
+    #[test]
+    fn european_specs_match_black_scholes() {
+        let market: Market =
+            Market::new(Spot(100.0), Rate(0.04), Vol(0.2)).with_dividend_yield(Rate(0.02));
+        for option_type in [OptionType::Call, OptionType::Put] {
+            let spec = OptionSpec::european(option_type, Strike(105.0), 0.5);
+            let exact = black_scholes_price(&spec, &market).unwrap();
+            let lsm = LongstaffSchwartz::default();
+            let estimate = lsm.estimate(&spec, &market).unwrap();
+            assert!(
+                (estimate.price - exact).abs() < 4.0 * estimate.standard_error,
+                "{option_type:?}: {estimate:?} vs {exact}"
+            );
+            assert_eq!(lsm.price(&spec, &market).unwrap().num_steps, Some(1));
+        }
+    }
+}
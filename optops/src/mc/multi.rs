@@ -0,0 +1,280 @@
+//! Correlated multi-asset paths and the options on several assets they
+//! price.
+
+use super::{Gbm, PathModel, PathPayoff, Philox};
+use crate::error::{Domain, OptOpsError, Result};
+use crate::float::{cast, Float};
+use crate::instrument::OptionType;
+use crate::optimize;
+use crate::validate;
+use serde::{Deserialize, Serialize};
+
+/// Smallest eigenvalue a repaired correlation matrix keeps, so its
+/// Cholesky factor exists.
+const MIN_EIGENVALUE: f64 = 1e-10;
+
+/// Correlated geometric Brownian motions, one per asset, each with its own
+/// spot, drift and vol and shocks correlated by `correlation`. The state is
+/// the spots, driven by one normal per asset a step.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(bound(
+    serialize = "T: Float + Serialize",
+    deserialize = "T: Float + Deserialize<'de>"
+))]
+pub struct MultiGbm<T = f64> {
+    assets: Vec<Gbm<T>>,
+    correlation: Vec<Vec<T>>,
+    /// Lower-triangular Cholesky factor of `correlation`.
+    cholesky: Vec<Vec<T>>,
+}
+
+impl<T: Float> MultiGbm<T> {
+    /// `assets` correlated by the square `correlation`, with unit diagonal
+    /// and entries in `[-1, 1]`. A matrix that is not positive definite, as
+    /// one estimated pairwise or bumped by hand may not be, is repaired by
+    /// clipping its eigenvalues just above zero and rescaling back to a
+    /// unit diagonal, and `correlation` returns what was used; an
+    /// asymmetric one is first averaged with its transpose. The repair is
+    /// a single eigenvalue clip, close to the input but not in general
+    /// Higham's (2002) nearest correlation matrix.
+    pub fn new(assets: Vec<Gbm<T>>, correlation: &[Vec<T>]) -> Result<Self> {
+        let n = assets.len();
+        if n == 0 {
+            return Err(OptOpsError::MissingParameter("assets"));
+        }
+        let size = Domain::Between(n as f64, n as f64);
+        validate::check("correlation", correlation.len() as f64, size)?;
+        for (i, row) in correlation.iter().enumerate() {
+            validate::check("correlation", row.len() as f64, size)?;
+            for (j, &rho) in row.iter().enumerate() {
+                let domain = if i == j {
+                    Domain::Between(1.0, 1.0)
+                } else {
+                    Domain::Between(-1.0, 1.0)
+                };
+                validate::check("correlation", rho, domain)?;
+            }
+        }
+        let half = cast::<T>(0.5);
+        let symmetric: Vec<Vec<T>> = (0..n)
+            .map(|i| {
+                (0..n)
+                    .map(|j| (correlation[i][j] + correlation[j][i]) * half)
+                    .collect()
+            })
+            .collect();
+        let (correlation, cholesky) = match cholesky(&symmetric) {
+            Some(factor) => (symmetric, factor),
+            None => {
+                let repaired = clip_eigenvalues(symmetric);
+                let factor = cholesky(&repaired).ok_or(OptOpsError::NonConvergence {
+                    solver: "correlation repair",
+                    iterations: 1,
+                })?;
+                (repaired, factor)
+            }
+        };
+        Ok(MultiGbm {
+            assets,
+            correlation,
+            cholesky,
+        })
+    }
+
+    pub fn assets(&self) -> &[Gbm<T>] {
+        &self.assets
+    }
+
+    /// The correlation simulated, after any repair.
+    pub fn correlation(&self) -> &[Vec<T>] {
+        &self.correlation
+    }
+}
+
+impl<T: Float> PathModel<T> for MultiGbm<T> {
+    fn factors(&self) -> usize {
+        self.assets.len()
+    }
+
+    fn initial_state(&self) -> Vec<T> {
+        self.assets.iter().map(|asset| asset.spot).collect()
+    }
+
+    fn assets(&self) -> usize {
+        self.assets.len()
+    }
+
+    /// Each asset's exact log step, driven by the independent `normals`
+    /// mixed through the Cholesky factor.
+    fn step(&self, _: T, dt: T, state: &mut [T], normals: &[T], _: &mut Philox) {
+        let root = dt.sqrt();
+        for ((spot, asset), row) in state.iter_mut().zip(&self.assets).zip(&self.cholesky) {
+            let shock = row
+                .iter()
+                .zip(normals)
+                .fold(T::zero(), |sum, (&l, &z)| sum + l * z);
+            let drift = (asset.drift - asset.vol * asset.vol * cast(0.5)) * dt;
+            *spot = *spot * (drift + asset.vol * root * shock).exp();
+        }
+    }
+}
+
+/// Lower-triangular `L` with `L L^T = matrix`, or `None` unless `matrix` is
+/// positive definite.
+fn cholesky<T: Float>(matrix: &[Vec<T>]) -> Option<Vec<Vec<T>>> {
+    let n = matrix.len();
+    let mut factor = vec![vec![T::zero(); n]; n];
+    for i in 0..n {
+        for j in 0..=i {
+            let dot = (0..j).fold(T::zero(), |sum, k| sum + factor[i][k] * factor[j][k]);
+            if i == j {
+                let pivot = matrix[i][i] - dot;
+                if pivot.is_nan() || pivot <= T::zero() {
+                    return None;
+                }
+                factor[i][i] = pivot.sqrt();
+            } else {
+                factor[i][j] = (matrix[i][j] - dot) / factor[j][j];
+            }
+        }
+    }
+    Some(factor)
+}
+
+/// `matrix` with its eigenvalues floored just above zero and rescaled back
+/// to a unit diagonal.
+fn clip_eigenvalues<T: Float>(matrix: Vec<Vec<T>>) -> Vec<Vec<T>> {
+    let n = matrix.len();
+    let (values, vectors) = optimize::symmetric_eigen(matrix);
+    let floor = cast::<T>(MIN_EIGENVALUE);
+    let rebuilt: Vec<Vec<T>> = (0..n)
+        .map(|i| {
+            (0..n)
+                .map(|j| {
+                    values
+                        .iter()
+                        .zip(&vectors)
+                        .fold(T::zero(), |sum, (&value, vector)| {
+                            sum + value.max(floor) * vector[i] * vector[j]
+                        })
+                })
+                .collect()
+        })
+        .collect();
+    (0..n)
+        .map(|i| {
+            (0..n)
+                .map(|j| rebuilt[i][j] / (rebuilt[i][i] * rebuilt[j][j]).sqrt())
+                .collect()
+        })
+        .collect()
+}
+
+/// What an option on several assets settles on at expiry.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Combination<T = f64> {
+    /// The weighted sum of the spots, one weight per asset.
+    Basket(Vec<T>),
+    /// The first spot less the second; struck at zero it is Margrabe's
+    /// exchange option.
+    Spread,
+    /// The highest spot: a rainbow call on the best of the assets.
+    BestOf,
+    /// The lowest spot.
+    WorstOf,
+}
+
+/// A European call or put on a combination of the assets' spots at expiry,
+/// priced over `MultiGbm` paths by `price_exotic`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(bound(
+    serialize = "T: Float + Serialize",
+    deserialize = "T: Float + Deserialize<'de>"
+))]
+pub struct MultiAssetOption<T = f64> {
+    pub option_type: OptionType,
+    pub strike: T,
+    pub combination: Combination<T>,
+}
+
+impl<T: Float> MultiAssetOption<T> {
+    pub fn new(option_type: OptionType, strike: T, combination: Combination<T>) -> Self {
+        MultiAssetOption {
+            option_type,
+            strike,
+            combination,
+        }
+    }
+
+    /// The settlement value of `spots`, one per asset.
+    pub fn underlying(&self, spots: &[T]) -> T {
+        match &self.combination {
+            Combination::Basket(weights) => weights
+                .iter()
+                .zip(spots)
+                .fold(T::zero(), |sum, (&w, &s)| sum + w * s),
+            Combination::Spread => spots[0] - spots[1],
+            Combination::BestOf => spots.iter().fold(T::neg_infinity(), |best, &s| best.max(s)),
+            Combination::WorstOf => spots.iter().fold(T::infinity(), |worst, &s| worst.min(s)),
+        }
+    }
+}
+
+/// The spots at the last time of the path, however many assets it holds.
+impl<T: Float> PathPayoff<T> for MultiAssetOption<T> {
+    fn present_value(&self, times: &[T], path: &[T], rate: T) -> T {
+        let assets = path.len() / times.len();
+        let expiry = times[times.len() - 1];
+        let spots = &path[path.len() - assets..];
+        (-rate * expiry).exp()
+            * self
+                .option_type
+                .intrinsic(self.underlying(spots), self.strike)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assets() -> Vec<Gbm> {
+        vec![Gbm::new(100.0, 0.03, 0.2).unwrap(); 3]
+    }
+
+    /// A positive definite matrix is simulated as given.
+    #[test]
+    fn valid_correlations_are_kept() {
+        let correlation = vec![
+            vec![1.0, 0.5, 0.2],
+            vec![0.5, 1.0, -0.3],
+            vec![0.2, -0.3, 1.0],
+        ];
+        let model = MultiGbm::new(assets(), &correlation).unwrap();
+        assert_eq!(model.correlation(), correlation.as_slice());
+    }
+
+    /// An indefinite matrix is clipped to a valid correlation near it.
+    #[test]
+    fn indefinite_correlations_are_repaired() {
+        let correlation = vec![
+            vec![1.0, 0.9, 0.7],
+            vec![0.9, 1.0, -0.4],
+            vec![0.7, -0.4, 1.0],
+        ];
+        assert!(cholesky(&correlation).is_none());
+        let model = MultiGbm::new(assets(), &correlation).unwrap();
+        let repaired = model.correlation();
+        assert!(cholesky(repaired).is_some());
+        for i in 0..3 {
+            assert!((repaired[i][i] - 1.0).abs() < 1e-12);
+            for j in 0..3 {
+                assert_eq!(repaired[i][j], repaired[j][i]);
+                assert!(
+                    (repaired[i][j] - correlation[i][j]).abs() < 0.3,
+                    "{repaired:?}"
+                );
+            }
+        }
+    }
+}
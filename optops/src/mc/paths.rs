@@ -0,0 +1,568 @@
+//! Simulated paths on a uniform time grid, and the dynamics behind them.
+
+use super::{BrownianBridge, Philox, RandomSource, Sobol};
+use crate::core::inverse_norm_cdf;
+use crate::error::{Domain, Result};
+use crate::float::{cast, Float};
+use crate::market::Market;
+use crate::validate;
+use serde::{Deserialize, Serialize};
+
+/// Dynamics `PathGenerator` can simulate: a spot, perhaps with further
+/// state such as its variance, advanced one step at a time by a fixed
+/// number of standard normals. Those normals come from the generator's
+/// sequence, so they are the ones Sobol points and the Brownian bridge
+/// order; anything else a step needs, such as jump counts, it draws from
+/// `extra`, the path's own stream.
+pub trait PathModel<T: Float>: Sync {
+    /// Normals each step takes, at least one.
+    fn factors(&self) -> usize {
+        1
+    }
+
+    /// Values of the state, the spot first, at time zero; `step` is handed
+    /// the same number back.
+    fn initial_state(&self) -> Vec<T>;
+
+    /// Leading values of the state the paths record at each time: one, the
+    /// spot, unless the model simulates several assets.
+    fn assets(&self) -> usize {
+        1
+    }
+
+    /// Advances `state` from time `t` over `dt` driven by `normals`.
+    fn step(&self, t: T, dt: T, state: &mut [T], normals: &[T], extra: &mut Philox);
+}
+
+/// Geometric Brownian motion `dS = mu S dt + sigma S dW`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Gbm<T = f64> {
+    pub spot: T,
+    /// Drift `mu` per year, continuously compounded.
+    pub drift: T,
+    pub vol: T,
+}
+
+impl<T: Float> Gbm<T> {
+    pub fn new(spot: T, drift: T, vol: T) -> Result<Self> {
+        Ok(Gbm {
+            spot: validate::positive("spot", spot)?,
+            drift: validate::finite("drift", drift)?,
+            vol: validate::check("vol", vol, Domain::NonNegative)?,
+        })
+    }
+
+    /// The risk-neutral dynamics `market` gives an option struck at
+    /// `strike` expiring at `expiry`: the spot net of dividends as the
+    /// analytic pricers escrow them, drifting at the rate less the carry
+    /// yield, with the vol `Market::vol_for` reads for the option.
+    pub fn risk_neutral(market: &Market<T>, strike: T, expiry: T) -> Result<Self> {
+        market.validate()?;
+        let expiry = validate::positive("expiry", expiry)?;
+        let rate = market.continuous_rate(expiry)?;
+        let spot = market.escrowed_spot(expiry)? * market.dividends.retained_fraction(expiry);
+        let vol = market.vol_for(strike, expiry)?;
+        Gbm::new(spot, rate - market.carry_yield(rate, vol), vol)
+    }
+}
+
+impl<T: Float> PathModel<T> for Gbm<T> {
+    fn initial_state(&self) -> Vec<T> {
+        vec![self.spot]
+    }
+
+    /// Exactly in the log, `S(t + dt) = S(t) exp((mu - sigma^2 / 2) dt +
+    /// sigma sqrt(dt) Z)`, so any step count samples the true distribution
+    /// at the grid times.
+    fn step(&self, _: T, dt: T, state: &mut [T], normals: &[T], _: &mut Philox) {
+        let drift = (self.drift - self.vol * self.vol * cast(0.5)) * dt;
+        state[0] = state[0] * (drift + self.vol * dt.sqrt() * normals[0]).exp();
+    }
+}
+
+/// Simulated paths on a shared time grid, stored path after path and, for
+/// several assets, asset after asset at each time.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Paths<T = f64> {
+    /// `num_steps + 1` times from zero.
+    times: Vec<T>,
+    values: Vec<T>,
+    batch: usize,
+    assets: usize,
+}
+
+impl<T: Float> Paths<T> {
+    pub fn num_paths(&self) -> usize {
+        self.values.len() / self.path_len()
+    }
+
+    /// Assets recorded at each time, as `PathModel::assets`.
+    pub fn assets(&self) -> usize {
+        self.assets
+    }
+
+    fn path_len(&self) -> usize {
+        self.times.len() * self.assets
+    }
+
+    pub fn num_steps(&self) -> usize {
+        self.times.len() - 1
+    }
+
+    /// The grid every path is sampled on, from zero to expiry.
+    pub fn times(&self) -> &[T] {
+        &self.times
+    }
+
+    /// Values of path `i` at each of `times`: the `j`-th asset at time `k`
+    /// is at `k * assets + j`.
+    pub fn path(&self, i: usize) -> &[T] {
+        let len = self.path_len();
+        &self.values[i * len..(i + 1) * len]
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &[T]> {
+        self.values.chunks_exact(self.path_len())
+    }
+
+    /// Consecutive paths that together make one independent sample: one
+    /// for independent draws, two for antithetic pairs, and every path of
+    /// a Sobol shift or a stratified replication. Estimates average each
+    /// batch before taking their spread.
+    pub fn batch(&self) -> usize {
+        self.batch
+    }
+
+    /// Each path's value at expiry, of the last asset where there are
+    /// several.
+    pub fn terminal(&self) -> impl Iterator<Item = T> + '_ {
+        self.iter().map(|path| path[path.len() - 1])
+    }
+
+    /// `f` of every path, in path order; with the `rayon` feature the paths
+    /// are shared across the thread pool, so `f` must be shareable too,
+    /// unless an adjoint tape is recording on this thread.
+    pub fn evaluate<U, F>(&self, f: F) -> Vec<U>
+    where
+        U: Send,
+        F: Fn(&[T]) -> U + Sync,
+    {
+        #[cfg(feature = "rayon")]
+        if !crate::adjoint::recording() {
+            use rayon::prelude::*;
+            return self
+                .values
+                .par_chunks_exact(self.path_len())
+                .map(&f)
+                .collect();
+        }
+        self.iter().map(f).collect()
+    }
+}
+
+/// Where the normals driving the paths come from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Sequence {
+    /// Independent pseudo-random draws, path `i` from stream `i` of
+    /// `Philox::new(seed)`.
+    #[default]
+    Pseudorandom,
+    /// Randomised quasi-Monte Carlo: the points of a Sobol sequence with
+    /// one dimension per factor per step, at most `MAX_DIMENSIONS` in all,
+    /// under `shifts` independent random digital shifts that share the
+    /// paths equally. The paths of each shift form one sample, so at least
+    /// two are needed for a standard error; the error itself falls almost
+    /// as `1 / n` for smooth payoffs rather than `1 / sqrt(n)`.
+    Sobol { shifts: usize },
+    /// Pseudo-random draws with each path's end stratified: the paths of
+    /// each of `replications` equal groups share the quantiles of the
+    /// first factor's terminal normal one to a stratum, drawn uniformly
+    /// within it, and the rest of the path is filled in by the Brownian
+    /// bridge. Takes out the part of the variance the terminal value
+    /// explains, nearly all of it for a European payoff; each group is one
+    /// sample, as each Sobol shift is.
+    Stratified { replications: usize },
+    /// Latin hypercube sampling of the first `dimensions` draws in the
+    /// Brownian bridge's order of importance (the terminal values of the
+    /// factors, then their midpoints, and so on): within each of
+    /// `replications` groups every one of those draws visits each stratum
+    /// of its own once, the strata matched across draws by independent
+    /// random permutations, the remaining draws pseudo-random. One
+    /// dimension is `Stratified`.
+    LatinHypercube {
+        dimensions: usize,
+        replications: usize,
+    },
+}
+
+impl Sequence {
+    /// Groups the paths are split into as independent samples, if the
+    /// sequence groups them.
+    fn groups(&self) -> Option<usize> {
+        match *self {
+            Sequence::Pseudorandom => None,
+            Sequence::Sobol { shifts } => Some(shifts),
+            Sequence::Stratified { replications }
+            | Sequence::LatinHypercube { replications, .. } => Some(replications),
+        }
+    }
+
+    /// Leading draws stratified.
+    fn stratified(&self) -> usize {
+        match *self {
+            Sequence::Stratified { .. } => 1,
+            Sequence::LatinHypercube { dimensions, .. } => dimensions,
+            _ => 0,
+        }
+    }
+}
+
+/// How many paths of how many steps to draw, and from which seed; the same
+/// settings always give the same paths. Each path reads its own
+/// counter-based stream of the seed (each Sobol shift its own for the
+/// shift), so a path depends only on the seed and its index, not on the
+/// order the paths are drawn in or the threads they are drawn on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PathGenerator {
+    pub num_paths: usize,
+    /// Equal steps from zero to expiry.
+    pub num_steps: usize,
+    pub seed: u64,
+    /// Pair each path with its mirror image, driven by the same normals
+    /// negated, which roughly halves the variance of estimates smooth in
+    /// the draws for the cost of the exponentials alone. An odd last path
+    /// goes unpaired.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub antithetic: bool,
+    #[serde(default)]
+    pub sequence: Sequence,
+    /// Build each path by `BrownianBridge` rather than step after step,
+    /// so Sobol's best coordinates drive its broad shape. The paths have
+    /// the same distribution either way.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub brownian_bridge: bool,
+}
+
+impl Default for PathGenerator {
+    fn default() -> Self {
+        PathGenerator {
+            num_paths: 10_000,
+            num_steps: 100,
+            seed: 0,
+            antithetic: false,
+            sequence: Sequence::Pseudorandom,
+            brownian_bridge: false,
+        }
+    }
+}
+
+impl PathGenerator {
+    pub fn with_num_paths(self, num_paths: usize) -> Self {
+        PathGenerator { num_paths, ..self }
+    }
+
+    pub fn with_num_steps(self, num_steps: usize) -> Self {
+        PathGenerator { num_steps, ..self }
+    }
+
+    pub fn with_seed(self, seed: u64) -> Self {
+        PathGenerator { seed, ..self }
+    }
+
+    pub fn with_antithetic(self, antithetic: bool) -> Self {
+        PathGenerator { antithetic, ..self }
+    }
+
+    pub fn with_sequence(self, sequence: Sequence) -> Self {
+        PathGenerator { sequence, ..self }
+    }
+
+    pub fn with_brownian_bridge(self, brownian_bridge: bool) -> Self {
+        PathGenerator {
+            brownian_bridge,
+            ..self
+        }
+    }
+
+    /// Paths of `model` out to `expiry`, one `PathModel::step` per grid
+    /// step. Sobol and stratified paths round `num_paths` up to a multiple
+    /// of their groups, and Sobol paths take one dimension per factor per
+    /// step, step after step. With the `rayon` feature the paths are drawn
+    /// across the thread pool in runs of consecutive paths, and come out
+    /// the same as on one thread.
+    pub fn generate<T: Float, M: PathModel<T>>(&self, model: &M, expiry: T) -> Result<Paths<T>> {
+        self.generate_block(model, expiry, 0)
+    }
+
+    /// The `block`-th run of `num_paths` paths, continuing the streams of
+    /// the runs before it: pseudo-random paths are those a single run of
+    /// all the blocks would draw, and each block of Sobol paths takes
+    /// shifts of its own. Keep `num_paths` even for antithetic pairs.
+    pub fn generate_block<T: Float, M: PathModel<T>>(
+        &self,
+        model: &M,
+        expiry: T,
+        block: usize,
+    ) -> Result<Paths<T>> {
+        let expiry = validate::positive("expiry", expiry)?;
+        validate::check("num_paths", self.num_paths as f64, Domain::AtLeast(1.0))?;
+        validate::check("num_steps", self.num_steps as f64, Domain::AtLeast(1.0))?;
+        let factors = model.factors();
+        validate::check("factors", factors as f64, Domain::AtLeast(1.0))?;
+        let times = (0..=self.num_steps)
+            .map(|i| expiry * cast(i as f64) / cast(self.num_steps as f64))
+            .collect();
+        let (num_paths, batch) = match self.sequence.groups() {
+            None => (self.num_paths, if self.antithetic { 2 } else { 1 }),
+            Some(groups) => {
+                let name = match self.sequence {
+                    Sequence::Sobol { .. } => "shifts",
+                    _ => "replications",
+                };
+                validate::check(name, groups as f64, Domain::AtLeast(1.0))?;
+                let batch = self.num_paths.div_ceil(groups);
+                (batch * groups, batch)
+            }
+        };
+        let sobol = match self.sequence {
+            Sequence::Sobol { .. } => Some(Sobol::new(factors * self.num_steps)?),
+            _ => None,
+        };
+        let stratified = self.sequence.stratified();
+        if let Sequence::LatinHypercube { dimensions, .. } = self.sequence {
+            validate::check("dimensions", dimensions as f64, Domain::AtLeast(1.0))?;
+        }
+        let bridge = self.brownian_bridge || stratified > 0;
+        let first = block * num_paths;
+        let run = Run {
+            generator: self,
+            model,
+            factors,
+            expiry,
+            batch,
+            sobol,
+            bridge: bridge.then(|| BrownianBridge::new(self.num_steps)),
+        };
+        let assets = model.assets();
+        let mut values = vec![T::zero(); num_paths * (self.num_steps + 1) * assets];
+        #[cfg(feature = "rayon")]
+        if crate::adjoint::recording() {
+            run.fill(first, &mut values);
+        } else {
+            use rayon::prelude::*;
+            values
+                .par_chunks_mut(PARALLEL_CHUNK * (self.num_steps + 1) * assets)
+                .enumerate()
+                .for_each(|(chunk, values)| run.fill(first + chunk * PARALLEL_CHUNK, values));
+        }
+        #[cfg(not(feature = "rayon"))]
+        run.fill(first, &mut values);
+        Ok(Paths {
+            times,
+            values,
+            batch,
+            assets,
+        })
+    }
+}
+
+/// Paths per task when generating across the rayon thread pool.
+#[cfg(feature = "rayon")]
+const PARALLEL_CHUNK: usize = 1024;
+
+/// Streams of the seed from which the Sobol shifts are drawn, apart from
+/// the paths' own.
+const SHIFT_STREAMS: u64 = 1 << 63;
+
+/// Streams keying the Latin hypercube permutations, apart from the shifts'.
+const PERMUTATION_STREAMS: u64 = 3 << 62;
+
+/// What every path of one `generate` call shares.
+struct Run<'a, T, M> {
+    generator: &'a PathGenerator,
+    model: &'a M,
+    factors: usize,
+    expiry: T,
+    batch: usize,
+    /// The unshifted sequence, for Sobol draws.
+    sobol: Option<Sobol>,
+    bridge: Option<BrownianBridge>,
+}
+
+impl<T: Float, M: PathModel<T>> Run<'_, T, M> {
+    /// Paths `first..` into `values`, each from its index alone, so any
+    /// split of the paths into runs gives the same values.
+    fn fill(&self, first: usize, values: &mut [T]) {
+        let (num_steps, factors) = (self.generator.num_steps, self.factors);
+        let dimension = num_steps * factors;
+        let dt = self.expiry / cast(num_steps as f64);
+        let streams = Philox::new(self.generator.seed);
+        // The shifted sequence of the current batch, with its index.
+        let mut shifted: Option<(usize, Sobol)> = None;
+        let mut draws = vec![T::zero(); dimension];
+        // By step, then factor.
+        let mut normals = vec![T::zero(); dimension];
+        let mut ordered = vec![T::zero(); num_steps];
+        let mut increments = vec![T::zero(); num_steps];
+        let assets = self.model.assets();
+        for (i, path) in (first..).zip(values.chunks_exact_mut((num_steps + 1) * assets)) {
+            // Antithetic pairs restart with each batch.
+            let mirrored = self.generator.antithetic && (i % self.batch) % 2 == 1;
+            let fresh = if mirrored { i - 1 } else { i };
+            let mut extra = streams.stream(fresh as u64);
+            if !mirrored || i == first {
+                if let Some(sobol) = &self.sobol {
+                    let batch = fresh / self.batch;
+                    if shifted
+                        .as_ref()
+                        .is_none_or(|(current, _)| *current != batch)
+                    {
+                        let mut stream = streams.stream(SHIFT_STREAMS | batch as u64);
+                        shifted = Some((batch, sobol.clone().with_digital_shift(&mut stream)));
+                    }
+                    let (_, points) = shifted.as_mut().expect("shifted for this batch");
+                    let pairs = if self.generator.antithetic { 2 } else { 1 };
+                    points.seek(((fresh % self.batch) / pairs) as u64);
+                    points.next_point(&mut draws);
+                    draws.iter_mut().for_each(|z| *z = inverse_norm_cdf(*z));
+                } else {
+                    let pairs = if self.generator.antithetic { 2 } else { 1 };
+                    let strata = self.batch.div_ceil(pairs);
+                    let (group, stratum) = (fresh / self.batch, (fresh % self.batch) / pairs);
+                    let stratified = self.generator.sequence.stratified();
+                    for (d, z) in draws.iter_mut().enumerate() {
+                        let u = extra.uniform();
+                        *z = inverse_norm_cdf(cast(if d < stratified {
+                            let cell = if stratified == 1 {
+                                stratum
+                            } else {
+                                let key = PERMUTATION_STREAMS | ((group as u64) << 20) | d as u64;
+                                permute(stratum as u64, strata as u64, streams.stream(key)) as usize
+                            };
+                            (cell as f64 + u) / strata as f64
+                        } else {
+                            u
+                        }));
+                    }
+                }
+                match &self.bridge {
+                    // Each factor's own motion, from its draws in order of
+                    // importance.
+                    Some(bridge) => {
+                        for factor in 0..factors {
+                            for (z, &draw) in ordered
+                                .iter_mut()
+                                .zip(draws[factor..].iter().step_by(factors))
+                            {
+                                *z = draw;
+                            }
+                            bridge.transform(&ordered, &mut increments);
+                            for (z, &increment) in normals[factor..]
+                                .iter_mut()
+                                .step_by(factors)
+                                .zip(&increments)
+                            {
+                                *z = increment;
+                            }
+                        }
+                    }
+                    None => normals.copy_from_slice(&draws),
+                }
+            }
+            if mirrored {
+                normals.iter_mut().for_each(|z| *z = -*z);
+            }
+            // Past the normals a pseudo-random path drew from it.
+            extra.seek(dimension as u64);
+            let mut state = self.model.initial_state();
+            let (start, rest) = path.split_at_mut(assets);
+            start.copy_from_slice(&state[..assets]);
+            for (k, (values, normals)) in rest
+                .chunks_exact_mut(assets)
+                .zip(normals.chunks_exact(factors))
+                .enumerate()
+            {
+                let t = self.expiry * cast(k as f64) / cast(num_steps as f64);
+                self.model.step(t, dt, &mut state, normals, &mut extra);
+                values.copy_from_slice(&state[..assets]);
+            }
+        }
+    }
+}
+
+/// `index` under a random permutation of `0..n` keyed by `key`: a
+/// four-round Feistel network on the smallest even number of bits
+/// covering `n`, walked until it lands inside the range.
+fn permute(index: u64, n: u64, mut key: Philox) -> u64 {
+    let half = (u64::BITS - n.saturating_sub(1).leading_zeros())
+        .div_ceil(2)
+        .max(1);
+    let mask = (1 << half) - 1;
+    let mut x = index;
+    loop {
+        let (mut left, mut right) = (x >> half, x & mask);
+        for round in 0..4u64 {
+            key.seek(round << 32 | right);
+            (left, right) = (right, left ^ (key.next_u64() & mask));
+        }
+        x = left << half | right;
+        if x < n {
+            return x;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A later block draws the paths a single longer run would.
+    #[test]
+    fn blocks_continue_the_streams() {
+        let model = Gbm::new(100.0, 0.03, 0.2).unwrap();
+        let generator = PathGenerator::default()
+            .with_num_paths(400)
+            .with_num_steps(6)
+            .with_seed(87);
+        let whole = generator.with_num_paths(800).generate(&model, 1.0).unwrap();
+        let second = generator.generate_block(&model, 1.0, 1).unwrap();
+        for i in 0..400 {
+            assert_eq!(second.path(i), whole.path(400 + i), "{i}");
+        }
+    }
+
+    /// The paths are the same on one thread as on many.
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn paths_do_not_depend_on_the_thread_count() {
+        let model = Gbm::new(100.0, 0.03, 0.2).unwrap();
+        let on = |threads: usize, generator: &PathGenerator| {
+            rayon::ThreadPoolBuilder::new()
+                .num_threads(threads)
+                .build()
+                .unwrap()
+                .install(|| generator.generate(&model, 1.0).unwrap())
+        };
+        let base = PathGenerator::default()
+            .with_num_paths(3000)
+            .with_num_steps(8)
+            .with_seed(87);
+        let generators = [
+            base,
+            base.with_antithetic(true),
+            base.with_sequence(Sequence::Sobol { shifts: 4 })
+                .with_brownian_bridge(true),
+            base.with_sequence(Sequence::LatinHypercube {
+                dimensions: 3,
+                replications: 5,
+            }),
+        ];
+        for generator in generators {
+            let single = on(1, &generator);
+            for threads in [2, 5] {
+                assert_eq!(on(threads, &generator), single, "{generator:?}");
+            }
+        }
+    }
+}
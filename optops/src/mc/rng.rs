@@ -0,0 +1,206 @@
+//! Seeded pseudo-random numbers for the simulations.
+
+use crate::core::inverse_norm_cdf;
+use crate::float::{cast, Float};
+
+/// A stream of random 64-bit words, and the uniforms and normals the
+/// simulations draw from it.
+pub trait RandomSource {
+    fn next_u64(&mut self) -> u64;
+
+    /// Uniform on the open interval `(0, 1)`, from the top 53 bits.
+    fn uniform(&mut self) -> f64 {
+        ((self.next_u64() >> 11) as f64 + 0.5) * (1.0 / (1u64 << 53) as f64)
+    }
+
+    /// Standard normal, by inverting the normal CDF at a uniform.
+    fn normal<T: Float>(&mut self) -> T
+    where
+        Self: Sized,
+    {
+        inverse_norm_cdf(cast(self.uniform()))
+    }
+}
+
+/// Xoshiro256++ (Blackman and Vigna, 2019), a small fast generator with a
+/// period of `2^256 - 1`, seeded through SplitMix64 so that nearby seeds
+/// start unrelated streams. The crate's own implementation, so a seed gives
+/// the same numbers whatever the versions of its dependencies.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rng {
+    state: [u64; 4],
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        let mut mix = seed;
+        Rng {
+            state: [(); 4].map(|_| splitmix64(&mut mix)),
+        }
+    }
+}
+
+impl RandomSource for Rng {
+    fn next_u64(&mut self) -> u64 {
+        let [s0, s1, s2, s3] = self.state;
+        let result = s0.wrapping_add(s3).rotate_left(23).wrapping_add(s0);
+        let shifted = s1 << 17;
+        let (mut s0, mut s1, mut s2, mut s3) = (s0, s1, s2 ^ s0, s3 ^ s1);
+        s1 ^= s2;
+        s0 ^= s3;
+        s2 ^= shifted;
+        s3 = s3.rotate_left(45);
+        self.state = [s0, s1, s2, s3];
+        result
+    }
+}
+
+/// Philox4x32-10 (Salmon, Moraes, Dror and Shaw, 2011), a counter-based
+/// generator: each word is a keyed hash of its position, so any stream and
+/// any point in it can be reached at once, with no state carried between
+/// them. Stream `i` of a seed reads the counters whose high half is `i`,
+/// giving `2^64` disjoint streams of `2^65` words each; a simulation that
+/// draws path `i` from stream `i` gets the same paths however the work is
+/// split between threads.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Philox {
+    key: [u32; 2],
+    stream: u64,
+    /// Blocks of four words consumed so far.
+    block: u64,
+    /// The second word of the current block, not yet returned.
+    spare: Option<u64>,
+}
+
+impl Philox {
+    /// Stream zero of `seed`.
+    pub fn new(seed: u64) -> Self {
+        Philox {
+            key: [seed as u32, (seed >> 32) as u32],
+            stream: 0,
+            block: 0,
+            spare: None,
+        }
+    }
+
+    /// Stream `stream` of the same seed, from its start.
+    pub fn stream(&self, stream: u64) -> Self {
+        Philox {
+            stream,
+            block: 0,
+            spare: None,
+            ..*self
+        }
+    }
+
+    /// Jumps to the `word`-th word of the current stream.
+    pub fn seek(&mut self, word: u64) {
+        self.block = word / 2;
+        self.spare = None;
+        if word % 2 == 1 {
+            let [_, second] = self.next_block();
+            self.spare = Some(second);
+        }
+    }
+
+    fn next_block(&mut self) -> [u64; 2] {
+        let counter = [
+            self.block as u32,
+            (self.block >> 32) as u32,
+            self.stream as u32,
+            (self.stream >> 32) as u32,
+        ];
+        self.block = self.block.wrapping_add(1);
+        let [a, b, c, d] = philox4x32(counter, self.key);
+        [(b as u64) << 32 | a as u64, (d as u64) << 32 | c as u64]
+    }
+}
+
+impl RandomSource for Philox {
+    fn next_u64(&mut self) -> u64 {
+        match self.spare.take() {
+            Some(word) => word,
+            None => {
+                let [first, second] = self.next_block();
+                self.spare = Some(second);
+                first
+            }
+        }
+    }
+}
+
+/// Ten Philox rounds of `counter` under `key`.
+fn philox4x32(mut counter: [u32; 4], mut key: [u32; 2]) -> [u32; 4] {
+    const MULTIPLIERS: [u64; 2] = [0xd251_1f53, 0xcd9e_8d57];
+    const WEYL: [u32; 2] = [0x9e37_79b9, 0xbb67_ae85];
+    for round in 0..10 {
+        if round > 0 {
+            key = [key[0].wrapping_add(WEYL[0]), key[1].wrapping_add(WEYL[1])];
+        }
+        let first = MULTIPLIERS[0] * counter[0] as u64;
+        let second = MULTIPLIERS[1] * counter[2] as u64;
+        counter = [
+            (second >> 32) as u32 ^ counter[1] ^ key[0],
+            second as u32,
+            (first >> 32) as u32 ^ counter[3] ^ key[1],
+            first as u32,
+        ];
+    }
+    counter
+}
+
+/// Steps `state` and returns its next SplitMix64 output.
+fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The known-answer vectors of Random123's `kat_vectors` for
+    /// philox4x32-10.
+    #[test]
+    fn philox_matches_random123() {
+        let vectors = [
+            (
+                [0; 4],
+                [0; 2],
+                [0x6627_e8d5, 0xe169_c58d, 0xbc57_ac4c, 0x9b00_dbd8],
+            ),
+            (
+                [u32::MAX; 4],
+                [u32::MAX; 2],
+                [0x408f_276d, 0x41c8_3b0e, 0xa20b_c7c6, 0x6d54_51fd],
+            ),
+            (
+                [0x243f_6a88, 0x85a3_08d3, 0x1319_8a2e, 0x0370_7344],
+                [0xa409_3822, 0x299f_31d0],
+                [0xd16c_fe09, 0x94fd_cceb, 0x5001_e420, 0x2412_6ea1],
+            ),
+        ];
+        for (counter, key, expected) in vectors {
+            assert_eq!(philox4x32(counter, key), expected);
+        }
+    }
+
+    /// Seeking lands on the word a sequential draw reaches, in any stream.
+    #[test]
+    fn philox_seeks_and_streams_agree_with_drawing() {
+        let root = Philox::new(87);
+        for stream in [0, 1, u64::MAX] {
+            let mut sequential = root.stream(stream);
+            let words: Vec<u64> = (0..9).map(|_| sequential.next_u64()).collect();
+            for (word, &expected) in words.iter().enumerate() {
+                let mut jumped = root.stream(stream);
+                jumped.seek(word as u64);
+                assert_eq!(jumped.next_u64(), expected, "{stream} {word}");
+            }
+        }
+        assert_ne!(root.stream(0).next_u64(), root.stream(1).next_u64());
+    }
+}
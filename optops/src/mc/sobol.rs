@@ -0,0 +1,260 @@
+//! Sobol low-discrepancy sequences, optionally randomised by a digital
+//! shift.
+
+use super::RandomSource;
+use crate::error::{Domain, Result};
+use crate::float::{cast, Float};
+use crate::validate;
+
+mod joe_kuo;
+
+/// Bits of every coordinate, so at most `2^32` points per sequence.
+const BITS: usize = 32;
+
+/// Dimensions with initial direction numbers from Joe and Kuo's (2008)
+/// table as far as this crate carries it: every primitive polynomial of
+/// degree at most 9.
+pub const MAX_DIMENSIONS: usize = joe_kuo::INITIAL.len() + 1;
+
+/// Sobol's (1967) sequence in up to `MAX_DIMENSIONS` dimensions, generated
+/// in Gray-code order (Antonov and Saleev, 1979) with one XOR per
+/// coordinate per point. Each coordinate after the first comes from the
+/// next primitive polynomial over GF(2), taken in order of degree, with
+/// Joe and Kuo's initial direction numbers, chosen for good
+/// two-dimensional projections.
+///
+/// A random digital shift XORs each coordinate with its own random bits:
+/// every shifted sequence is as evenly spread as the original and is
+/// uniform over the cube on average, so independent shifts give unbiased
+/// estimates whose spread measures the error.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sobol {
+    /// `directions[j][k]`, direction number `k` of coordinate `j`, already
+    /// scaled to the top bits.
+    directions: Vec<[u32; BITS]>,
+    shift: Vec<u32>,
+    state: Vec<u32>,
+    index: u64,
+}
+
+impl Sobol {
+    /// The unshifted sequence in `dimension` coordinates, starting at the
+    /// origin.
+    pub fn new(dimension: usize) -> Result<Self> {
+        validate::check(
+            "dimension",
+            dimension as f64,
+            Domain::Between(1.0, MAX_DIMENSIONS as f64),
+        )?;
+        let mut directions = Vec::with_capacity(dimension);
+        directions.push(core::array::from_fn(|k| 1u32 << (BITS - 1 - k)));
+        let mut polynomials = PrimitivePolynomials::new();
+        for j in 1..dimension {
+            let (degree, polynomial) = polynomials.next_polynomial();
+            directions.push(direction_numbers(j, degree, polynomial));
+        }
+        Ok(Sobol {
+            directions,
+            shift: vec![0; dimension],
+            state: vec![0; dimension],
+            index: 0,
+        })
+    }
+
+    /// The same sequence XORed with random bits from `rng`, one word per
+    /// coordinate.
+    pub fn with_digital_shift<R: RandomSource>(self, rng: &mut R) -> Self {
+        let shift = self
+            .shift
+            .iter()
+            .map(|_| (rng.next_u64() >> 32) as u32)
+            .collect();
+        Sobol { shift, ..self }
+    }
+
+    pub fn dimension(&self) -> usize {
+        self.directions.len()
+    }
+
+    /// Jumps to point `index` of the sequence, `0` being the origin.
+    pub fn seek(&mut self, index: u64) {
+        if index == self.index {
+            return;
+        }
+        let gray = index ^ (index >> 1);
+        for (x, directions) in self.state.iter_mut().zip(&self.directions) {
+            *x = (0..BITS)
+                .filter(|&k| (gray >> k) & 1 == 1)
+                .fold(0, |x, k| x ^ directions[k]);
+        }
+        self.index = index;
+    }
+
+    /// Writes the next point's coordinates into `point`, each in `(0, 1)`:
+    /// the midpoint of its `2^-32` cell, so the origin never reaches an
+    /// inverse CDF.
+    ///
+    /// # Panics
+    ///
+    /// If `point` is shorter than the dimension, or after `2^32` points.
+    pub fn next_point<T: Float>(&mut self, point: &mut [T]) {
+        let scale = 1.0 / (1u64 << BITS) as f64;
+        for ((x, &bits), &shift) in point.iter_mut().zip(&self.state).zip(&self.shift) {
+            *x = cast(((bits ^ shift) as f64 + 0.5) * scale);
+        }
+        assert!(self.index < 1 << BITS, "a Sobol sequence holds 2^32 points");
+        // Gray code: the next point differs in the direction numbered by
+        // the lowest zero bit of the index.
+        let k = self.index.trailing_ones() as usize;
+        if k < BITS {
+            for (x, directions) in self.state.iter_mut().zip(&self.directions) {
+                *x ^= directions[k];
+            }
+        }
+        self.index += 1;
+    }
+}
+
+/// Direction numbers of coordinate `j` from the primitive `polynomial` of
+/// `degree`, with bit `i` its coefficient of `x^i`, and its row of Joe and
+/// Kuo's initial numbers, by Bratley and Fox's (1988) recurrence.
+fn direction_numbers(j: usize, degree: usize, polynomial: u64) -> [u32; BITS] {
+    let mut v = [0u32; BITS];
+    for (k, (direction, &m)) in v.iter_mut().zip(joe_kuo::INITIAL[j - 1]).enumerate() {
+        *direction = m << (BITS - 1 - k);
+    }
+    for k in degree..BITS {
+        let mut value = v[k - degree] ^ (v[k - degree] >> degree);
+        for i in 1..degree {
+            if (polynomial >> (degree - i)) & 1 == 1 {
+                value ^= v[k - i];
+            }
+        }
+        v[k] = value;
+    }
+    v
+}
+
+/// Primitive polynomials over GF(2) in order of degree, skipping `x` itself
+/// and starting from `x + 1`.
+struct PrimitivePolynomials {
+    degree: usize,
+    next: u64,
+}
+
+impl PrimitivePolynomials {
+    fn new() -> Self {
+        PrimitivePolynomials {
+            degree: 1,
+            next: 0b11,
+        }
+    }
+
+    fn next_polynomial(&mut self) -> (usize, u64) {
+        loop {
+            if self.next >= 1 << (self.degree + 1) {
+                self.degree += 1;
+                self.next = (1 << self.degree) | 1;
+            }
+            let candidate = self.next;
+            // Only polynomials with a constant term can be primitive.
+            self.next += 2;
+            if is_primitive(candidate, self.degree) {
+                return (self.degree, candidate);
+            }
+        }
+    }
+}
+
+/// Whether `x` has the full order `2^degree - 1` modulo `polynomial`.
+fn is_primitive(polynomial: u64, degree: usize) -> bool {
+    let order = (1u64 << degree) - 1;
+    let power = |exponent: u64| power_of_x(exponent, polynomial, degree);
+    power(order) == 1 && prime_factors(order).all(|p| power(order / p) != 1)
+}
+
+/// `x^exponent` modulo `polynomial` of `degree`, by repeated squaring.
+fn power_of_x(mut exponent: u64, polynomial: u64, degree: usize) -> u64 {
+    let multiply = |mut a: u64, mut b: u64| {
+        let mut product = 0u64;
+        while b != 0 {
+            if b & 1 == 1 {
+                product ^= a;
+            }
+            b >>= 1;
+            a <<= 1;
+            if (a >> degree) & 1 == 1 {
+                a ^= polynomial;
+            }
+        }
+        product
+    };
+    // Modulo `x + 1` the class of `x` is one.
+    let (mut result, mut base) = (1u64, if degree == 1 { 1 } else { 0b10 });
+    while exponent != 0 {
+        if exponent & 1 == 1 {
+            result = multiply(result, base);
+        }
+        base = multiply(base, base);
+        exponent >>= 1;
+    }
+    result
+}
+
+/// Distinct prime factors of `n`, by trial division.
+fn prime_factors(mut n: u64) -> impl Iterator<Item = u64> {
+    let mut factors = Vec::new();
+    let mut p = 2;
+    while p * p <= n {
+        if n.is_multiple_of(p) {
+            factors.push(p);
+            while n.is_multiple_of(p) {
+                n /= p;
+            }
+        }
+        p += 1;
+    }
+    if n > 1 {
+        factors.push(n);
+    }
+    factors.into_iter()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Each row of the table belongs to the polynomial of its coordinate:
+    /// one odd initial number below `2^k` per degree.
+    #[test]
+    fn joe_kuo_rows_match_the_polynomials() {
+        let mut polynomials = PrimitivePolynomials::new();
+        for initial in joe_kuo::INITIAL {
+            let (degree, _) = polynomials.next_polynomial();
+            assert_eq!(initial.len(), degree);
+            for (k, &m) in initial.iter().enumerate() {
+                assert!(m % 2 == 1 && m < 1 << (k + 1), "{initial:?}");
+            }
+        }
+    }
+
+    /// Joe and Kuo's third coordinate, from `x^2 + x + 1` and `m = 1, 3`,
+    /// runs 0, 1/2, 1/4, 3/4, 5/8, 1/8 in Gray-code order.
+    #[test]
+    fn third_coordinate_follows_the_table() {
+        let mut sobol = Sobol::new(3).unwrap();
+        let mut point = [0.0_f64; 3];
+        let expected = [0.0, 0.5, 0.25, 0.75, 0.625, 0.125];
+        for x in expected {
+            sobol.next_point(&mut point);
+            assert!((point[2] - x).abs() < 1e-9, "{point:?}");
+        }
+    }
+
+    /// Past the table there are no tuned direction numbers to fall back on.
+    #[test]
+    fn dimensions_beyond_the_table_are_rejected() {
+        assert!(Sobol::new(MAX_DIMENSIONS).is_ok());
+        assert!(Sobol::new(MAX_DIMENSIONS + 1).is_err());
+    }
+}
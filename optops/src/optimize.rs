@@ -0,0 +1,188 @@
+//! Small dense solvers shared by the calibrations.
+
+use crate::float::{cast, Float};
+
+/// Nelder-Mead simplex minimisation of `f` from `start`, with the first
+/// simplex stepping `scale` along each axis. Stops once the function values
+/// across the simplex agree to a relative `tolerance` or after
+/// `max_evaluations`, and returns the best point with its value.
+pub(crate) fn nelder_mead<T, F, const N: usize>(
+    mut f: F,
+    start: [T; N],
+    scale: [T; N],
+    tolerance: T,
+    max_evaluations: usize,
+) -> ([T; N], T)
+where
+    T: Float,
+    F: FnMut(&[T; N]) -> T,
+{
+    let (half, two) = (cast::<T>(0.5), cast::<T>(2.0));
+    // Failed evaluations rank last rather than poisoning the ordering.
+    let mut value = |x: &[T; N]| {
+        let fx = f(x);
+        if fx.is_nan() {
+            T::infinity()
+        } else {
+            fx
+        }
+    };
+    let mut simplex: Vec<([T; N], T)> = Vec::with_capacity(N + 1);
+    simplex.push((start, value(&start)));
+    for i in 0..N {
+        let mut vertex = start;
+        vertex[i] = vertex[i] + scale[i];
+        simplex.push((vertex, value(&vertex)));
+    }
+    let mut evaluations = N + 1;
+    let along = |from: &[T; N], to: &[T; N], t: T| {
+        let mut x = *from;
+        for i in 0..N {
+            x[i] = from[i] + (to[i] - from[i]) * t;
+        }
+        x
+    };
+    while evaluations < max_evaluations {
+        simplex.sort_by(|a, b| a.1.partial_cmp(&b.1).expect("NaN values rank as infinite"));
+        let (best, worst) = (simplex[0].1, simplex[N].1);
+        if worst - best <= tolerance * (best.abs() + worst.abs()) + T::min_positive_value() {
+            break;
+        }
+        let mut centroid = [T::zero(); N];
+        for (vertex, _) in &simplex[..N] {
+            for i in 0..N {
+                centroid[i] = centroid[i] + vertex[i] / cast(N as f64);
+            }
+        }
+        let worst_vertex = simplex[N].0;
+        let reflected = along(&worst_vertex, &centroid, two);
+        let at_reflected = value(&reflected);
+        evaluations += 1;
+        if at_reflected < best {
+            let expanded = along(&worst_vertex, &centroid, cast(3.0));
+            let at_expanded = value(&expanded);
+            evaluations += 1;
+            simplex[N] = if at_expanded < at_reflected {
+                (expanded, at_expanded)
+            } else {
+                (reflected, at_reflected)
+            };
+        } else if at_reflected < simplex[N - 1].1 {
+            simplex[N] = (reflected, at_reflected);
+        } else {
+            // Contract towards the better of the worst and reflected points.
+            let (from, at_from) = if at_reflected < worst {
+                (reflected, at_reflected)
+            } else {
+                (worst_vertex, worst)
+            };
+            let contracted = along(&centroid, &from, half);
+            let at_contracted = value(&contracted);
+            evaluations += 1;
+            if at_contracted < at_from {
+                simplex[N] = (contracted, at_contracted);
+            } else {
+                let anchor = simplex[0].0;
+                for vertex in simplex.iter_mut().skip(1) {
+                    let shrunk = along(&anchor, &vertex.0, half);
+                    *vertex = (shrunk, value(&shrunk));
+                }
+                evaluations += N;
+            }
+        }
+    }
+    simplex.sort_by(|a, b| a.1.partial_cmp(&b.1).expect("NaN values rank as infinite"));
+    simplex.swap_remove(0)
+}
+
+/// Solution of the square system `matrix x = rhs`, with `matrix` given row
+/// by row, by Gaussian elimination with partial pivoting; `None` when the
+/// system is singular.
+pub(crate) fn solve_linear<T: Float>(mut matrix: Vec<Vec<T>>, mut rhs: Vec<T>) -> Option<Vec<T>> {
+    let n = rhs.len();
+    let scale = matrix
+        .iter()
+        .flatten()
+        .fold(T::zero(), |largest, &entry| largest.max(entry.abs()));
+    for column in 0..n {
+        let pivot = (column..n).max_by(|&a, &b| {
+            let (a, b) = (matrix[a][column].abs(), matrix[b][column].abs());
+            a.partial_cmp(&b).expect("finite entries")
+        })?;
+        if matrix[pivot][column].abs() <= scale * cast(1e-13) {
+            return None;
+        }
+        matrix.swap(column, pivot);
+        rhs.swap(column, pivot);
+        let pivot_row = matrix[column].clone();
+        for row in column + 1..n {
+            let factor = matrix[row][column] / pivot_row[column];
+            for (entry, &above) in matrix[row][column..].iter_mut().zip(&pivot_row[column..]) {
+                *entry = *entry - factor * above;
+            }
+            rhs[row] = rhs[row] - factor * rhs[column];
+        }
+    }
+    let mut x = vec![T::zero(); n];
+    for row in (0..n).rev() {
+        let tail = (row + 1..n).fold(T::zero(), |sum, k| sum + matrix[row][k] * x[k]);
+        x[row] = (rhs[row] - tail) / matrix[row][row];
+    }
+    Some(x)
+}
+
+/// Eigenvalues and eigenvectors of the symmetric `matrix` by cyclic Jacobi
+/// rotations: `(values, vectors)` with `vectors[k]` the unit eigenvector of
+/// `values[k]`.
+pub(crate) fn symmetric_eigen<T: Float>(mut matrix: Vec<Vec<T>>) -> (Vec<T>, Vec<Vec<T>>) {
+    let n = matrix.len();
+    let mut vectors: Vec<Vec<T>> = (0..n)
+        .map(|i| {
+            (0..n)
+                .map(|j| if i == j { T::one() } else { T::zero() })
+                .collect()
+        })
+        .collect();
+    for _ in 0..100 {
+        let off = (0..n)
+            .flat_map(|i| (i + 1..n).map(move |j| (i, j)))
+            .fold(T::zero(), |sum, (i, j)| sum + matrix[i][j] * matrix[i][j]);
+        if off <= T::epsilon() * T::epsilon() {
+            break;
+        }
+        for p in 0..n {
+            for q in p + 1..n {
+                if matrix[p][q] == T::zero() {
+                    continue;
+                }
+                let theta = (matrix[q][q] - matrix[p][p]) / (cast::<T>(2.0) * matrix[p][q]);
+                let t = theta.signum() / (theta.abs() + (theta * theta + T::one()).sqrt());
+                let c = T::one() / (t * t + T::one()).sqrt();
+                let s = t * c;
+                for row in matrix.iter_mut() {
+                    let (a, b) = (row[p], row[q]);
+                    row[p] = c * a - s * b;
+                    row[q] = s * a + c * b;
+                }
+                let (upper, lower) = matrix.split_at_mut(q);
+                for (x, y) in upper[p].iter_mut().zip(lower[0].iter_mut()) {
+                    let (a, b) = (*x, *y);
+                    *x = c * a - s * b;
+                    *y = s * a + c * b;
+                }
+                for vector in vectors.iter_mut() {
+                    let (a, b) = (vector[p], vector[q]);
+                    vector[p] = c * a - s * b;
+                    vector[q] = s * a + c * b;
+                }
+            }
+        }
+    }
+    let values = (0..n).map(|i| matrix[i][i]).collect();
+    // Rows of the accumulated rotation hold the components; transpose so
+    // each eigenvector is one row.
+    let vectors = (0..n)
+        .map(|k| (0..n).map(|i| vectors[i][k]).collect())
+        .collect();
+    (values, vectors)
+}
@@ -0,0 +1,429 @@
+//! Exercise payoffs `(t, s) -> value` and combinators for building them up.
+
+use crate::float::Float;
+use crate::instrument::OptionType;
+use alloc::boxed::Box;
+use serde::{Deserialize, Serialize};
+
+/// Value received when the option is exercised at time `t` with spot `s`.
+///
+/// Payoffs are `Send + Sync` so trees holding them can be priced on a thread
+/// pool.
+pub trait Payoff<T = f64>: Send + Sync {
+    fn value(&self, t: T, s: T) -> T;
+
+    /// The single strike the payoff is struck at, if it has one; lattices
+    /// centred on the strike (Leisen-Reimer) need it.
+    fn strike(&self) -> Option<T> {
+        None
+    }
+
+    /// Set for plain vanilla calls and puts, whose closed-form value the
+    /// binomial Black-Scholes smoothing uses.
+    fn option_type(&self) -> Option<OptionType> {
+        None
+    }
+
+    /// Mean of the payoff over spots in `[lower, upper]` with `ln s` uniform,
+    /// used to smooth a lattice's terminal layer (cell averaging). Defaults
+    /// to the value at the geometric midpoint, i.e. no smoothing.
+    fn cell_average(&self, t: T, lower: T, upper: T) -> T
+    where
+        T: Float,
+    {
+        self.value(t, (lower * upper).sqrt())
+    }
+}
+
+/// Log-uniform moments of a cell `[lower, upper]` split at `strike`.
+struct Cell<T> {
+    /// Share of the cell above the strike.
+    above: T,
+    /// Mean of `s` times the indicator `s > strike`.
+    asset_above: T,
+    /// Mean of `s` over the whole cell.
+    asset: T,
+}
+
+impl<T: Float> Cell<T> {
+    fn new(lower: T, upper: T, strike: T) -> Self {
+        let width = (upper / lower).ln();
+        if width.is_nan() || width <= T::zero() {
+            let above = if lower > strike { T::one() } else { T::zero() };
+            return Cell {
+                above,
+                asset_above: lower * above,
+                asset: lower,
+            };
+        }
+        let from = lower.max(strike);
+        let (above, asset_above) = if upper > from {
+            ((upper / from).ln() / width, (upper - from) / width)
+        } else {
+            (T::zero(), T::zero())
+        };
+        Cell {
+            above,
+            asset_above,
+            asset: (upper - lower) / width,
+        }
+    }
+}
+
+impl<T, F> Payoff<T> for F
+where
+    F: Fn(T, T) -> T + Send + Sync,
+{
+    fn value(&self, t: T, s: T) -> T {
+        self(t, s)
+    }
+}
+
+impl<T> Payoff<T> for Box<dyn Payoff<T>> {
+    fn value(&self, t: T, s: T) -> T {
+        (**self).value(t, s)
+    }
+
+    fn strike(&self) -> Option<T> {
+        (**self).strike()
+    }
+
+    fn option_type(&self) -> Option<OptionType> {
+        (**self).option_type()
+    }
+
+    fn cell_average(&self, t: T, lower: T, upper: T) -> T
+    where
+        T: Float,
+    {
+        (**self).cell_average(t, lower, upper)
+    }
+}
+
+/// `max(s - strike, 0)`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct VanillaCall<T = f64> {
+    pub strike: T,
+}
+
+impl<T: Float> Payoff<T> for VanillaCall<T> {
+    fn value(&self, _t: T, s: T) -> T {
+        (s - self.strike).max(T::zero())
+    }
+
+    fn strike(&self) -> Option<T> {
+        Some(self.strike)
+    }
+
+    fn option_type(&self) -> Option<OptionType> {
+        Some(OptionType::Call)
+    }
+
+    fn cell_average(&self, _t: T, lower: T, upper: T) -> T {
+        let cell = Cell::new(lower, upper, self.strike);
+        cell.asset_above - self.strike * cell.above
+    }
+}
+
+/// `max(strike - s, 0)`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct VanillaPut<T = f64> {
+    pub strike: T,
+}
+
+impl<T: Float> Payoff<T> for VanillaPut<T> {
+    fn value(&self, _t: T, s: T) -> T {
+        (self.strike - s).max(T::zero())
+    }
+
+    fn strike(&self) -> Option<T> {
+        Some(self.strike)
+    }
+
+    fn option_type(&self) -> Option<OptionType> {
+        Some(OptionType::Put)
+    }
+
+    fn cell_average(&self, _t: T, lower: T, upper: T) -> T {
+        let cell = Cell::new(lower, upper, self.strike);
+        self.strike * (T::one() - cell.above) - (cell.asset - cell.asset_above)
+    }
+}
+
+/// Cash-or-nothing call: pays `cash` when `s > strike`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct DigitalCall<T = f64> {
+    pub strike: T,
+    pub cash: T,
+}
+
+impl<T: Float> Payoff<T> for DigitalCall<T> {
+    fn value(&self, _t: T, s: T) -> T {
+        if s > self.strike {
+            self.cash
+        } else {
+            T::zero()
+        }
+    }
+
+    fn strike(&self) -> Option<T> {
+        Some(self.strike)
+    }
+
+    fn cell_average(&self, _t: T, lower: T, upper: T) -> T {
+        self.cash * Cell::new(lower, upper, self.strike).above
+    }
+}
+
+/// Cash-or-nothing put: pays `cash` when `s < strike`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct DigitalPut<T = f64> {
+    pub strike: T,
+    pub cash: T,
+}
+
+impl<T: Float> Payoff<T> for DigitalPut<T> {
+    fn value(&self, _t: T, s: T) -> T {
+        if s < self.strike {
+            self.cash
+        } else {
+            T::zero()
+        }
+    }
+
+    fn strike(&self) -> Option<T> {
+        Some(self.strike)
+    }
+
+    fn cell_average(&self, _t: T, lower: T, upper: T) -> T {
+        self.cash * (T::one() - Cell::new(lower, upper, self.strike).above)
+    }
+}
+
+/// Asset-or-nothing call: pays `s` when `s > strike`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct AssetOrNothingCall<T = f64> {
+    pub strike: T,
+}
+
+impl<T: Float> Payoff<T> for AssetOrNothingCall<T> {
+    fn value(&self, _t: T, s: T) -> T {
+        if s > self.strike {
+            s
+        } else {
+            T::zero()
+        }
+    }
+
+    fn strike(&self) -> Option<T> {
+        Some(self.strike)
+    }
+
+    fn cell_average(&self, _t: T, lower: T, upper: T) -> T {
+        Cell::new(lower, upper, self.strike).asset_above
+    }
+}
+
+/// Asset-or-nothing put: pays `s` when `s < strike`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct AssetOrNothingPut<T = f64> {
+    pub strike: T,
+}
+
+impl<T: Float> Payoff<T> for AssetOrNothingPut<T> {
+    fn value(&self, _t: T, s: T) -> T {
+        if s < self.strike {
+            s
+        } else {
+            T::zero()
+        }
+    }
+
+    fn strike(&self) -> Option<T> {
+        Some(self.strike)
+    }
+
+    fn cell_average(&self, _t: T, lower: T, upper: T) -> T {
+        let cell = Cell::new(lower, upper, self.strike);
+        cell.asset - cell.asset_above
+    }
+}
+
+/// Sum of two payoffs, e.g. the legs of a straddle.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Sum<A, B>(pub A, pub B);
+
+impl<T, A, B> Payoff<T> for Sum<A, B>
+where
+    T: Float,
+    A: Payoff<T>,
+    B: Payoff<T>,
+{
+    fn value(&self, t: T, s: T) -> T {
+        self.0.value(t, s) + self.1.value(t, s)
+    }
+
+    /// Shared strike of both legs, e.g. for a straddle.
+    fn strike(&self) -> Option<T> {
+        match (self.0.strike(), self.1.strike()) {
+            (Some(a), Some(b)) if a == b => Some(a),
+            _ => None,
+        }
+    }
+
+    fn cell_average(&self, t: T, lower: T, upper: T) -> T {
+        self.0.cell_average(t, lower, upper) + self.1.cell_average(t, lower, upper)
+    }
+}
+
+/// A payoff multiplied by a constant; negative factors give short legs.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Scaled<P, T = f64> {
+    pub payoff: P,
+    pub factor: T,
+}
+
+impl<T: Float, P: Payoff<T>> Payoff<T> for Scaled<P, T> {
+    fn value(&self, t: T, s: T) -> T {
+        self.factor * self.payoff.value(t, s)
+    }
+
+    fn strike(&self) -> Option<T> {
+        self.payoff.strike()
+    }
+
+    fn cell_average(&self, t: T, lower: T, upper: T) -> T {
+        self.factor * self.payoff.cell_average(t, lower, upper)
+    }
+}
+
+/// Combinators available on every sized payoff.
+pub trait PayoffExt<T: Float>: Payoff<T> + Sized {
+    fn plus<Q: Payoff<T>>(self, other: Q) -> Sum<Self, Q> {
+        Sum(self, other)
+    }
+
+    fn minus<Q: Payoff<T>>(self, other: Q) -> Sum<Self, Scaled<Q, T>> {
+        Sum(self, other.scaled(-T::one()))
+    }
+
+    fn scaled(self, factor: T) -> Scaled<Self, T> {
+        Scaled {
+            payoff: self,
+            factor,
+        }
+    }
+}
+
+impl<T: Float, P: Payoff<T>> PayoffExt<T> for P {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CELLS: [(f64, f64); 5] = [
+        (90.0, 110.0),
+        (101.0, 130.0),
+        (70.0, 95.0),
+        (99.5, 100.5),
+        (104.0, 104.0),
+    ];
+
+    /// Log-uniform mean of `payoff` over `[lower, upper]` by the midpoint
+    /// rule in `ln s`.
+    fn numeric_average<P: Payoff<f64>>(payoff: &P, lower: f64, upper: f64) -> f64 {
+        if lower == upper {
+            return payoff.value(1.0, lower);
+        }
+        let n = 200_000;
+        let (from, width) = (lower.ln(), (upper / lower).ln());
+        let sum: f64 = (0..n)
+            .map(|i| {
+                let u = from + width * (i as f64 + 0.5) / n as f64;
+                payoff.value(1.0, u.exp())
+            })
+            .sum();
+        sum / n as f64
+    }
+
+    fn assert_cell_averages<P: Payoff<f64>>(payoff: &P) {
+        for (lower, upper) in CELLS {
+            let exact = payoff.cell_average(1.0, lower, upper);
+            let numeric = numeric_average(payoff, lower, upper);
+            assert!(
+                (exact - numeric).abs() < 1e-3,
+                "[{lower}, {upper}]: {exact} vs {numeric}"
+            );
+        }
+    }
+
+    #[test]
+    fn vanilla_and_binary_values() {
+        let strike = 100.0;
+        assert_eq!(VanillaCall { strike }.value(0.0, 120.0), 20.0);
+        assert_eq!(VanillaCall { strike }.value(0.0, 80.0), 0.0);
+        assert_eq!(VanillaPut { strike }.value(0.0, 80.0), 20.0);
+        assert_eq!(VanillaPut { strike }.value(0.0, 120.0), 0.0);
+        let cash = 3.0;
+        assert_eq!(DigitalCall { strike, cash }.value(0.0, 100.5), 3.0);
+        assert_eq!(DigitalCall { strike, cash }.value(0.0, 100.0), 0.0);
+        assert_eq!(DigitalPut { strike, cash }.value(0.0, 99.5), 3.0);
+        assert_eq!(DigitalPut { strike, cash }.value(0.0, 100.0), 0.0);
+        assert_eq!(AssetOrNothingCall { strike }.value(0.0, 120.0), 120.0);
+        assert_eq!(AssetOrNothingCall { strike }.value(0.0, 80.0), 0.0);
+        assert_eq!(AssetOrNothingPut { strike }.value(0.0, 80.0), 80.0);
+        assert_eq!(AssetOrNothingPut { strike }.value(0.0, 120.0), 0.0);
+        assert_eq!(VanillaCall { strike }.option_type(), Some(OptionType::Call));
+        assert_eq!(VanillaPut { strike }.strike(), Some(100.0));
+    }
+
+    #[test]
+    fn cell_averages_match_a_numeric_average() {
+        let (strike, cash) = (100.0, 2.5);
+        assert_cell_averages(&VanillaCall { strike });
+        assert_cell_averages(&VanillaPut { strike });
+        assert_cell_averages(&DigitalCall { strike, cash });
+        assert_cell_averages(&DigitalPut { strike, cash });
+        assert_cell_averages(&AssetOrNothingCall { strike });
+        assert_cell_averages(&AssetOrNothingPut { strike });
+        assert_cell_averages(&VanillaCall { strike }.plus(VanillaPut { strike: 95.0 }));
+        assert_cell_averages(&VanillaPut { strike }.scaled(-1.5));
+    }
+
+    #[test]
+    fn combinators_add_and_scale_legs() {
+        let straddle = VanillaCall { strike: 100.0 }.plus(VanillaPut { strike: 100.0 });
+        assert_eq!(straddle.value(0.0, 90.0), 10.0);
+        assert_eq!(straddle.value(0.0, 115.0), 15.0);
+        assert_eq!(straddle.strike(), Some(100.0));
+
+        let spread = VanillaCall { strike: 90.0 }.minus(VanillaCall { strike: 110.0 });
+        assert_eq!(spread.value(0.0, 80.0), 0.0);
+        assert_eq!(spread.value(0.0, 100.0), 10.0);
+        assert_eq!(spread.value(0.0, 130.0), 20.0);
+        assert_eq!(spread.strike(), None);
+
+        let scaled = DigitalCall {
+            strike: 100.0,
+            cash: 1.0,
+        }
+        .scaled(4.0);
+        assert_eq!(scaled.value(0.0, 101.0), 4.0);
+        assert_eq!(scaled.strike(), Some(100.0));
+        let (lower, upper) = (95.0, 105.0);
+        let legs = VanillaCall { strike: 100.0 }.cell_average(0.0, lower, upper)
+            + VanillaPut { strike: 100.0 }.cell_average(0.0, lower, upper);
+        assert_eq!(straddle.cell_average(0.0, lower, upper), legs);
+    }
+
+    #[test]
+    fn closures_are_payoffs_without_smoothing() {
+        let square = |_: f64, s: f64| s * s;
+        assert_eq!(square.value(0.0, 3.0), 9.0);
+        assert_eq!(square.strike(), None);
+        assert_eq!(square.cell_average(0.0, 4.0, 9.0), 36.0);
+        let boxed: Box<dyn Payoff<f64>> = Box::new(VanillaCall { strike: 100.0 });
+        assert_eq!(boxed.value(0.0, 105.0), 5.0);
+        assert_eq!(boxed.option_type(), Some(OptionType::Call));
+    }
+}
@@ -0,0 +1,556 @@
+//! Chart generation for exercise boundaries and value functions.
+
+use crate::barrier::Barrier;
+use crate::core::norm_cdf;
+use crate::error::{OptOpsError, Result};
+use crate::market::Market;
+use crate::mc::Paths;
+use crate::surface::{StrikeAxis, VolSurface};
+use crate::tree::ValueFunction;
+use plotters::prelude::*;
+use std::path::Path;
+
+impl<E: std::error::Error + Send + Sync> From<DrawingAreaErrorKind<E>> for OptOpsError {
+    fn from(err: DrawingAreaErrorKind<E>) -> Self {
+        OptOpsError::Plotting(err.to_string())
+    }
+}
+
+// Function to plot exercise boundary chart
+pub fn plot_exercise_boundary<P: AsRef<Path>>(
+    ex_boundary: &[(f64, f64)],
+    title: &str,
+    path: P,
+) -> Result<()> {
+    draw_exercise_boundary(ex_boundary, None, title, path)
+}
+
+/// Exercise boundary chart with a horizontal line at `asymptote`, the
+/// perpetual threshold the boundary tends to far from expiry.
+pub fn plot_exercise_boundary_with_asymptote<P: AsRef<Path>>(
+    ex_boundary: &[(f64, f64)],
+    asymptote: f64,
+    title: &str,
+    path: P,
+) -> Result<()> {
+    draw_exercise_boundary(ex_boundary, Some(asymptote), title, path)
+}
+
+fn draw_exercise_boundary<P: AsRef<Path>>(
+    ex_boundary: &[(f64, f64)],
+    asymptote: Option<f64>,
+    title: &str,
+    path: P,
+) -> Result<()> {
+    let root = BitMapBackend::new(path.as_ref(), (1080, 720)).into_drawing_area();
+    root.fill(&WHITE)?;
+
+    let (x_vals, y_vals): (Vec<f64>, Vec<f64>) = ex_boundary.iter().cloned().unzip();
+    let x_max = x_vals.iter().cloned().fold(f64::NAN, f64::max);
+    let y_max = y_vals
+        .iter()
+        .cloned()
+        .chain(asymptote)
+        .fold(f64::NAN, f64::max);
+    let mut chart = ChartBuilder::on(&root)
+        .caption(title, ("sans-serif", 50).into_font())
+        .margin(10)
+        .x_label_area_size(30)
+        .y_label_area_size(30)
+        .build_cartesian_2d(0f64..x_max, 0f64..y_max)?;
+
+    chart.configure_mesh().draw()?;
+
+    chart.draw_series(LineSeries::new(x_vals.into_iter().zip(y_vals), &RED))?;
+    if let Some(level) = asymptote {
+        chart.draw_series(LineSeries::new([(0.0, level), (x_max, level)], &BLACK))?;
+    }
+
+    root.present()?;
+    Ok(())
+}
+
+/// The lattice's exercise boundary as a line against the one a
+/// Longstaff-Schwartz regression fitted, `LsmExercise::boundary`, as
+/// points, on shared axes.
+pub fn plot_exercise_boundary_comparison<P: AsRef<Path>>(
+    lsm: &[(f64, f64)],
+    lattice: &[(f64, f64)],
+    title: &str,
+    path: P,
+) -> Result<()> {
+    let points = || lsm.iter().chain(lattice);
+    let x_max = points().map(|&(t, _)| t).fold(f64::NAN, f64::max);
+    let (low, high) = points().fold((f64::NAN, f64::NAN), |(lo, hi), &(_, spot)| {
+        (lo.min(spot), hi.max(spot))
+    });
+    let margin = (high - low) * 0.05;
+    let root = BitMapBackend::new(path.as_ref(), (1080, 720)).into_drawing_area();
+    root.fill(&WHITE)?;
+    let mut chart = ChartBuilder::on(&root)
+        .caption(title, ("sans-serif", 50).into_font())
+        .margin(10)
+        .x_label_area_size(40)
+        .y_label_area_size(50)
+        .build_cartesian_2d(0f64..x_max, (low - margin).max(0.0)..high + margin)?;
+    chart
+        .configure_mesh()
+        .x_desc("time")
+        .y_desc("spot")
+        .draw()?;
+
+    chart
+        .draw_series(LineSeries::new(
+            lattice.iter().copied(),
+            BLUE.stroke_width(2),
+        ))?
+        .label("lattice")
+        .legend(|(x, y)| PathElement::new([(x, y), (x + 20, y)], BLUE));
+    chart
+        .draw_series(lsm.iter().map(|&point| Circle::new(point, 3, RED.filled())))?
+        .label("Longstaff-Schwartz")
+        .legend(|(x, y)| Circle::new((x + 10, y), 3, RED.filled()));
+    chart
+        .configure_series_labels()
+        .background_style(WHITE.mix(0.8))
+        .border_style(BLACK)
+        .draw()?;
+
+    root.present()?;
+    Ok(())
+}
+
+// Function to plot option price evolution over time and asset prices
+pub fn plot_option_price_evolution<P: AsRef<Path>>(
+    vf_seq: &ValueFunction,
+    title: &str,
+    path: P,
+) -> Result<()> {
+    let root = BitMapBackend::new(path.as_ref(), (1080, 720)).into_drawing_area();
+    root.fill(&WHITE)?;
+
+    let mut chart = ChartBuilder::on(&root)
+        .caption(title, ("sans-serif", 50).into_font())
+        .margin(10)
+        .x_label_area_size(30)
+        .y_label_area_size(30)
+        .build_cartesian_2d(
+            0f64..((vf_seq.num_steps() + 1) as f64),
+            0f64..vf_seq.as_slice().iter().cloned().fold(f64::NAN, f64::max),
+        )?;
+
+    chart.configure_mesh().draw()?;
+
+    for (i, vf) in vf_seq.levels().enumerate() {
+        let vf_prices: Vec<(f64, f64)> = vf.iter().map(|&v| (i as f64, v)).collect();
+        chart.draw_series(LineSeries::new(vf_prices, &BLUE))?;
+    }
+
+    root.present()?;
+    Ok(())
+}
+
+/// Strikes sampled along each smile curve.
+const SMILE_POINTS: usize = 200;
+
+/// Strike coordinates and expiries of the mesh of a surface chart.
+const MESH: (usize, usize) = (40, 30);
+
+/// Horizontal axis of a smile chart.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SmileAxis {
+    Strike,
+    /// Black forward delta of the call, `N(d1)`, at the smile's own vol.
+    Delta,
+}
+
+/// Implied vol from `surface` against strike or delta at each of
+/// `expiries`, one coloured curve per expiry on shared axes, with the
+/// quotes of any quoted expiry marked. Every curve spans the strikes quoted
+/// anywhere on the surface, against forwards from `market`, leaving out
+/// points the surface's extrapolation rules refuse.
+pub fn plot_smiles<P: AsRef<Path>>(
+    surface: &VolSurface,
+    market: &Market,
+    expiries: &[f64],
+    axis: SmileAxis,
+    title: &str,
+    path: P,
+) -> Result<()> {
+    if expiries.is_empty() {
+        return Err(OptOpsError::MissingParameter("expiries"));
+    }
+    let (low, high) = quoted_coordinates(surface);
+    let position = |expiry: f64, strike: f64, forward: f64, vol: f64| match axis {
+        SmileAxis::Strike => strike,
+        SmileAxis::Delta => {
+            let total = vol * expiry.sqrt();
+            norm_cdf(((forward / strike).ln() + total * total / 2.0) / total)
+        }
+    };
+    let mut curves = Vec::with_capacity(expiries.len());
+    for &expiry in expiries {
+        let forward = market.forward(expiry)?;
+        let to_strike = |coordinate: f64| match surface.axis() {
+            StrikeAxis::Strike => coordinate,
+            StrikeAxis::Moneyness => coordinate * forward,
+        };
+        let (first, last) = (to_strike(low), to_strike(high));
+        let curve: Vec<(f64, f64)> = (0..SMILE_POINTS)
+            .filter_map(|i| {
+                let strike = first + (last - first) * i as f64 / (SMILE_POINTS - 1) as f64;
+                let vol = surface.vol(expiry, strike, forward).ok()?;
+                Some((position(expiry, strike, forward, vol), vol))
+            })
+            .collect();
+        let quotes: Vec<(f64, f64)> = surface
+            .smiles()
+            .filter(|&(time, _)| time == expiry)
+            .flat_map(|(_, smile)| smile)
+            .map(|&(coordinate, vol)| {
+                let strike = to_strike(coordinate);
+                (position(expiry, strike, forward, vol), vol)
+            })
+            .collect();
+        curves.push((expiry, curve, quotes));
+    }
+
+    let points = || {
+        curves
+            .iter()
+            .flat_map(|(_, curve, quotes)| curve.iter().chain(quotes))
+    };
+    let (x_low, x_high) = match axis {
+        SmileAxis::Strike => points().fold((f64::NAN, f64::NAN), |(lo, hi), &(x, _)| {
+            (lo.min(x), hi.max(x))
+        }),
+        SmileAxis::Delta => (0.0, 1.0),
+    };
+    let (y_low, y_high) = points().fold((f64::NAN, f64::NAN), |(lo, hi), &(_, vol)| {
+        (lo.min(vol), hi.max(vol))
+    });
+    if x_low.is_nan() || x_low >= x_high || y_high.is_nan() {
+        return Err(OptOpsError::Plotting("no vols to plot".to_string()));
+    }
+    let margin = ((y_high - y_low) * 0.1).max(y_high * 0.01);
+
+    let root = BitMapBackend::new(path.as_ref(), (1080, 720)).into_drawing_area();
+    root.fill(&WHITE)?;
+    let mut chart = ChartBuilder::on(&root)
+        .caption(title, ("sans-serif", 50).into_font())
+        .margin(10)
+        .x_label_area_size(40)
+        .y_label_area_size(50)
+        .build_cartesian_2d(x_low..x_high, (y_low - margin).max(0.0)..y_high + margin)?;
+    chart
+        .configure_mesh()
+        .x_desc(match axis {
+            SmileAxis::Strike => "strike",
+            SmileAxis::Delta => "call delta",
+        })
+        .y_desc("implied vol")
+        .draw()?;
+
+    for (i, (expiry, curve, quotes)) in curves.into_iter().enumerate() {
+        let color = Palette99::pick(i).to_rgba();
+        chart
+            .draw_series(LineSeries::new(curve, color.stroke_width(2)))?
+            .label(format!("T = {}", expiry))
+            .legend(move |(x, y)| PathElement::new([(x, y), (x + 20, y)], color));
+        chart.draw_series(
+            quotes
+                .into_iter()
+                .map(|point| Circle::new(point, 4, color.filled())),
+        )?;
+    }
+    chart
+        .configure_series_labels()
+        .background_style(WHITE.mix(0.8))
+        .border_style(BLACK)
+        .draw()?;
+
+    root.present()?;
+    Ok(())
+}
+
+/// ATM vol from `surface` against expiry, with forwards from `market`:
+/// the interpolated curve out to the last quoted expiry, its quoted points
+/// marked, and the forward vols between quoted expiries as steps.
+pub fn plot_atm_term_structure<P: AsRef<Path>>(
+    surface: &VolSurface,
+    market: &Market,
+    title: &str,
+    path: P,
+) -> Result<()> {
+    let quoted = surface.atm_term_structure(market)?;
+    let last = quoted[quoted.len() - 1].0;
+    let curve = (1..=SMILE_POINTS)
+        .map(|i| {
+            let expiry = last * i as f64 / SMILE_POINTS as f64;
+            Ok((expiry, surface.atm_vol(expiry, market)?))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let mut steps = Vec::with_capacity(2 * quoted.len());
+    let mut start = 0.0;
+    for &(end, _) in &quoted {
+        let vol = surface.forward_vol(start, end, market)?;
+        steps.extend([(start, vol), (end, vol)]);
+        start = end;
+    }
+
+    let (low, high) = curve
+        .iter()
+        .chain(&steps)
+        .fold((f64::NAN, f64::NAN), |(lo, hi), &(_, vol)| {
+            (lo.min(vol), hi.max(vol))
+        });
+    let margin = ((high - low) * 0.1).max(high * 0.01);
+    let root = BitMapBackend::new(path.as_ref(), (1080, 720)).into_drawing_area();
+    root.fill(&WHITE)?;
+    let mut chart = ChartBuilder::on(&root)
+        .caption(title, ("sans-serif", 50).into_font())
+        .margin(10)
+        .x_label_area_size(40)
+        .y_label_area_size(50)
+        .build_cartesian_2d(0f64..last, (low - margin).max(0.0)..high + margin)?;
+    chart
+        .configure_mesh()
+        .x_desc("expiry")
+        .y_desc("vol")
+        .draw()?;
+
+    chart
+        .draw_series(LineSeries::new(curve, BLUE.stroke_width(2)))?
+        .label("ATM vol")
+        .legend(|(x, y)| PathElement::new([(x, y), (x + 20, y)], BLUE));
+    chart.draw_series(
+        quoted
+            .into_iter()
+            .map(|point| Circle::new(point, 4, BLUE.filled())),
+    )?;
+    chart
+        .draw_series(LineSeries::new(steps, &RED))?
+        .label("forward vol")
+        .legend(|(x, y)| PathElement::new([(x, y), (x + 20, y)], RED));
+    chart
+        .configure_series_labels()
+        .background_style(WHITE.mix(0.8))
+        .border_style(BLACK)
+        .draw()?;
+
+    root.present()?;
+    Ok(())
+}
+
+/// Camera of a 3D chart: `yaw` turns it about the vertical axis and `pitch`
+/// tilts it, both in radians, and `scale` zooms it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SurfaceView {
+    pub yaw: f64,
+    pub pitch: f64,
+    pub scale: f64,
+}
+
+impl Default for SurfaceView {
+    fn default() -> Self {
+        SurfaceView {
+            yaw: 0.5,
+            pitch: 0.3,
+            scale: 0.9,
+        }
+    }
+}
+
+/// `surface` as a mesh of implied vol over strike and expiry seen from
+/// `view`, shaded from blue at the lowest vol to red at the highest. The
+/// mesh spans the quoted expiries and, at each, the strike coordinates
+/// quoted anywhere on the surface against forwards from `market`, so a
+/// moneyness surface fans out in strike with the forward; cells with a
+/// corner the extrapolation rules refuse are left out.
+pub fn plot_vol_surface<P: AsRef<Path>>(
+    surface: &VolSurface,
+    market: &Market,
+    view: SurfaceView,
+    title: &str,
+    path: P,
+) -> Result<()> {
+    let (low, high) = quoted_coordinates(surface);
+    let expiries: Vec<f64> = surface.smiles().map(|(expiry, _)| expiry).collect();
+    let last = expiries[expiries.len() - 1];
+    // A single smile gets a mesh out to its expiry from a tenth of it.
+    let first = if expiries.len() > 1 {
+        expiries[0]
+    } else {
+        last / 10.0
+    };
+    let (columns, rows) = MESH;
+    let mut mesh = Vec::with_capacity(rows);
+    for i in 0..rows {
+        let expiry = first + (last - first) * i as f64 / (rows - 1) as f64;
+        let forward = market.forward(expiry)?;
+        let row: Vec<Option<(f64, f64, f64)>> = (0..columns)
+            .map(|j| {
+                let coordinate = low + (high - low) * j as f64 / (columns - 1) as f64;
+                let strike = match surface.axis() {
+                    StrikeAxis::Strike => coordinate,
+                    StrikeAxis::Moneyness => coordinate * forward,
+                };
+                let vol = surface.vol(expiry, strike, forward).ok()?;
+                Some((strike, vol, expiry))
+            })
+            .collect();
+        mesh.push(row);
+    }
+
+    let points = || mesh.iter().flatten().flatten();
+    let fold = |axis: fn(&(f64, f64, f64)) -> f64| {
+        points().fold((f64::NAN, f64::NAN), |(lo, hi), point| {
+            (lo.min(axis(point)), hi.max(axis(point)))
+        })
+    };
+    let ((x_low, x_high), (y_low, y_high)) = (fold(|p| p.0), fold(|p| p.1));
+    if x_low.is_nan() || x_low >= x_high {
+        return Err(OptOpsError::Plotting("no vols to plot".to_string()));
+    }
+    let spread = (y_high - y_low).max(y_high * 1e-3);
+
+    let root = BitMapBackend::new(path.as_ref(), (1080, 720)).into_drawing_area();
+    root.fill(&WHITE)?;
+    let mut chart = ChartBuilder::on(&root)
+        .caption(title, ("sans-serif", 50).into_font())
+        .margin(20)
+        .build_cartesian_3d(x_low..x_high, y_low..y_low + spread, first..last)?;
+    chart.with_projection(|mut projection| {
+        projection.yaw = view.yaw;
+        projection.pitch = view.pitch;
+        projection.scale = view.scale;
+        projection.into_matrix()
+    });
+    chart
+        .configure_axes()
+        .light_grid_style(BLACK.mix(0.15))
+        .max_light_lines(3)
+        .draw()?;
+
+    let shade = |vol: f64| HSLColor(0.66 * (1.0 - (vol - y_low) / spread), 0.8, 0.5);
+    let cells = mesh.windows(2).flat_map(|pair| {
+        (0..columns - 1).filter_map(move |j| {
+            let corners = [pair[0][j]?, pair[0][j + 1]?, pair[1][j + 1]?, pair[1][j]?];
+            let vol = corners.iter().map(|corner| corner.1).sum::<f64>() / 4.0;
+            Some((corners, vol))
+        })
+    });
+    for (corners, vol) in cells {
+        chart.draw_series([Polygon::new(corners, shade(vol).filled())])?;
+        let outline = corners.into_iter().chain([corners[0]]).collect::<Vec<_>>();
+        chart.draw_series([PathElement::new(outline, BLACK.mix(0.3))])?;
+    }
+
+    root.present()?;
+    Ok(())
+}
+
+/// Smallest and largest strike coordinates quoted on `surface`.
+fn quoted_coordinates(surface: &VolSurface) -> (f64, f64) {
+    surface.smiles().flat_map(|(_, smile)| smile).fold(
+        (f64::INFINITY, f64::NEG_INFINITY),
+        |(low, high), &(coordinate, _)| (low.min(coordinate), high.max(coordinate)),
+    )
+}
+
+/// What to pick out on a chart of simulated paths.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PathMarks<'a> {
+    None,
+    /// A horizontal line at the barrier, with the paths that touch it at a
+    /// grid time drawn in red and their first touch marked.
+    Barrier(Barrier),
+    /// The grid step at which each path is exercised, if it is, indexed
+    /// by path: the path is drawn solid up to it, faded after, and the
+    /// exercise marked.
+    Exercise(&'a [Option<usize>]),
+}
+
+/// `count` of `paths`, spread evenly through them so antithetic partners
+/// and Sobol shifts do not crowd the sample, against time, with `marks`
+/// picked out; paths of several assets are drawn by their first.
+pub fn plot_paths<P: AsRef<Path>>(
+    paths: &Paths,
+    count: usize,
+    marks: PathMarks,
+    title: &str,
+    path: P,
+) -> Result<()> {
+    let count = count.clamp(1, paths.num_paths());
+    let times = paths.times();
+    let samples: Vec<(usize, Vec<(f64, f64)>)> = (0..count)
+        .map(|n| {
+            let i = n * paths.num_paths() / count;
+            let spots = paths.path(i).iter().step_by(paths.assets());
+            (i, times.iter().copied().zip(spots.copied()).collect())
+        })
+        .collect();
+    let (low, high) = samples
+        .iter()
+        .flat_map(|(_, points)| points)
+        .map(|&(_, spot)| spot)
+        .chain(match marks {
+            PathMarks::Barrier(barrier) => Some(barrier.level),
+            _ => None,
+        })
+        .fold((f64::NAN, f64::NAN), |(lo, hi), spot| {
+            (lo.min(spot), hi.max(spot))
+        });
+    let margin = (high - low) * 0.05;
+    let expiry = times[times.len() - 1];
+    let root = BitMapBackend::new(path.as_ref(), (1080, 720)).into_drawing_area();
+    root.fill(&WHITE)?;
+    let mut chart = ChartBuilder::on(&root)
+        .caption(title, ("sans-serif", 50).into_font())
+        .margin(10)
+        .x_label_area_size(40)
+        .y_label_area_size(50)
+        .build_cartesian_2d(0f64..expiry, (low - margin).max(0.0)..high + margin)?;
+    chart
+        .configure_mesh()
+        .x_desc("time")
+        .y_desc("spot")
+        .draw()?;
+
+    let faded = BLUE.mix(0.25);
+    for (i, points) in samples {
+        match marks {
+            PathMarks::None => {
+                chart.draw_series(LineSeries::new(points, faded))?;
+            }
+            PathMarks::Barrier(barrier) => {
+                match points.iter().position(|&(_, spot)| barrier.is_hit(spot)) {
+                    Some(k) => {
+                        chart.draw_series([Circle::new(points[k], 4, RED.filled())])?;
+                        chart.draw_series(LineSeries::new(points, RED.mix(0.6)))?;
+                    }
+                    None => {
+                        chart.draw_series(LineSeries::new(points, faded))?;
+                    }
+                }
+            }
+            PathMarks::Exercise(exercised) => match exercised.get(i).copied().flatten() {
+                Some(k) if k < points.len() => {
+                    chart.draw_series(LineSeries::new(points[..=k].to_vec(), BLUE))?;
+                    chart.draw_series(LineSeries::new(points[k..].to_vec(), faded))?;
+                    chart.draw_series([Circle::new(points[k], 4, RED.filled())])?;
+                }
+                _ => {
+                    chart.draw_series(LineSeries::new(points, faded))?;
+                }
+            },
+        }
+    }
+    if let PathMarks::Barrier(barrier) = marks {
+        let level = barrier.level;
+        chart.draw_series(LineSeries::new([(0.0, level), (expiry, level)], &BLACK))?;
+    }
+
+    root.present()?;
+    Ok(())
+}
@@ -0,0 +1,238 @@
+//! Historical volatility estimated from open, high, low and close prices.
+
+use crate::csv;
+use crate::error::{persistence, Domain, Result};
+use crate::float::{cast, to_f64, Float};
+use crate::units::Vol;
+use crate::validate;
+use serde::{Deserialize, Serialize};
+use std::f64::consts::LN_2;
+use std::fs;
+use std::path::Path;
+
+mod forecast;
+
+pub use forecast::{Ewma, Garch};
+
+/// Trading days in a year, the default annualisation of daily bars.
+const TRADING_DAYS: f64 = 252.0;
+
+/// Open, high, low and close prices of one period.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Bar<T = f64> {
+    pub open: T,
+    pub high: T,
+    pub low: T,
+    pub close: T,
+}
+
+impl<T: Float> Bar<T> {
+    /// Rejected unless every price is positive and the open and close lie
+    /// between the low and the high.
+    pub fn new(open: T, high: T, low: T, close: T) -> Result<Self> {
+        let low = validate::positive("low", low)?;
+        let high = validate::check("high", high, Domain::AtLeast(to_f64(low)))?;
+        let inside = Domain::Between(to_f64(low), to_f64(high));
+        Ok(Bar {
+            open: validate::check("open", open, inside)?,
+            high,
+            low,
+            close: validate::check("close", close, inside)?,
+        })
+    }
+
+    /// Bars from CSV text with a header row naming `open`, `high`, `low`
+    /// and `close` columns in any order and case; other columns, such as
+    /// a date, are ignored, as are blank lines.
+    pub fn parse_csv(csv: &str) -> Result<Vec<Self>> {
+        csv::read(csv, &["open", "high", "low", "close"])?
+            .into_iter()
+            .map(|row| Bar::new(cast(row[0]), cast(row[1]), cast(row[2]), cast(row[3])))
+            .collect()
+    }
+
+    /// Bars from the CSV file at `path`, as `parse_csv` reads them.
+    pub fn load_csv<P: AsRef<Path>>(path: P) -> Result<Vec<Self>> {
+        Bar::parse_csv(&fs::read_to_string(path).map_err(persistence)?)
+    }
+}
+
+/// Log returns from close to close, oldest first, as the forecasting
+/// models take them.
+pub fn log_returns<T: Float>(bars: &[Bar<T>]) -> Vec<T> {
+    bars.windows(2)
+        .map(|pair| (pair[1].close / pair[0].close).ln())
+        .collect()
+}
+
+/// How one window of bars is turned into a variance.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Estimator {
+    /// Sample variance of the log returns from close to close.
+    #[default]
+    CloseToClose,
+    /// Parkinson (1980): the high-low range, blind to drift and to
+    /// overnight gaps.
+    Parkinson,
+    /// Garman and Klass (1980): the range together with the open-to-close
+    /// move, assuming no drift.
+    GarmanKlass,
+    /// Rogers and Satchell (1991): the range relative to the open and
+    /// close, unbiased under any drift.
+    RogersSatchell,
+    /// Yang and Zhang (2000): overnight gaps, open-to-close moves and
+    /// Rogers-Satchell combined with the weights of least variance, robust
+    /// to both drift and gaps.
+    YangZhang,
+}
+
+/// An estimator of annualised realized vol over a series of bars.
+///
+/// Each period is one bar together with the close before it, so a series
+/// of `n` bars holds `n - 1` periods whichever estimator is chosen, and
+/// rolling windows over the same bars line up across estimators.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(bound(
+    serialize = "T: Float + Serialize",
+    deserialize = "T: Float + Deserialize<'de>"
+))]
+pub struct RealizedVol<T = f64> {
+    estimator: Estimator,
+    periods_per_year: T,
+}
+
+impl<T: Float> RealizedVol<T> {
+    /// `estimator` annualised for daily bars, 252 to the year.
+    pub fn new(estimator: Estimator) -> Self {
+        RealizedVol {
+            estimator,
+            periods_per_year: cast(TRADING_DAYS),
+        }
+    }
+
+    /// Bars per year the variance is scaled by: 52 for weekly bars, or one
+    /// for the vol of a single period; must be positive.
+    pub fn with_periods_per_year(self, periods_per_year: T) -> Result<Self> {
+        Ok(RealizedVol {
+            periods_per_year: validate::positive("periods_per_year", periods_per_year)?,
+            ..self
+        })
+    }
+
+    pub fn estimator(&self) -> Estimator {
+        self.estimator
+    }
+
+    /// Vol over every period of `bars`. Needs three bars for the estimators
+    /// that take a sample variance, two for the others.
+    pub fn estimate(&self, bars: &[Bar<T>]) -> Result<Vol<T>> {
+        let least = self.least_periods();
+        validate::check(
+            "bars",
+            bars.len() as f64,
+            Domain::AtLeast(least as f64 + 1.0),
+        )?;
+        let variance = self.variance(bars).max(T::zero());
+        Ok(Vol((variance * self.periods_per_year).sqrt()))
+    }
+
+    /// Vol over each run of `window` consecutive periods, oldest first: the
+    /// `i`-th covers the periods ending at bars `i + 1` to `i + window`.
+    pub fn rolling(&self, bars: &[Bar<T>], window: usize) -> Result<Vec<Vol<T>>> {
+        let least = self.least_periods();
+        validate::check("window", window as f64, Domain::AtLeast(least as f64))?;
+        validate::check(
+            "bars",
+            bars.len() as f64,
+            Domain::AtLeast(window as f64 + 1.0),
+        )?;
+        bars.windows(window + 1)
+            .map(|window| self.estimate(window))
+            .collect()
+    }
+
+    fn least_periods(&self) -> usize {
+        match self.estimator {
+            Estimator::CloseToClose | Estimator::YangZhang => 2,
+            _ => 1,
+        }
+    }
+
+    /// Per-period variance over the periods ending at `bars[1..]`.
+    fn variance(&self, bars: &[Bar<T>]) -> T {
+        let periods = bars.windows(2).map(|pair| (pair[0].close, pair[1]));
+        let range = |bar: &Bar<T>| (bar.high / bar.low).ln();
+        let rogers_satchell = |bar: &Bar<T>| {
+            let (high, low) = ((bar.high / bar.close).ln(), (bar.low / bar.close).ln());
+            high * (bar.high / bar.open).ln() + low * (bar.low / bar.open).ln()
+        };
+        let n = cast::<T>((bars.len() - 1) as f64);
+        match self.estimator {
+            Estimator::CloseToClose => {
+                sample_variance(periods.map(|(previous, bar)| (bar.close / previous).ln()))
+            }
+            Estimator::Parkinson => {
+                let sum = periods.fold(T::zero(), |sum, (_, bar)| sum + range(&bar).powi(2));
+                sum / (n * cast(4.0 * LN_2))
+            }
+            Estimator::GarmanKlass => {
+                let weight = cast::<T>(2.0 * LN_2 - 1.0);
+                let sum = periods.fold(T::zero(), |sum, (_, bar)| {
+                    let body = (bar.close / bar.open).ln();
+                    sum + cast::<T>(0.5) * range(&bar).powi(2) - weight * body * body
+                });
+                sum / n
+            }
+            Estimator::RogersSatchell => {
+                periods.fold(T::zero(), |sum, (_, bar)| sum + rogers_satchell(&bar)) / n
+            }
+            Estimator::YangZhang => {
+                let gaps = periods
+                    .clone()
+                    .map(|(previous, bar)| (bar.open / previous).ln());
+                let bodies = periods.clone().map(|(_, bar)| (bar.close / bar.open).ln());
+                let (overnight, open_to_close) = (sample_variance(gaps), sample_variance(bodies));
+                let drift_free =
+                    periods.fold(T::zero(), |sum, (_, bar)| sum + rogers_satchell(&bar)) / n;
+                let one = T::one();
+                let k = cast::<T>(0.34) / (cast::<T>(1.34) + (n + one) / (n - one));
+                overnight + k * open_to_close + (one - k) * drift_free
+            }
+        }
+    }
+}
+
+/// Variance about the mean, with Bessel's correction.
+fn sample_variance<T: Float>(values: impl Iterator<Item = T> + Clone) -> T {
+    let (count, sum) = values
+        .clone()
+        .fold((0usize, T::zero()), |(count, sum), x| (count + 1, sum + x));
+    let count = cast::<T>(count as f64);
+    let mean = sum / count;
+    let squares = values.fold(T::zero(), |total, x| total + (x - mean) * (x - mean));
+    squares / (count - T::one())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Annualisations are checked when set, and scale the vol by the root
+    /// of the periods.
+    #[test]
+    fn periods_per_year_are_checked_eagerly() {
+        let estimator: RealizedVol = RealizedVol::new(Estimator::Parkinson);
+        for periods in [0.0, -52.0, f64::NAN] {
+            assert!(estimator.with_periods_per_year(periods).is_err());
+        }
+        let bars: Vec<Bar> = [(100.0, 103.0, 99.0, 102.0), (102.0, 104.0, 98.0, 99.0)]
+            .into_iter()
+            .map(|(open, high, low, close)| Bar::new(open, high, low, close).unwrap())
+            .collect();
+        let daily = estimator.estimate(&bars).unwrap().0;
+        let weekly = estimator.with_periods_per_year(52.0).unwrap();
+        let weekly = weekly.estimate(&bars).unwrap().0;
+        assert!((weekly / daily - (52.0f64 / 252.0).sqrt()).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,301 @@
+//! Conditional variance models fitted to a return series, forecasting vol
+//! forward from its last observation.
+
+use crate::curve::VolCurve;
+use crate::error::{Domain, OptOpsError, Result};
+use crate::float::{cast, Float};
+use crate::optimize;
+use crate::units::Vol;
+use crate::validate;
+use serde::{Deserialize, Serialize};
+
+/// Evaluations allowed to each simplex search of the likelihood.
+const MAX_EVALUATIONS: usize = 4000;
+
+/// Exponentially weighted moving average variance, `sigma^2_{t+1} = lambda
+/// sigma^2_t + (1 - lambda) r_t^2`, with returns taken as zero-mean. Its
+/// forecast is flat at every horizon.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(bound(
+    serialize = "T: Float + Serialize",
+    deserialize = "T: Float + Deserialize<'de>"
+))]
+pub struct Ewma<T = f64> {
+    /// Decay of the weights, in `(0, 1)`.
+    pub lambda: T,
+    variance: T,
+    periods_per_year: T,
+}
+
+impl<T: Float> Ewma<T> {
+    /// RiskMetrics' decay for daily returns.
+    pub const RISKMETRICS: f64 = 0.94;
+
+    /// The average run over `returns`, oldest first, from a seed of their
+    /// mean square, for daily returns at 252 to the year.
+    pub fn new(lambda: T, returns: &[T]) -> Result<Self> {
+        let lambda = validate::check("lambda", lambda, Domain::Inside(0.0, 1.0))?;
+        let seed = mean_square(returns)?;
+        let variance = returns.iter().fold(seed, |variance, &r| {
+            lambda * variance + (T::one() - lambda) * r * r
+        });
+        Ok(Ewma {
+            lambda,
+            variance,
+            periods_per_year: cast(super::TRADING_DAYS),
+        })
+    }
+
+    /// RiskMetrics' average, `lambda = 0.94`.
+    pub fn riskmetrics(returns: &[T]) -> Result<Self> {
+        Ewma::new(cast(Self::RISKMETRICS), returns)
+    }
+
+    /// Returns per year the variance is annualised by; must be positive.
+    pub fn with_periods_per_year(self, periods_per_year: T) -> Result<Self> {
+        Ok(Ewma {
+            periods_per_year: validate::positive("periods_per_year", periods_per_year)?,
+            ..self
+        })
+    }
+
+    /// Variance of the next return.
+    pub fn variance(&self) -> T {
+        self.variance
+    }
+
+    /// Annualised vol of the returns to come, the same over every horizon.
+    pub fn forecast(&self) -> Vol<T> {
+        Vol((self.variance * self.periods_per_year).sqrt())
+    }
+
+    /// Forecast vols at each of `expiries`, strictly increasing, as a curve
+    /// for `Market::with_vol_curve`.
+    pub fn term_structure(&self, expiries: &[T]) -> Result<VolCurve<T>> {
+        VolCurve::from_implied(expiries.iter().map(|&t| (t, self.forecast().0)))
+    }
+}
+
+/// GARCH(1,1) variance, `sigma^2_{t+1} = omega + alpha r_t^2 + beta
+/// sigma^2_t`, with returns taken as zero-mean. Forecasts revert from the
+/// last conditional variance to `omega / (1 - alpha - beta)` at the rate
+/// `alpha + beta` per period.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(bound(
+    serialize = "T: Float + Serialize",
+    deserialize = "T: Float + Deserialize<'de>"
+))]
+pub struct Garch<T = f64> {
+    pub omega: T,
+    /// Weight of the latest squared return.
+    pub alpha: T,
+    /// Weight of the latest variance.
+    pub beta: T,
+    variance: T,
+    periods_per_year: T,
+}
+
+impl<T: Float> Garch<T> {
+    /// The recursion run over `returns`, oldest first, from a seed of their
+    /// mean square, for daily returns at 252 to the year. Rejected unless
+    /// `alpha + beta < 1`, so that the variance reverts to a long-run
+    /// level; zero persistence is allowed, and is a constant variance
+    /// `omega`.
+    pub fn new(omega: T, alpha: T, beta: T, returns: &[T]) -> Result<Self> {
+        let omega = validate::positive("omega", omega)?;
+        let alpha = validate::check("alpha", alpha, Domain::NonNegative)?;
+        let beta = validate::check("beta", beta, Domain::NonNegative)?;
+        validate::check("alpha + beta", alpha + beta, Domain::HalfOpen(0.0, 1.0))?;
+        let seed = mean_square(returns)?;
+        let (variance, _) = filter(omega, alpha, beta, seed, returns);
+        Ok(Garch {
+            omega,
+            alpha,
+            beta,
+            variance,
+            periods_per_year: cast(super::TRADING_DAYS),
+        })
+    }
+
+    /// Gaussian maximum-likelihood fit to `returns`, oldest first. The
+    /// simplex search runs on `ln omega` and logits of the persistence
+    /// `alpha + beta` and of alpha's share of it, so every trial point is
+    /// stationary; it restarts once from its best point. Needs at least
+    /// ten returns.
+    pub fn fit(returns: &[T]) -> Result<Self> {
+        validate::check("returns", returns.len() as f64, Domain::AtLeast(10.0))?;
+        let seed = mean_square(returns)?;
+        validate::positive("mean square return", seed)?;
+        let logistic = |x: T| T::one() / (T::one() + (-x).exp());
+        let decode = |x: &[T; 3]| {
+            let (persistence, share) = (logistic(x[1]), logistic(x[2]));
+            (
+                x[0].exp(),
+                persistence * share,
+                persistence * (T::one() - share),
+            )
+        };
+        let negative_log_likelihood = |x: &[T; 3]| {
+            let (omega, alpha, beta) = decode(x);
+            filter(omega, alpha, beta, seed, returns).1
+        };
+        let logit = |p: f64| cast::<T>((p / (1.0 - p)).ln());
+        let (persistence, share) = (0.95, 0.1 / 0.95);
+        let mut start = [
+            (seed * cast(1.0 - persistence)).ln(),
+            logit(persistence),
+            logit(share),
+        ];
+        let scale = [cast(0.5), cast(1.0), cast(1.0)];
+        let tolerance = cast::<T>(1e-12);
+        for _ in 0..2 {
+            (start, _) = optimize::nelder_mead(
+                negative_log_likelihood,
+                start,
+                scale,
+                tolerance,
+                MAX_EVALUATIONS,
+            );
+        }
+        let (omega, alpha, beta) = decode(&start);
+        Garch::new(omega, alpha, beta, returns)
+    }
+
+    /// Returns per year the variance is annualised by; must be positive.
+    pub fn with_periods_per_year(self, periods_per_year: T) -> Result<Self> {
+        Ok(Garch {
+            periods_per_year: validate::positive("periods_per_year", periods_per_year)?,
+            ..self
+        })
+    }
+
+    /// Variance of the next return.
+    pub fn variance(&self) -> T {
+        self.variance
+    }
+
+    /// `omega / (1 - alpha - beta)`, the per-period variance forecasts
+    /// revert to.
+    pub fn long_run_variance(&self) -> T {
+        self.omega / (T::one() - self.alpha - self.beta)
+    }
+
+    /// Annualised vol over the next `horizon` years: the root of the mean
+    /// expected variance of the `h = horizon * periods_per_year` returns to
+    /// come, `V + (sigma^2 - V) (1 - phi^h) / ((1 - phi) h)` with `V` the
+    /// long-run variance and `phi = alpha + beta`.
+    pub fn forecast(&self, horizon: T) -> Vol<T> {
+        let periods = horizon * self.periods_per_year;
+        let (long_run, persistence) = (self.long_run_variance(), self.alpha + self.beta);
+        let decay = if periods > T::zero() {
+            (T::one() - persistence.powf(periods)) / ((T::one() - persistence) * periods)
+        } else {
+            T::one()
+        };
+        let mean = long_run + (self.variance - long_run) * decay;
+        Vol((mean * self.periods_per_year).sqrt())
+    }
+
+    /// Forecast vols at each of `expiries`, strictly increasing, as a curve
+    /// for `Market::with_vol_curve`.
+    pub fn term_structure(&self, expiries: &[T]) -> Result<VolCurve<T>> {
+        VolCurve::from_implied(expiries.iter().map(|&t| (t, self.forecast(t).0)))
+    }
+}
+
+/// Mean of the squared `returns`, after checking they are finite.
+fn mean_square<T: Float>(returns: &[T]) -> Result<T> {
+    if returns.is_empty() {
+        return Err(OptOpsError::MissingParameter("returns"));
+    }
+    let sum = returns.iter().try_fold(T::zero(), |sum, &r| {
+        validate::finite("return", r).map(|r| sum + r * r)
+    })?;
+    Ok(sum / cast(returns.len() as f64))
+}
+
+/// Conditional variance after the last of `returns` from `seed` before the
+/// first, with the Gaussian negative log-likelihood of the returns up to
+/// constants.
+fn filter<T: Float>(omega: T, alpha: T, beta: T, seed: T, returns: &[T]) -> (T, T) {
+    let half = cast::<T>(0.5);
+    returns
+        .iter()
+        .fold((seed, T::zero()), |(variance, nll), &r| {
+            let nll = nll + half * (variance.ln() + r * r / variance);
+            (omega + alpha * r * r + beta * variance, nll)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mc::{Philox, RandomSource};
+
+    /// Simulated GARCH(1, 1) returns from known parameters.
+    fn simulate(omega: f64, alpha: f64, beta: f64, count: usize) -> Vec<f64> {
+        let mut rng = Philox::new(42);
+        let mut variance = omega / (1.0 - alpha - beta);
+        (0..count)
+            .map(|_| {
+                let normal: f64 = rng.normal();
+                let value = variance.sqrt() * normal;
+                variance = omega + alpha * value * value + beta * variance;
+                value
+            })
+            .collect()
+    }
+
+    #[test]
+    fn fit_recovers_the_simulated_parameters() {
+        let (omega, alpha, beta) = (4e-6, 0.08, 0.9);
+        let returns = simulate(omega, alpha, beta, 8000);
+        let fitted = Garch::fit(&returns).unwrap();
+        assert!((fitted.alpha - alpha).abs() < 0.03, "{fitted:?}");
+        assert!((fitted.beta - beta).abs() < 0.04, "{fitted:?}");
+        let long_run = omega / (1.0 - alpha - beta);
+        let ratio = fitted.long_run_variance() / long_run;
+        assert!((0.7..1.4).contains(&ratio), "{fitted:?}: {ratio}");
+    }
+
+    #[test]
+    fn ewma_tracks_the_latest_squares() {
+        let returns = [0.01, -0.02, 0.015, 0.0];
+        let ewma = Ewma::new(0.9, &returns).unwrap();
+        let mut variance = returns.iter().map(|r| r * r).sum::<f64>() / returns.len() as f64;
+        for r in returns {
+            variance = 0.9 * variance + 0.1 * r * r;
+        }
+        assert!(
+            (ewma.variance() - variance).abs() < 1e-15,
+            "{}",
+            ewma.variance()
+        );
+    }
+
+    /// Decays at either end of (0, 1) are rejected as outside the open
+    /// interval, unit persistence as outside [0, 1), and annualisations
+    /// that are not positive as well. Zero persistence is a constant
+    /// variance.
+    #[test]
+    fn parameters_are_checked_against_their_intervals() {
+        let returns = simulate(4e-6, 0.08, 0.9, 50);
+        let outside = |err: OptOpsError, expected: Domain| matches!(err, OptOpsError::InvalidParameter { domain, .. } if domain == expected);
+        for lambda in [0.0, 1.0] {
+            let err = Ewma::new(lambda, &returns).unwrap_err();
+            assert!(outside(err, Domain::Inside(0.0, 1.0)));
+        }
+        let err = Garch::new(4e-6, 0.1, 0.9, &returns).unwrap_err();
+        assert!(outside(err, Domain::HalfOpen(0.0, 1.0)));
+        let constant = Garch::new(4e-6, 0.0, 0.0, &returns).unwrap();
+        assert_eq!(
+            (constant.variance(), constant.long_run_variance()),
+            (4e-6, 4e-6)
+        );
+        let ewma = Ewma::riskmetrics(&returns).unwrap();
+        assert!(ewma.with_periods_per_year(0.0).is_err());
+        let garch = Garch::new(4e-6, 0.08, 0.9, &returns).unwrap();
+        assert!(garch.with_periods_per_year(-52.0).is_err());
+        assert!(garch.with_periods_per_year(52.0).is_ok());
+    }
+}
@@ -0,0 +1,179 @@
+//! Audit trail of pricing runs: record inputs and outputs, replay later.
+
+use crate::engine::{EngineConfig, PricingEngine, PricingResult};
+use crate::error::{persistence, OptOpsError, Result};
+use crate::instrument::OptionSpec;
+use crate::market::Market;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+/// Everything needed to reproduce one pricing run.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RunRecord {
+    pub crate_version: String,
+    pub instrument: OptionSpec,
+    pub market: Market,
+    pub engine: EngineConfig,
+    pub result: PricingResult,
+    /// FNV-1a hash of the canonical JSON of instrument, market and engine.
+    pub input_hash: u64,
+}
+
+impl RunRecord {
+    /// Prices `instrument` with `engine` and records the run.
+    pub fn capture(instrument: OptionSpec, market: Market, engine: EngineConfig) -> Result<Self> {
+        let result = engine.price(&instrument, &market)?;
+        let input_hash = input_hash(&instrument, &market, &engine)?;
+        Ok(RunRecord {
+            crate_version: env!("CARGO_PKG_VERSION").to_string(),
+            instrument,
+            market,
+            engine,
+            result,
+            input_hash,
+        })
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string_pretty(self).map_err(persistence)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self> {
+        serde_json::from_str(json).map_err(persistence)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        fs::write(path, self.to_json()?).map_err(persistence)
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        RunRecord::from_json(&fs::read_to_string(path).map_err(persistence)?)
+    }
+
+    /// Recomputes the input hash and compares it with the stored one.
+    pub fn verify_hash(&self) -> Result<()> {
+        let computed = input_hash(&self.instrument, &self.market, &self.engine)?;
+        if computed != self.input_hash {
+            return Err(OptOpsError::HashMismatch {
+                recorded: self.input_hash,
+                computed,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Re-executes `record` and checks the price agrees within `tolerance`,
+/// returning the fresh result.
+pub fn replay(record: &RunRecord, tolerance: f64) -> Result<PricingResult> {
+    record.verify_hash()?;
+    let result = record.engine.price(&record.instrument, &record.market)?;
+    // Written so that a NaN on either side counts as a mismatch.
+    let matches = (result.price - record.result.price).abs() <= tolerance;
+    if !matches {
+        return Err(OptOpsError::ReplayMismatch {
+            recorded: record.result.price,
+            replayed: result.price,
+            tolerance,
+        });
+    }
+    Ok(result)
+}
+
+fn input_hash(instrument: &OptionSpec, market: &Market, engine: &EngineConfig) -> Result<u64> {
+    let canonical = serde_json::to_vec(&(instrument, market, engine)).map_err(persistence)?;
+    Ok(fnv1a(&canonical))
+}
+
+/// 64-bit FNV-1a; unlike `DefaultHasher` it is stable across Rust releases.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &b| {
+        (hash ^ b as u64).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::instrument::OptionType;
+    use crate::mc::{Philox, RandomSource};
+    use crate::pde::FiniteDifference;
+    use crate::tree::BinomialEngine;
+    use crate::units::{Rate, Spot, Strike, Vol};
+
+    /// Records of random American and European puts and calls under the
+    /// closed-form, lattice, approximation and finite-difference engines.
+    fn records(count: usize) -> Vec<RunRecord> {
+        let mut rng = Philox::new(15);
+        let engines = [
+            EngineConfig::BlackScholes,
+            EngineConfig::Binomial(BinomialEngine::leisen_reimer(101)),
+            EngineConfig::BaroneAdesiWhaley,
+            EngineConfig::FiniteDifference(FiniteDifference::default()),
+        ];
+        (0..count)
+            .map(|i| {
+                let option_type = if i % 2 == 0 {
+                    OptionType::Call
+                } else {
+                    OptionType::Put
+                };
+                let strike = Strike(60.0 + 80.0 * rng.uniform());
+                let expiry = 0.1 + 2.0 * rng.uniform();
+                let engine = engines[i % engines.len()];
+                let instrument = match engine {
+                    EngineConfig::BlackScholes => OptionSpec::european(option_type, strike, expiry),
+                    _ => OptionSpec::american(option_type, strike, expiry),
+                };
+                let market = Market::new(
+                    Spot(100.0),
+                    Rate(0.1 * rng.uniform()),
+                    Vol(0.05 + 0.5 * rng.uniform()),
+                )
+                .with_dividend_yield(Rate(0.05 * rng.uniform()));
+                RunRecord::capture(instrument, market, engine).unwrap()
+            })
+            .collect()
+    }
+
+    /// Every float survives the JSON round trip bit for bit.
+    #[test]
+    fn records_round_trip_through_json_exactly() {
+        for record in records(40) {
+            let reloaded = RunRecord::from_json(&record.to_json().unwrap()).unwrap();
+            assert_eq!(reloaded, record);
+        }
+    }
+
+    /// A reloaded record replays to its own price with no tolerance.
+    #[test]
+    fn reloaded_records_replay_exactly() {
+        for record in records(40) {
+            let reloaded = RunRecord::from_json(&record.to_json().unwrap()).unwrap();
+            let result = replay(&reloaded, 0.0).unwrap();
+            assert_eq!(
+                result.early_exercise_premium,
+                record.result.early_exercise_premium
+            );
+        }
+    }
+
+    /// Edited inputs fail the hash check, and an edited price the replay.
+    #[test]
+    fn tampered_records_are_caught() {
+        let record = records(1).remove(0);
+        let mut market = record.clone();
+        market.market.spot += 1.0;
+        assert!(matches!(
+            replay(&market, 1e-9),
+            Err(OptOpsError::HashMismatch { .. })
+        ));
+        let mut price = record;
+        price.result.price += 1e-6;
+        assert!(matches!(
+            replay(&price, 1e-9),
+            Err(OptOpsError::ReplayMismatch { .. })
+        ));
+    }
+}
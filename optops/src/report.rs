@@ -0,0 +1,211 @@
+//! Exact decimal view of pricing results for reconciliation.
+//!
+//! Engines work in binary floating point; this layer rounds their output once,
+//! to a tick size or a currency's minor units, so reported figures match the
+//! books to the last digit.
+
+use crate::engine::{Greeks, PricingResult};
+use crate::error::{Domain, OptOpsError, Result};
+use crate::validate;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+pub use rust_decimal::RoundingStrategy;
+
+/// Rounds values to a whole multiple of `increment`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Rounding {
+    pub increment: Decimal,
+    #[serde(with = "strategy")]
+    pub strategy: RoundingStrategy,
+}
+
+impl Rounding {
+    /// Rounds to a quoted tick size, e.g. `0.05`, half away from zero.
+    pub fn tick(size: Decimal) -> Self {
+        Rounding {
+            increment: size,
+            strategy: RoundingStrategy::MidpointAwayFromZero,
+        }
+    }
+
+    /// Rounds to `digits` decimal places, e.g. 2 for cents or 0 for yen;
+    /// rejected beyond the 28 places a `Decimal` holds.
+    pub fn minor_units(digits: u32) -> Result<Self> {
+        let most = f64::from(Decimal::MAX_SCALE);
+        validate::check("digits", f64::from(digits), Domain::Between(0.0, most))?;
+        Ok(Rounding::tick(Decimal::new(1, digits)))
+    }
+
+    pub fn with_strategy(mut self, strategy: RoundingStrategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    /// Rounds `value`, rejecting NaN, infinities and a non-positive increment.
+    pub fn round(&self, value: f64) -> Result<Decimal> {
+        self.round_named("value", value)
+    }
+
+    fn round_named(&self, name: &'static str, value: f64) -> Result<Decimal> {
+        if self.increment <= Decimal::ZERO {
+            return Err(OptOpsError::InvalidParameter {
+                name: "increment",
+                value: self.increment.try_into().unwrap_or(f64::NAN),
+                domain: Domain::Positive,
+            });
+        }
+        let exact = Decimal::try_from(value).map_err(|_| OptOpsError::InvalidParameter {
+            name,
+            value,
+            domain: Domain::Finite,
+        })?;
+        let units = (exact / self.increment).round_dp_with_strategy(0, self.strategy);
+        Ok(units * self.increment)
+    }
+}
+
+impl Default for Rounding {
+    /// Cents, half away from zero.
+    fn default() -> Self {
+        Rounding::tick(Decimal::new(1, 2))
+    }
+}
+
+/// `PricingResult` figures as rounded decimals.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DecimalResult {
+    pub price: Decimal,
+    pub early_exercise_premium: Option<Decimal>,
+    pub engine: String,
+    pub num_steps: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub greeks: Option<DecimalGreeks>,
+    pub rounding: Rounding,
+}
+
+/// `Greeks` rounded like the price they came with.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct DecimalGreeks {
+    pub delta: Decimal,
+    pub gamma: Decimal,
+    pub theta: Decimal,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vega: Option<Decimal>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rho: Option<Decimal>,
+}
+
+impl DecimalGreeks {
+    fn new(greeks: &Greeks, rounding: &Rounding) -> Result<Self> {
+        let optional = |name, value: Option<f64>| {
+            value
+                .map(|value| rounding.round_named(name, value))
+                .transpose()
+        };
+        Ok(DecimalGreeks {
+            delta: rounding.round_named("delta", greeks.delta)?,
+            gamma: rounding.round_named("gamma", greeks.gamma)?,
+            theta: rounding.round_named("theta", greeks.theta)?,
+            vega: optional("vega", greeks.vega)?,
+            rho: optional("rho", greeks.rho)?,
+        })
+    }
+}
+
+impl PricingResult {
+    /// The price, premium and any greeks, each rounded by `rounding`.
+    pub fn to_decimal(&self, rounding: Rounding) -> Result<DecimalResult> {
+        Ok(DecimalResult {
+            price: rounding.round_named("price", self.price)?,
+            early_exercise_premium: self
+                .early_exercise_premium
+                .map(|premium| rounding.round_named("early_exercise_premium", premium))
+                .transpose()?,
+            engine: self.engine.clone(),
+            num_steps: self.num_steps,
+            greeks: self
+                .greeks
+                .map(|greeks| DecimalGreeks::new(&greeks, &rounding))
+                .transpose()?,
+            rounding,
+        })
+    }
+}
+
+/// `RoundingStrategy` has no serde support of its own.
+mod strategy {
+    use super::RoundingStrategy;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    const NAMES: [(&str, RoundingStrategy); 7] = [
+        (
+            "midpoint_nearest_even",
+            RoundingStrategy::MidpointNearestEven,
+        ),
+        (
+            "midpoint_away_from_zero",
+            RoundingStrategy::MidpointAwayFromZero,
+        ),
+        ("midpoint_toward_zero", RoundingStrategy::MidpointTowardZero),
+        ("to_zero", RoundingStrategy::ToZero),
+        ("away_from_zero", RoundingStrategy::AwayFromZero),
+        ("to_negative_infinity", RoundingStrategy::ToNegativeInfinity),
+        ("to_positive_infinity", RoundingStrategy::ToPositiveInfinity),
+    ];
+
+    pub fn serialize<S: Serializer>(
+        value: &RoundingStrategy,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let name = NAMES
+            .iter()
+            .find(|(_, strategy)| strategy == value)
+            .map_or("midpoint_nearest_even", |(name, _)| *name);
+        serializer.serialize_str(name)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<RoundingStrategy, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        NAMES
+            .iter()
+            .find(|(candidate, _)| *candidate == name)
+            .map(|(_, strategy)| *strategy)
+            .ok_or_else(|| serde::de::Error::custom(format!("unknown rounding strategy `{name}`")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn rounds_the_price_and_every_greek() {
+        let mut result = PricingResult::new("test", 10.4506, Duration::ZERO);
+        result.greeks = Some(Greeks {
+            delta: 0.63683,
+            gamma: 0.01876,
+            theta: -6.41403,
+            vega: Some(37.524),
+            rho: None,
+        });
+        let decimal = result.to_decimal(Rounding::default()).unwrap();
+        assert_eq!(decimal.price, Decimal::new(1045, 2));
+        let greeks = decimal.greeks.unwrap();
+        assert_eq!(greeks.delta, Decimal::new(64, 2));
+        assert_eq!(greeks.gamma, Decimal::new(2, 2));
+        assert_eq!(greeks.theta, Decimal::new(-641, 2));
+        assert_eq!(greeks.vega, Some(Decimal::new(3752, 2)));
+        assert_eq!(greeks.rho, None);
+    }
+
+    #[test]
+    fn minor_units_beyond_a_decimal_are_rejected() {
+        assert!(Rounding::minor_units(28).is_ok());
+        assert!(Rounding::minor_units(29).is_err());
+        assert_eq!(Rounding::minor_units(2).unwrap(), Rounding::default());
+    }
+}
@@ -0,0 +1,294 @@
+//! Implied vol surfaces over expiry and strike, interpolated from quotes.
+
+use crate::error::{Domain, OptOpsError, Result};
+use crate::float::{cast, to_f64, Float};
+use crate::validate;
+use serde::{Deserialize, Serialize};
+
+mod arbitrage;
+mod io;
+mod local_vol;
+mod sabr;
+mod svi;
+mod term;
+
+pub use arbitrage::Arbitrage;
+pub use local_vol::LocalVol;
+pub use sabr::Sabr;
+pub use svi::Svi;
+
+/// What the second coordinate of a surface's quotes measures.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StrikeAxis {
+    /// The strike itself.
+    #[default]
+    Strike,
+    /// Strike over the forward to the quote's expiry, so the smile moves
+    /// with the spot.
+    Moneyness,
+}
+
+/// How each expiry's smile is filled in between its quoted strikes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SmileInterpolation {
+    /// Straight lines in vol between neighbouring strikes.
+    #[default]
+    Linear,
+    /// The natural cubic spline through the vols, smooth enough for the
+    /// strike derivatives density and local-vol models need.
+    CubicSpline,
+}
+
+/// What a surface answers beyond its outermost strikes or last expiry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Extrapolation {
+    /// The vol at the nearest edge.
+    #[default]
+    Flat,
+    /// The edge's slope carried on: in vol along strikes, in total variance
+    /// along expiries.
+    Linear,
+    /// An out-of-range error.
+    Error,
+}
+
+/// Black-Scholes implied vols by expiry and strike. Each quoted expiry
+/// keeps its own smile; between expiries the total variance `σ²T` is
+/// interpolated linearly at a fixed strike coordinate, and before the first
+/// it falls linearly to zero at `T = 0`, which keeps that vol flat.
+///
+/// Set on a market with `Market::with_vol_surface`, the Black-Scholes,
+/// barrier, chooser, geometric Asian and American pricers and the tree read
+/// their vol off the surface at their own strike and expiry.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(bound(
+    serialize = "T: Float + Serialize",
+    deserialize = "T: Float + Deserialize<'de>"
+))]
+pub struct VolSurface<T = f64> {
+    axis: StrikeAxis,
+    /// `(expiry, [(strike coordinate, vol)])` in increasing order of both.
+    smiles: Vec<(T, Vec<(T, T)>)>,
+    #[serde(default)]
+    interpolation: SmileInterpolation,
+    #[serde(default)]
+    strike_extrapolation: Extrapolation,
+    #[serde(default)]
+    time_extrapolation: Extrapolation,
+}
+
+impl<T: Float> VolSurface<T> {
+    /// Surface through `(expiry, strike or moneyness, vol)` quotes in any
+    /// order; quotes sharing an expiry form its smile. Two quotes at the
+    /// same expiry and strike are rejected.
+    pub fn new(axis: StrikeAxis, quotes: impl IntoIterator<Item = (T, T, T)>) -> Result<Self> {
+        let mut checked = quotes
+            .into_iter()
+            .map(|(expiry, coordinate, vol)| {
+                Ok((
+                    validate::positive("expiry", expiry)?,
+                    validate::positive("strike", coordinate)?,
+                    validate::positive("vol", vol)?,
+                ))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        if checked.is_empty() {
+            return Err(OptOpsError::MissingParameter("quotes"));
+        }
+        checked.sort_by(|a, b| {
+            (a.0, a.1)
+                .partial_cmp(&(b.0, b.1))
+                .expect("validated quotes are finite")
+        });
+        let mut smiles: Vec<(T, Vec<(T, T)>)> = Vec::new();
+        for (expiry, coordinate, vol) in checked {
+            match smiles.last_mut() {
+                Some((last, smile)) if *last == expiry => {
+                    let previous = smile[smile.len() - 1].0;
+                    validate::check("strike", coordinate, Domain::Above(to_f64(previous)))?;
+                    smile.push((coordinate, vol));
+                }
+                _ => smiles.push((expiry, vec![(coordinate, vol)])),
+            }
+        }
+        Ok(VolSurface {
+            axis,
+            smiles,
+            interpolation: SmileInterpolation::default(),
+            strike_extrapolation: Extrapolation::default(),
+            time_extrapolation: Extrapolation::default(),
+        })
+    }
+
+    pub fn with_interpolation(self, interpolation: SmileInterpolation) -> Self {
+        VolSurface {
+            interpolation,
+            ..self
+        }
+    }
+
+    pub fn with_strike_extrapolation(self, strike_extrapolation: Extrapolation) -> Self {
+        VolSurface {
+            strike_extrapolation,
+            ..self
+        }
+    }
+
+    /// How expiries after the last are answered; earlier ones always
+    /// interpolate towards zero variance.
+    pub fn with_time_extrapolation(self, time_extrapolation: Extrapolation) -> Self {
+        VolSurface {
+            time_extrapolation,
+            ..self
+        }
+    }
+
+    pub fn axis(&self) -> StrikeAxis {
+        self.axis
+    }
+
+    pub fn interpolation(&self) -> SmileInterpolation {
+        self.interpolation
+    }
+
+    /// Each quoted expiry with its `(strike coordinate, vol)` quotes.
+    pub fn smiles(&self) -> impl Iterator<Item = (T, &[(T, T)])> {
+        self.smiles
+            .iter()
+            .map(|(expiry, smile)| (*expiry, smile.as_slice()))
+    }
+
+    /// Implied vol for `strike` at `expiry`, where `forward` is the forward
+    /// to `expiry` that moneyness is measured against.
+    pub fn vol(&self, expiry: T, strike: T, forward: T) -> Result<T> {
+        let expiry = validate::positive("expiry", expiry)?;
+        let coordinate = match self.axis {
+            StrikeAxis::Strike => validate::positive("strike", strike)?,
+            StrikeAxis::Moneyness => {
+                validate::positive("strike", strike)? / validate::positive("forward", forward)?
+            }
+        };
+        validate::positive(
+            "vol",
+            (self.total_variance(expiry, coordinate)? / expiry).sqrt(),
+        )
+    }
+
+    /// Total variance `σ²T` at `expiry` and a strike coordinate.
+    pub fn total_variance(&self, expiry: T, coordinate: T) -> Result<T> {
+        let variance = |i: usize| -> Result<T> {
+            let (time, smile) = &self.smiles[i];
+            let vol = self.smile_vol(smile, coordinate)?;
+            Ok(vol * vol * *time)
+        };
+        let after = self.smiles.partition_point(|(time, _)| *time < expiry);
+        if after == 0 {
+            return Ok(variance(0)? * expiry / self.smiles[0].0);
+        }
+        if let Some((time, _)) = self.smiles.get(after) {
+            let (previous, _) = self.smiles[after - 1];
+            let weight = (expiry - previous) / (*time - previous);
+            let (w0, w1) = (variance(after - 1)?, variance(after)?);
+            return Ok(w0 + (w1 - w0) * weight);
+        }
+
+        let last = self.smiles.len() - 1;
+        let (end, _) = self.smiles[last];
+        let at_end = variance(last)?;
+        match self.time_extrapolation {
+            Extrapolation::Flat => Ok(at_end * expiry / end),
+            Extrapolation::Linear if last > 0 => {
+                // Never below the last expiry's variance, so the extension
+                // cannot itself create calendar arbitrage.
+                let (previous, _) = self.smiles[last - 1];
+                let slope = (at_end - variance(last - 1)?) / (end - previous);
+                Ok(at_end + slope.max(T::zero()) * (expiry - end))
+            }
+            Extrapolation::Linear => Ok(at_end * expiry / end),
+            Extrapolation::Error => Err(OptOpsError::InvalidParameter {
+                name: "expiry",
+                value: to_f64(expiry),
+                domain: Domain::Between(0.0, to_f64(end)),
+            }),
+        }
+    }
+
+    /// Vol of `smile` at a strike coordinate.
+    fn smile_vol(&self, smile: &[(T, T)], coordinate: T) -> Result<T> {
+        let (first, last) = (smile[0], smile[smile.len() - 1]);
+        let curvature = match self.interpolation {
+            SmileInterpolation::CubicSpline => spline_curvature(smile),
+            SmileInterpolation::Linear => vec![T::zero(); smile.len()],
+        };
+        let inside = first.0 <= coordinate && coordinate <= last.0;
+        if inside && smile.len() > 1 {
+            let i = smile
+                .partition_point(|&(x, _)| x <= coordinate)
+                .clamp(1, smile.len() - 1);
+            return Ok(spline(smile, &curvature, i, coordinate));
+        }
+        if inside {
+            return Ok(first.1);
+        }
+        let below = coordinate < first.0;
+        let edge = if below { first } else { last };
+        match self.strike_extrapolation {
+            Extrapolation::Flat => Ok(edge.1),
+            Extrapolation::Linear if smile.len() > 1 => {
+                let n = smile.len() - 1;
+                let sixth = cast::<T>(1.0 / 6.0);
+                let slope = if below {
+                    let width = smile[1].0 - first.0;
+                    (smile[1].1 - first.1) / width - width * curvature[1] * sixth
+                } else {
+                    let width = last.0 - smile[n - 1].0;
+                    (last.1 - smile[n - 1].1) / width + width * curvature[n - 1] * sixth
+                };
+                Ok(edge.1 + slope * (coordinate - edge.0))
+            }
+            Extrapolation::Linear => Ok(edge.1),
+            Extrapolation::Error => Err(OptOpsError::InvalidParameter {
+                name: "strike",
+                value: to_f64(coordinate),
+                domain: Domain::Between(to_f64(first.0), to_f64(last.0)),
+            }),
+        }
+    }
+}
+
+/// Second derivatives of the natural cubic spline through `points`, zero at
+/// both ends, from the tridiagonal system by the Thomas algorithm.
+fn spline_curvature<T: Float>(points: &[(T, T)]) -> Vec<T> {
+    let n = points.len();
+    let mut curvature = vec![T::zero(); n];
+    if n < 3 {
+        return curvature;
+    }
+    let (two, six) = (cast::<T>(2.0), cast::<T>(6.0));
+    let (mut upper, mut rhs) = (vec![T::zero(); n], vec![T::zero(); n]);
+    for i in 1..n - 1 {
+        let (h0, h1) = (points[i].0 - points[i - 1].0, points[i + 1].0 - points[i].0);
+        let jump = (points[i + 1].1 - points[i].1) / h1 - (points[i].1 - points[i - 1].1) / h0;
+        let pivot = two * (h0 + h1) - h0 * upper[i - 1];
+        upper[i] = h1 / pivot;
+        rhs[i] = (six * jump - h0 * rhs[i - 1]) / pivot;
+    }
+    for i in (1..n - 1).rev() {
+        curvature[i] = rhs[i] - upper[i] * curvature[i + 1];
+    }
+    curvature
+}
+
+/// Cubic through `points[i - 1]` and `points[i]` with the given second
+/// derivatives at its ends, at `x`; zero curvature makes it a straight line.
+fn spline<T: Float>(points: &[(T, T)], curvature: &[T], i: usize, x: T) -> T {
+    let ((x0, y0), (x1, y1)) = (points[i - 1], points[i]);
+    let width = x1 - x0;
+    let t = (x - x0) / width;
+    let s = T::one() - t;
+    let bend = (s * s * s - s) * curvature[i - 1] + (t * t * t - t) * curvature[i];
+    s * y0 + t * y1 + width * width * bend / cast(6.0)
+}
@@ -0,0 +1,95 @@
+//! Static arbitrage in a surface's quotes.
+
+use super::{StrikeAxis, VolSurface};
+use crate::core;
+use crate::error::Result;
+use crate::float::{cast, Float};
+use crate::instrument::OptionType;
+use crate::market::Market;
+use serde::{Deserialize, Serialize};
+
+/// One place where a surface's quotes admit a riskless profit.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Arbitrage<T = f64> {
+    /// Call prices at `expiry` are concave in strike at the quoted `strike`,
+    /// so the butterfly centred there costs less than nothing.
+    Butterfly { expiry: T, strike: T },
+    /// Total variance falls from `earlier` to `later` at the moneyness of
+    /// `strike` quoted at `earlier`, so a calendar spread there is free.
+    Calendar { earlier: T, later: T, strike: T },
+}
+
+impl<T: Float> VolSurface<T> {
+    /// Every butterfly and calendar arbitrage among the quotes, with
+    /// strikes on the surface's own axis and forwards from `market`. The
+    /// butterfly check prices each quoted strike and its neighbours with
+    /// Black's formula on the forward; the calendar check compares each
+    /// expiry's total variance with the next one's at the same moneyness,
+    /// wherever both smiles are quoted.
+    pub fn arbitrage(&self, market: &Market<T>) -> Result<Vec<Arbitrage<T>>> {
+        let slack = cast::<T>(1e-12);
+        let forwards = self
+            .smiles
+            .iter()
+            .map(|(expiry, _)| market.forward(*expiry))
+            .collect::<Result<Vec<_>>>()?;
+        let strike_of = |coordinate: T, forward: T| match self.axis {
+            StrikeAxis::Strike => coordinate,
+            StrikeAxis::Moneyness => coordinate * forward,
+        };
+
+        let mut found = Vec::new();
+        for ((expiry, smile), &forward) in self.smiles.iter().zip(&forwards) {
+            let call = |&(coordinate, vol): &(T, T)| {
+                let strike = strike_of(coordinate, forward);
+                let price = core::black_scholes(
+                    OptionType::Call,
+                    forward,
+                    strike,
+                    T::zero(),
+                    T::zero(),
+                    vol,
+                    *expiry,
+                );
+                (strike, price)
+            };
+            for window in smile.windows(3) {
+                let [(k0, c0), (k1, c1), (k2, c2)] = [0, 1, 2].map(|i| call(&window[i]));
+                let chord = (c0 * (k2 - k1) + c2 * (k1 - k0)) / (k2 - k0);
+                if c1 > chord + slack * forward {
+                    found.push(Arbitrage::Butterfly {
+                        expiry: *expiry,
+                        strike: window[1].0,
+                    });
+                }
+            }
+        }
+
+        for (j, pair) in self.smiles.windows(2).enumerate() {
+            let [(earlier, near), (later, far)] = [&pair[0], &pair[1]];
+            // Coordinates on the later smile holding the moneyness fixed.
+            let shift = match self.axis {
+                StrikeAxis::Strike => forwards[j + 1] / forwards[j],
+                StrikeAxis::Moneyness => T::one(),
+            };
+            let (low, high) = (far[0].0, far[far.len() - 1].0);
+            for &(coordinate, vol) in near {
+                let shifted = coordinate * shift;
+                if shifted < low || shifted > high {
+                    continue;
+                }
+                let before = vol * vol * *earlier;
+                let after = self.smile_vol(far, shifted)?;
+                if after * after * *later < before * (T::one() - slack) {
+                    found.push(Arbitrage::Calendar {
+                        earlier: *earlier,
+                        later: *later,
+                        strike: coordinate,
+                    });
+                }
+            }
+        }
+        Ok(found)
+    }
+}
@@ -0,0 +1,287 @@
+//! Surfaces and fitted smile parameters as CSV and JSON, to share
+//! calibrations with other systems.
+
+use super::{Sabr, StrikeAxis, Svi, VolSurface};
+use crate::csv;
+use crate::error::{persistence, OptOpsError, Result};
+use crate::float::{cast, to_f64, Float};
+use crate::validate;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+/// File format of a path, from its extension.
+enum Format {
+    Csv,
+    Json,
+}
+
+impl Format {
+    fn of(path: &Path) -> Result<Self> {
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some(extension) if extension.eq_ignore_ascii_case("csv") => Ok(Format::Csv),
+            Some(extension) if extension.eq_ignore_ascii_case("json") => Ok(Format::Json),
+            _ => Err(OptOpsError::Persistence(format!(
+                "`{}` is neither a .csv nor a .json file",
+                path.display()
+            ))),
+        }
+    }
+}
+
+impl<T: Float + Serialize + DeserializeOwned> VolSurface<T> {
+    /// One `expiry,strike,vol` row per quote, or `expiry,moneyness,vol` on
+    /// the moneyness axis. The interpolation and extrapolation rules are
+    /// not written; JSON keeps them.
+    pub fn to_csv(&self) -> String {
+        let rows = self.smiles().flat_map(|(expiry, smile)| {
+            smile.iter().map(move |&(coordinate, vol)| {
+                [expiry, coordinate, vol].into_iter().map(to_f64).collect()
+            })
+        });
+        csv::write(&["expiry", axis_column(self.axis), "vol"], rows)
+    }
+
+    /// Surface from `to_csv`'s rows, on whichever axis the header names,
+    /// with the default interpolation and extrapolation.
+    pub fn from_csv(csv: &str) -> Result<Self> {
+        let axis = if csv::has_column(csv, "moneyness") {
+            StrikeAxis::Moneyness
+        } else {
+            StrikeAxis::Strike
+        };
+        let rows = csv::read(csv, &["expiry", axis_column(axis), "vol"])?;
+        VolSurface::new(
+            axis,
+            rows.into_iter()
+                .map(|row| (cast(row[0]), cast(row[1]), cast(row[2]))),
+        )
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string_pretty(self).map_err(persistence)
+    }
+
+    /// Surface from `to_json`'s output, with its quotes checked as
+    /// `VolSurface::new` checks them.
+    pub fn from_json(json: &str) -> Result<Self> {
+        let surface: VolSurface<T> = serde_json::from_str(json).map_err(persistence)?;
+        let quotes = surface.smiles().flat_map(|(expiry, smile)| {
+            smile
+                .iter()
+                .map(move |&(coordinate, vol)| (expiry, coordinate, vol))
+        });
+        Ok(VolSurface::new(surface.axis, quotes)?
+            .with_interpolation(surface.interpolation)
+            .with_strike_extrapolation(surface.strike_extrapolation)
+            .with_time_extrapolation(surface.time_extrapolation))
+    }
+
+    /// Writes the surface to `path` as CSV or JSON by its extension.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let contents = match Format::of(path.as_ref())? {
+            Format::Csv => self.to_csv(),
+            Format::Json => self.to_json()?,
+        };
+        fs::write(path, contents).map_err(persistence)
+    }
+
+    /// Reads a surface from `path` as CSV or JSON by its extension.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let format = Format::of(path.as_ref())?;
+        let contents = fs::read_to_string(path).map_err(persistence)?;
+        match format {
+            Format::Csv => VolSurface::from_csv(&contents),
+            Format::Json => VolSurface::from_json(&contents),
+        }
+    }
+}
+
+fn axis_column(axis: StrikeAxis) -> &'static str {
+    match axis {
+        StrikeAxis::Strike => "strike",
+        StrikeAxis::Moneyness => "moneyness",
+    }
+}
+
+/// A smile's parameters, tabulated one expiry per row.
+trait Parameters<T>: Copy + Serialize + DeserializeOwned {
+    const COLUMNS: &'static [&'static str];
+
+    fn row(&self) -> Vec<T>;
+
+    /// Parameters from a row in `COLUMNS` order, checked by `new`.
+    fn from_row(row: &[T]) -> Result<Self>;
+}
+
+impl<T: Float + Serialize + DeserializeOwned> Parameters<T> for Svi<T> {
+    const COLUMNS: &'static [&'static str] = &["a", "b", "rho", "m", "sigma"];
+
+    fn row(&self) -> Vec<T> {
+        vec![self.a, self.b, self.rho, self.m, self.sigma]
+    }
+
+    fn from_row(row: &[T]) -> Result<Self> {
+        Svi::new(row[0], row[1], row[2], row[3], row[4])
+    }
+}
+
+impl<T: Float + Serialize + DeserializeOwned> Parameters<T> for Sabr<T> {
+    const COLUMNS: &'static [&'static str] = &["alpha", "beta", "rho", "nu"];
+
+    fn row(&self) -> Vec<T> {
+        vec![self.alpha, self.beta, self.rho, self.nu]
+    }
+
+    fn from_row(row: &[T]) -> Result<Self> {
+        Sabr::new(row[0], row[1], row[2], row[3])
+    }
+}
+
+/// One expiry's parameters in JSON, as a flat object.
+#[derive(Serialize, Deserialize)]
+struct Slice<T, P> {
+    expiry: T,
+    #[serde(flatten)]
+    parameters: P,
+}
+
+fn columns<T, P: Parameters<T>>() -> Vec<&'static str> {
+    std::iter::once("expiry")
+        .chain(P::COLUMNS.iter().copied())
+        .collect()
+}
+
+fn to_csv<T: Float, P: Parameters<T>>(smiles: &[(T, P)]) -> String {
+    let rows = smiles.iter().map(|(expiry, parameters)| {
+        std::iter::once(*expiry)
+            .chain(parameters.row())
+            .map(to_f64)
+            .collect()
+    });
+    csv::write(&columns::<T, P>(), rows)
+}
+
+fn from_csv<T: Float, P: Parameters<T>>(csv: &str) -> Result<Vec<(T, P)>> {
+    csv::read(csv, &columns::<T, P>())?
+        .into_iter()
+        .map(|row| {
+            let row: Vec<T> = row.into_iter().map(cast).collect();
+            Ok((
+                validate::positive("expiry", row[0])?,
+                P::from_row(&row[1..])?,
+            ))
+        })
+        .collect()
+}
+
+fn to_json<T: Float + Serialize, P: Parameters<T>>(smiles: &[(T, P)]) -> Result<String> {
+    let slices: Vec<Slice<T, P>> = smiles
+        .iter()
+        .map(|&(expiry, parameters)| Slice { expiry, parameters })
+        .collect();
+    serde_json::to_string_pretty(&slices).map_err(persistence)
+}
+
+fn from_json<T: Float + DeserializeOwned, P: Parameters<T>>(json: &str) -> Result<Vec<(T, P)>> {
+    let slices: Vec<Slice<T, P>> = serde_json::from_str(json).map_err(persistence)?;
+    slices
+        .into_iter()
+        .map(|slice| {
+            let expiry = validate::positive("expiry", slice.expiry)?;
+            Ok((expiry, P::from_row(&slice.parameters.row())?))
+        })
+        .collect()
+}
+
+fn save<T, P, Q>(smiles: &[(T, P)], path: Q) -> Result<()>
+where
+    T: Float + Serialize,
+    P: Parameters<T>,
+    Q: AsRef<Path>,
+{
+    let contents = match Format::of(path.as_ref())? {
+        Format::Csv => to_csv(smiles),
+        Format::Json => to_json(smiles)?,
+    };
+    fs::write(path, contents).map_err(persistence)
+}
+
+fn load<T, P, Q>(path: Q) -> Result<Vec<(T, P)>>
+where
+    T: Float + DeserializeOwned,
+    P: Parameters<T>,
+    Q: AsRef<Path>,
+{
+    let format = Format::of(path.as_ref())?;
+    let contents = fs::read_to_string(path).map_err(persistence)?;
+    match format {
+        Format::Csv => from_csv(&contents),
+        Format::Json => from_json(&contents),
+    }
+}
+
+impl<T: Float + Serialize + DeserializeOwned> Svi<T> {
+    /// `expiry,a,b,rho,m,sigma` rows, one per `(expiry, smile)`.
+    pub fn to_csv(smiles: &[(T, Self)]) -> String {
+        to_csv(smiles)
+    }
+
+    /// Smiles from `to_csv`'s rows, each checked by `Svi::new`.
+    pub fn from_csv(csv: &str) -> Result<Vec<(T, Self)>> {
+        from_csv(csv)
+    }
+
+    /// An array of objects holding each expiry beside its parameters.
+    pub fn to_json(smiles: &[(T, Self)]) -> Result<String> {
+        to_json(smiles)
+    }
+
+    /// Smiles from `to_json`'s output, each checked by `Svi::new`.
+    pub fn from_json(json: &str) -> Result<Vec<(T, Self)>> {
+        from_json(json)
+    }
+
+    /// Writes `smiles` to `path` as CSV or JSON by its extension.
+    pub fn save<P: AsRef<Path>>(smiles: &[(T, Self)], path: P) -> Result<()> {
+        save(smiles, path)
+    }
+
+    /// Reads smiles from `path` as CSV or JSON by its extension.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Vec<(T, Self)>> {
+        load(path)
+    }
+}
+
+impl<T: Float + Serialize + DeserializeOwned> Sabr<T> {
+    /// `expiry,alpha,beta,rho,nu` rows, one per `(expiry, smile)`.
+    pub fn to_csv(smiles: &[(T, Self)]) -> String {
+        to_csv(smiles)
+    }
+
+    /// Smiles from `to_csv`'s rows, each checked by `Sabr::new`.
+    pub fn from_csv(csv: &str) -> Result<Vec<(T, Self)>> {
+        from_csv(csv)
+    }
+
+    /// An array of objects holding each expiry beside its parameters.
+    pub fn to_json(smiles: &[(T, Self)]) -> Result<String> {
+        to_json(smiles)
+    }
+
+    /// Smiles from `to_json`'s output, each checked by `Sabr::new`.
+    pub fn from_json(json: &str) -> Result<Vec<(T, Self)>> {
+        from_json(json)
+    }
+
+    /// Writes `smiles` to `path` as CSV or JSON by its extension.
+    pub fn save<P: AsRef<Path>>(smiles: &[(T, Self)], path: P) -> Result<()> {
+        save(smiles, path)
+    }
+
+    /// Reads smiles from `path` as CSV or JSON by its extension.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Vec<(T, Self)>> {
+        load(path)
+    }
+}
@@ -0,0 +1,152 @@
+//! Dupire local vols implied by a vol surface.
+
+use super::{StrikeAxis, VolSurface};
+use crate::error::{Domain, OptOpsError, Result};
+use crate::float::{cast, to_f64, Float};
+use crate::market::Market;
+use crate::validate;
+use serde::{Deserialize, Serialize};
+
+/// Bounds every local vol is clamped to, against the spikes finite
+/// differences of market data produce.
+const VOL_BOUNDS: (f64, f64) = (1e-3, 5.0);
+
+/// Smallest denominator admitted in Dupire's formula; the quotes have
+/// butterfly arbitrage where the true one falls below zero.
+const MIN_DENOMINATOR: f64 = 1e-4;
+
+/// Log-moneyness step of the strike differences.
+const LOG_STEP: f64 = 1e-3;
+
+/// Local vol `sigma(t, S)` tabulated on a grid of times and spots, read
+/// between them bilinearly and held flat beyond them, for local-vol
+/// lattices and PDE solvers to sample at their own nodes.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(bound(
+    serialize = "T: Float + Serialize",
+    deserialize = "T: Float + Deserialize<'de>"
+))]
+pub struct LocalVol<T = f64> {
+    times: Vec<T>,
+    spots: Vec<T>,
+    /// `vols[i][j]` at `times[i]` and `spots[j]`.
+    vols: Vec<Vec<T>>,
+}
+
+impl<T: Float> LocalVol<T> {
+    /// Dupire's local vol from `surface` at each of `times` and `spots`,
+    /// both strictly increasing, with forwards from `market`. In Gatheral's
+    /// form over the total variance `w(y, T)` at log-moneyness `y =
+    /// ln(K / F)`,
+    ///
+    /// `sigma^2 = dw/dT / (1 - y/w dw/dy + (-1/4 - 1/w + y^2/w^2) (dw/dy)^2 / 4
+    /// + d2w/dy2 / 2)`,
+    ///
+    /// with the derivatives taken by central differences of the surface
+    /// at the spot as strike. The surface should be smooth in strike, so
+    /// use `SmileInterpolation::CubicSpline`: linear smiles have no second
+    /// derivative between quotes. Negative calendar slopes count as zero,
+    /// the denominator is floored at `1e-4` and the results are clamped to
+    /// `[0.001, 5]`.
+    pub fn dupire(
+        surface: &VolSurface<T>,
+        market: &Market<T>,
+        times: &[T],
+        spots: &[T],
+    ) -> Result<Self> {
+        let times = increasing("time", times)?;
+        let spots = increasing("spot", spots)?;
+        let (two, four) = (cast::<T>(2.0), cast::<T>(4.0));
+        let quarter = T::one() / four;
+        let step = cast::<T>(LOG_STEP);
+        let (lowest, highest) = (cast::<T>(VOL_BOUNDS.0), cast::<T>(VOL_BOUNDS.1));
+
+        let variance = |time: T, y: T| -> Result<T> {
+            let coordinate = match surface.axis() {
+                StrikeAxis::Strike => market.forward(time)? * y.exp(),
+                StrikeAxis::Moneyness => y.exp(),
+            };
+            surface.total_variance(time, coordinate)
+        };
+        let vols = times
+            .iter()
+            .map(|&time| {
+                let forward = market.forward(time)?;
+                let dt = (time * cast(1e-3)).max(cast(1e-5)).min(time / two);
+                spots
+                    .iter()
+                    .map(|&spot| {
+                        let y = (spot / forward).ln();
+                        // Log-moneyness holds fixed across the time bump.
+                        let later = variance(time + dt, y)?;
+                        let slope_t = (later - variance(time - dt, y)?) / (dt + dt);
+                        let (down, w, up) = (
+                            variance(time, y - step)?,
+                            variance(time, y)?,
+                            variance(time, y + step)?,
+                        );
+                        let slope = (up - down) / (step + step);
+                        let curvature = (up - w - w + down) / (step * step);
+                        let denominator = T::one() - y / w * slope
+                            + quarter * (-quarter - T::one() / w + y * y / (w * w)) * slope * slope
+                            + curvature / two;
+                        let denominator = denominator.max(cast(MIN_DENOMINATOR));
+                        let local = (slope_t.max(T::zero()) / denominator).sqrt();
+                        Ok(local.max(lowest).min(highest))
+                    })
+                    .collect::<Result<Vec<_>>>()
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(LocalVol { times, spots, vols })
+    }
+
+    pub fn times(&self) -> &[T] {
+        &self.times
+    }
+
+    pub fn spots(&self) -> &[T] {
+        &self.spots
+    }
+
+    /// Local vol at `time` and `spot`.
+    pub fn vol(&self, time: T, spot: T) -> T {
+        let (i, s) = bracket(&self.times, time);
+        let (j, u) = bracket(&self.spots, spot);
+        let row = |i: usize| {
+            let vols = &self.vols[i];
+            vols[j] + (vols[(j + 1).min(vols.len() - 1)] - vols[j]) * u
+        };
+        let next = (i + 1).min(self.times.len() - 1);
+        row(i) + (row(next) - row(i)) * s
+    }
+}
+
+/// `values` checked positive and strictly increasing.
+fn increasing<T: Float>(name: &'static str, values: &[T]) -> Result<Vec<T>> {
+    if values.is_empty() {
+        return Err(OptOpsError::MissingParameter(name));
+    }
+    let mut previous = T::zero();
+    values
+        .iter()
+        .map(|&value| {
+            validate::check(name, value, Domain::Above(to_f64(previous)))?;
+            previous = value;
+            Ok(value)
+        })
+        .collect()
+}
+
+/// Index of the grid point at or below `x` and the fraction of the way to
+/// the next, zero beyond either end.
+fn bracket<T: Float>(grid: &[T], x: T) -> (usize, T) {
+    let above = grid.partition_point(|&point| point <= x);
+    if above == 0 {
+        return (0, T::zero());
+    }
+    if above == grid.len() {
+        return (grid.len() - 1, T::zero());
+    }
+    let (lo, hi) = (grid[above - 1], grid[above]);
+    (above - 1, (x - lo) / (hi - lo))
+}
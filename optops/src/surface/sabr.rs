@@ -0,0 +1,210 @@
+//! SABR smiles through Hagan's lognormal implied vol expansion.
+
+use crate::error::{Domain, Result};
+use crate::float::{cast, Float};
+use crate::optimize;
+use crate::validate;
+use serde::{Deserialize, Serialize};
+
+/// Evaluations allowed to the simplex search of a fit.
+const MAX_EVALUATIONS: usize = 4000;
+
+/// SABR dynamics of a forward, `dF = alpha_t F^beta dW` with the vol
+/// itself lognormal, `dalpha = nu alpha dZ`, and `d<W, Z> = rho dt`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Sabr<T = f64> {
+    /// Initial level of the vol, in units of `F^(1 - beta)`.
+    pub alpha: T,
+    /// Backbone exponent in `[0, 1]`: one is lognormal, zero normal.
+    pub beta: T,
+    /// Correlation of the forward and its vol, tilting the smile.
+    pub rho: T,
+    /// Vol of the vol, curving the smile.
+    pub nu: T,
+}
+
+impl<T: Float> Sabr<T> {
+    pub fn new(alpha: T, beta: T, rho: T, nu: T) -> Result<Self> {
+        Ok(Sabr {
+            alpha: validate::positive("alpha", alpha)?,
+            beta: validate::check("beta", beta, Domain::Between(0.0, 1.0))?,
+            rho: validate::check("rho", rho, Domain::Inside(-1.0, 1.0))?,
+            nu: validate::check("nu", nu, Domain::NonNegative)?,
+        })
+    }
+
+    /// Hagan, Kumar, Lesniewski and Woodward's (2002) Black vol for
+    /// `strike` at `expiry` on a forward at `forward`, both positive. The
+    /// expansion is first order in `expiry` and loses accuracy for long
+    /// expiries and far wings, where it can even imply arbitrage.
+    pub fn implied_vol(&self, forward: T, strike: T, expiry: T) -> T {
+        let Sabr {
+            alpha,
+            beta,
+            rho,
+            nu,
+        } = *self;
+        let (one, two) = (T::one(), cast::<T>(2.0));
+        let skew = one - beta;
+        let log = (forward / strike).ln();
+        let geometric = (forward * strike).powf(skew / two);
+        let (log2, skew2) = (log * log, skew * skew);
+        let series = skew2 / cast(24.0) * log2 + skew2 * skew2 / cast(1920.0) * log2 * log2;
+        let tilt = two - cast::<T>(3.0) * rho * rho;
+        // `z / x(z)`, by its Taylor series near the money where `x(z)`
+        // cancels.
+        let z = nu / alpha * geometric * log;
+        let ratio = if z.abs() < cast(1e-6) {
+            one - rho * z / two + tilt * z * z / cast(12.0)
+        } else {
+            let root = (one - two * rho * z + z * z).sqrt();
+            z / ((root + z - rho) / (one - rho)).ln()
+        };
+        let correction = skew2 / cast(24.0) * alpha * alpha / (geometric * geometric)
+            + rho * beta * nu * alpha / (cast::<T>(4.0) * geometric)
+            + tilt / cast(24.0) * nu * nu;
+        let denominator = geometric * (one + series);
+        alpha / denominator * ratio * (one + correction * expiry)
+    }
+
+    /// `(expiry, strike, vol)` at each of `strikes`, ready for
+    /// `VolSurface::new` on `StrikeAxis::Strike`.
+    pub fn quotes(&self, forward: T, expiry: T, strikes: &[T]) -> Vec<(T, T, T)> {
+        strikes
+            .iter()
+            .map(|&strike| (expiry, strike, self.implied_vol(forward, strike, expiry)))
+            .collect()
+    }
+
+    /// Least-squares fit in vol of `alpha`, `rho` and `nu` to `(strike,
+    /// implied vol)` quotes at `expiry` on a forward at `forward`, with
+    /// `beta` fixed as markets usually fix it. The simplex search runs on
+    /// `ln alpha`, `atanh rho` and `ln nu`, so every trial point is valid;
+    /// it starts from the `alpha` that matches the quote nearest the
+    /// money. Needs at least three quotes.
+    pub fn fit(forward: T, expiry: T, beta: T, quotes: &[(T, T)]) -> Result<Self> {
+        let forward = validate::positive("forward", forward)?;
+        let expiry = validate::positive("expiry", expiry)?;
+        let beta = validate::check("beta", beta, Domain::Between(0.0, 1.0))?;
+        validate::check("quotes", quotes.len() as f64, Domain::AtLeast(3.0))?;
+        for &(strike, vol) in quotes {
+            validate::positive("strike", strike)?;
+            validate::positive("vol", vol)?;
+        }
+        let distance = |strike: T| (strike / forward).ln().abs();
+        let (_, atm_vol) = *quotes
+            .iter()
+            .min_by(|a, b| {
+                distance(a.0)
+                    .partial_cmp(&distance(b.0))
+                    .expect("finite strikes")
+            })
+            .expect("at least three quotes");
+
+        let decode = |x: &[T; 3]| Sabr {
+            alpha: x[0].exp(),
+            beta,
+            rho: x[1].tanh(),
+            nu: x[2].exp(),
+        };
+        let residual = |x: &[T; 3]| {
+            let sabr = decode(x);
+            quotes.iter().fold(T::zero(), |sum, &(strike, vol)| {
+                let miss = sabr.implied_vol(forward, strike, expiry) - vol;
+                sum + miss * miss
+            })
+        };
+        let start = [
+            (atm_vol * forward.powf(T::one() - beta)).ln(),
+            T::zero(),
+            cast(-0.7),
+        ];
+        let scale = [cast(0.2), cast(0.5), cast(0.5)];
+        let tolerance = cast::<T>(1e-14);
+        let (best, _) = optimize::nelder_mead(residual, start, scale, tolerance, MAX_EVALUATIONS);
+        let sabr = decode(&best);
+        // `tanh` rounds to one far out; keep the correlation just inside.
+        let bound = T::one() - T::epsilon();
+        Sabr::new(sabr.alpha, beta, sabr.rho.max(-bound).min(bound), sabr.nu)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::OptOpsError;
+
+    fn smile() -> Sabr {
+        Sabr::new(0.035, 0.5, -0.3, 0.45).unwrap()
+    }
+
+    /// With no vol of vol and a lognormal backbone the model is Black's at
+    /// `alpha`.
+    #[test]
+    fn lognormal_without_vol_of_vol_is_black() {
+        let sabr: Sabr = Sabr::new(0.2, 1.0, -0.5, 0.0).unwrap();
+        for strike in [60.0, 100.0, 150.0] {
+            assert!((sabr.implied_vol(100.0, strike, 2.0) - 0.2).abs() < 1e-15);
+        }
+    }
+
+    /// At the money the expansion reduces to Hagan et al's (2002) equation
+    /// (2.18), and stays continuous as the strike moves off the money past
+    /// the switch to the Taylor series of `z / x(z)`.
+    #[test]
+    fn at_the_money_matches_hagan() {
+        let Sabr {
+            alpha,
+            beta,
+            rho,
+            nu,
+        } = smile();
+        let (forward, expiry): (f64, f64) = (0.04, 1.5);
+        let level = forward.powf(1.0 - beta);
+        let atm = alpha / level
+            * (1.0
+                + ((1.0 - beta).powi(2) / 24.0 * alpha * alpha / (level * level)
+                    + rho * beta * nu * alpha / (4.0 * level)
+                    + (2.0 - 3.0 * rho * rho) / 24.0 * nu * nu)
+                    * expiry);
+        let sabr = smile();
+        assert!((sabr.implied_vol(forward, forward, expiry) - atm).abs() < 1e-14);
+        for bump in [1e-7, 1e-5] {
+            let strike = forward * (1.0 + bump);
+            let slope = (sabr.implied_vol(forward, strike, expiry) - atm) / (strike - forward);
+            let wider = forward * (1.0 + 10.0 * bump);
+            let wide = (sabr.implied_vol(forward, wider, expiry) - atm) / (wider - forward);
+            assert!((slope - wide).abs() < 1e-2 * wide.abs(), "{bump}");
+        }
+    }
+
+    /// Perfect correlation is outside the model and reported as such.
+    #[test]
+    fn correlation_must_lie_strictly_inside_one() {
+        for rho in [-1.0, 1.0, f64::NAN] {
+            match Sabr::new(0.2, 0.5, rho, 0.3) {
+                Err(OptOpsError::InvalidParameter { name, domain, .. }) => {
+                    assert_eq!((name, domain), ("rho", Domain::Inside(-1.0, 1.0)))
+                }
+                other => panic!("{other:?}"),
+            }
+        }
+    }
+
+    /// Quotes read off a SABR smile fit back to its parameters.
+    #[test]
+    fn fit_recovers_an_exact_smile() {
+        let (forward, expiry) = (0.04, 1.5);
+        let truth = smile();
+        let strikes: Vec<f64> = (0..=12).map(|i| 0.02 + 0.004 * f64::from(i)).collect();
+        let quotes: Vec<(f64, f64)> = truth
+            .quotes(forward, expiry, &strikes)
+            .into_iter()
+            .map(|(_, strike, vol)| (strike, vol))
+            .collect();
+        let fit = Sabr::fit(forward, expiry, truth.beta, &quotes).unwrap();
+        assert!((fit.alpha - truth.alpha).abs() < 1e-5, "{fit:?}");
+        assert!((fit.rho - truth.rho).abs() < 1e-3, "{fit:?}");
+        assert!((fit.nu - truth.nu).abs() < 1e-3, "{fit:?}");
+    }
+}
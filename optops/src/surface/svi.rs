@@ -0,0 +1,271 @@
+//! Gatheral's raw SVI smile, fitted to one expiry's implied vols.
+
+use crate::error::{Domain, Result};
+use crate::float::{cast, Float};
+use crate::optimize;
+use crate::validate;
+use serde::{Deserialize, Serialize};
+
+/// Evaluations allowed to each start of the outer simplex search.
+const MAX_EVALUATIONS: usize = 2000;
+
+/// Raw SVI total variance `w(k) = a + b (rho (k - m) + sqrt((k - m)^2 +
+/// sigma^2))` in log-moneyness `k = ln(K / F)`: a hyperbola whose wings
+/// are linear in `k`, as Lee's moment formula requires of any smile.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Svi<T = f64> {
+    /// Level of the total variance.
+    pub a: T,
+    /// Slope of the wings.
+    pub b: T,
+    /// Tilt between the put and call wings, in `[-1, 1]`.
+    pub rho: T,
+    /// Log-moneyness the smile is centred on.
+    pub m: T,
+    /// Curvature at the vertex; smaller is sharper.
+    pub sigma: T,
+}
+
+impl<T: Float> Svi<T> {
+    /// Raw parameters, rejected unless they give a non-negative variance
+    /// everywhere.
+    pub fn new(a: T, b: T, rho: T, m: T, sigma: T) -> Result<Self> {
+        let svi = Svi {
+            a: validate::finite("a", a)?,
+            b: validate::check("b", b, Domain::NonNegative)?,
+            rho: validate::check("rho", rho, Domain::Between(-1.0, 1.0))?,
+            m: validate::finite("m", m)?,
+            sigma: validate::positive("sigma", sigma)?,
+        };
+        validate::check(
+            "minimum variance",
+            svi.minimum_variance(),
+            Domain::NonNegative,
+        )?;
+        Ok(svi)
+    }
+
+    /// `a + b sigma sqrt(1 - rho^2)`, the variance at the vertex.
+    pub fn minimum_variance(&self) -> T {
+        self.a + self.b * self.sigma * (T::one() - self.rho * self.rho).sqrt()
+    }
+
+    /// Total variance `σ²T` at log-moneyness `k`.
+    pub fn total_variance(&self, k: T) -> T {
+        let shifted = k - self.m;
+        let root = (shifted * shifted + self.sigma * self.sigma).sqrt();
+        self.a + self.b * (self.rho * shifted + root)
+    }
+
+    /// Implied vol at log-moneyness `k` for a smile expiring at `expiry`.
+    pub fn implied_vol(&self, k: T, expiry: T) -> T {
+        (self.total_variance(k) / expiry).sqrt()
+    }
+
+    /// `(expiry, K / F, vol)` at each of `moneyness`, ready for
+    /// `VolSurface::new` on `StrikeAxis::Moneyness`, so a fitted smile
+    /// rather than the raw quotes builds the surface.
+    pub fn quotes(&self, expiry: T, moneyness: &[T]) -> Vec<(T, T, T)> {
+        moneyness
+            .iter()
+            .map(|&ratio| (expiry, ratio, self.implied_vol(ratio.ln(), expiry)))
+            .collect()
+    }
+
+    /// Least-squares fit in total variance to `(log-moneyness, implied vol)`
+    /// quotes at `expiry`, after Zeliade's quasi-explicit method: for a
+    /// given `m` and `sigma` the variance is linear in the other three
+    /// parameters, whose constrained fit is solved exactly, leaving a
+    /// simplex search over `m` and `ln sigma`.
+    ///
+    /// The constraints keep the variance non-negative (`a >= 0`), `a` no
+    /// higher than the largest quoted variance and the wings within Lee's
+    /// bound `b (1 + |rho|) <= 2`. Needs at least five quotes.
+    pub fn fit(expiry: T, quotes: &[(T, T)]) -> Result<Self> {
+        let expiry = validate::positive("expiry", expiry)?;
+        validate::check("quotes", quotes.len() as f64, Domain::AtLeast(5.0))?;
+        let points = quotes
+            .iter()
+            .map(|&(k, vol)| {
+                let vol = validate::positive("vol", vol)?;
+                Ok((validate::finite("log-moneyness", k)?, vol * vol * expiry))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let ceiling = points.iter().fold(T::zero(), |top, &(_, w)| top.max(w));
+        let (mut lowest, mut left, mut right) = (points[0], T::infinity(), T::neg_infinity());
+        for &(k, w) in &points {
+            if w < lowest.1 {
+                lowest = (k, w);
+            }
+            (left, right) = (left.min(k), right.max(k));
+        }
+        let width = (right - left).max(cast(1e-3));
+
+        let residual = |x: &[T; 2]| linear_fit(&points, ceiling, x[0], x[1].exp()).1;
+        let tolerance = cast::<T>(1e-14);
+        let scale = [width / cast(4.0), cast(0.5)];
+        let (best, _) = [0.05, 0.2, 0.8]
+            .iter()
+            .map(|&sigma| {
+                let start = [lowest.0, (width * cast(sigma)).ln()];
+                optimize::nelder_mead(residual, start, scale, tolerance, MAX_EVALUATIONS)
+            })
+            .min_by(|a, b| a.1.partial_cmp(&b.1).expect("residuals are finite"))
+            .expect("three starts");
+        let (m, sigma) = (best[0], best[1].exp());
+        let ([a, d, c], _) = linear_fit(&points, ceiling, m, sigma);
+        let rho = if c > T::zero() { d / c } else { T::zero() };
+        Svi::new(a, c / sigma, rho.max(-T::one()).min(T::one()), m, sigma)
+    }
+}
+
+/// Best `[a, d, c]` for `w = a + d y + c sqrt(y^2 + 1)`, `y = (k - m) /
+/// sigma`, with its sum of squared residuals, subject to `0 <= a <=
+/// ceiling`, `|d| <= c` and `c + |d| <= 2 sigma`. The fit is a convex
+/// quadratic over a polytope, so its minimum is the equality-constrained
+/// one of whichever active set is feasible and lowest; with six
+/// constraints and three unknowns every such set is tried.
+fn linear_fit<T: Float>(points: &[(T, T)], ceiling: T, m: T, sigma: T) -> ([T; 3], T) {
+    let rows: Vec<([T; 3], T)> = points
+        .iter()
+        .map(|&(k, w)| {
+            let y = (k - m) / sigma;
+            ([T::one(), y, (y * y + T::one()).sqrt()], w)
+        })
+        .collect();
+    let mut normal = [[T::zero(); 3]; 3];
+    let mut rhs = [T::zero(); 3];
+    for (row, w) in &rows {
+        for i in 0..3 {
+            for j in 0..3 {
+                normal[i][j] = normal[i][j] + row[i] * row[j];
+            }
+            rhs[i] = rhs[i] + row[i] * *w;
+        }
+    }
+    let (zero, one, two) = (T::zero(), T::one(), cast::<T>(2.0));
+    let constraints: [([T; 3], T); 6] = [
+        ([-one, zero, zero], zero),
+        ([one, zero, zero], ceiling),
+        ([zero, one, -one], zero),
+        ([zero, -one, -one], zero),
+        ([zero, one, one], two * sigma),
+        ([zero, -one, one], two * sigma),
+    ];
+    let residual = |p: &[T; 3]| {
+        rows.iter().fold(zero, |sum, (row, w)| {
+            let miss = *w - (row[0] * p[0] + row[1] * p[1] + row[2] * p[2]);
+            sum + miss * miss
+        })
+    };
+    let feasible = |p: &[T; 3]| {
+        constraints.iter().all(|(g, h)| {
+            g[0] * p[0] + g[1] * p[1] + g[2] * p[2] <= *h + cast::<T>(1e-12) * (one + h.abs())
+        })
+    };
+
+    // `a = c = d = 0` is always feasible.
+    let mut best = ([zero; 3], residual(&[zero; 3]));
+    for subset in 0u32..1 << constraints.len() {
+        let active: Vec<_> = (0..constraints.len())
+            .filter(|i| subset >> i & 1 == 1)
+            .collect();
+        if active.len() > 3 {
+            continue;
+        }
+        let size = 3 + active.len();
+        let mut matrix = vec![vec![zero; size]; size];
+        let mut vector = vec![zero; size];
+        for i in 0..3 {
+            matrix[i][..3].copy_from_slice(&normal[i]);
+            vector[i] = rhs[i];
+        }
+        for (row, &index) in active.iter().enumerate() {
+            let (g, h) = constraints[index];
+            for i in 0..3 {
+                matrix[3 + row][i] = g[i];
+                matrix[i][3 + row] = g[i];
+            }
+            vector[3 + row] = h;
+        }
+        let Some(solution) = optimize::solve_linear(matrix, vector) else {
+            continue;
+        };
+        let p = [solution[0], solution[1], solution[2]];
+        if feasible(&p) {
+            let value = residual(&p);
+            // The unconstrained minimum, tried first, is the answer
+            // whenever it is feasible.
+            if subset == 0 {
+                return (p, value);
+            }
+            if value < best.1 {
+                best = (p, value);
+            }
+        }
+    }
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn smile() -> Svi {
+        Svi::new(0.02, 0.4, -0.4, 0.05, 0.2).unwrap()
+    }
+
+    fn grid() -> impl Iterator<Item = f64> {
+        (0..=24).map(|i| -0.6 + 0.05 * f64::from(i))
+    }
+
+    /// Quotes read off a raw SVI smile fit back to its parameters.
+    #[test]
+    fn fit_recovers_an_exact_smile() {
+        let expiry = 0.5;
+        let truth = smile();
+        let quotes: Vec<(f64, f64)> = grid().map(|k| (k, truth.implied_vol(k, expiry))).collect();
+        let fit = Svi::fit(expiry, &quotes).unwrap();
+        let pairs = [
+            (fit.a, truth.a),
+            (fit.b, truth.b),
+            (fit.rho, truth.rho),
+            (fit.m, truth.m),
+            (fit.sigma, truth.sigma),
+        ];
+        for (fitted, exact) in pairs {
+            assert!((fitted - exact).abs() < 1e-4, "{fit:?}");
+        }
+    }
+
+    /// Noisy quotes still give a smile inside the constraints that stays
+    /// within the noise of the quotes.
+    #[test]
+    fn fit_of_noisy_quotes_respects_the_constraints() {
+        let expiry = 0.25;
+        let truth = smile();
+        let quotes: Vec<(f64, f64)> = grid()
+            .enumerate()
+            .map(|(i, k)| {
+                let noise = if i % 2 == 0 { 1.0 } else { -1.0 } * 2e-3;
+                (k, truth.implied_vol(k, expiry) + noise)
+            })
+            .collect();
+        let fit = Svi::fit(expiry, &quotes).unwrap();
+        assert!(fit.a >= 0.0 && fit.b * (1.0 + fit.rho.abs()) <= 2.0 + 1e-12);
+        for &(k, vol) in &quotes {
+            assert!((fit.implied_vol(k, expiry) - vol).abs() < 5e-3, "{k}");
+        }
+    }
+
+    /// The vertex variance is the least on the smile.
+    #[test]
+    fn minimum_variance_is_the_vertex() {
+        let svi = smile();
+        let lowest = (0..=20_000)
+            .map(|i| svi.total_variance(-1.0 + 1e-4 * f64::from(i)))
+            .fold(f64::INFINITY, f64::min);
+        assert!((svi.minimum_variance() - lowest).abs() < 1e-9);
+        assert!(Svi::new(-0.1, 0.1, 0.0, 0.0, 0.1).is_err());
+    }
+}
@@ -0,0 +1,55 @@
+//! At-the-money term structure of a surface.
+
+use super::VolSurface;
+use crate::curve::VolCurve;
+use crate::error::{Domain, OptOpsError, Result};
+use crate::float::{to_f64, Float};
+use crate::market::Market;
+use crate::validate;
+
+impl<T: Float> VolSurface<T> {
+    /// Vol at `expiry` struck at the forward from `market`.
+    pub fn atm_vol(&self, expiry: T, market: &Market<T>) -> Result<T> {
+        let forward = market.forward(expiry)?;
+        self.vol(expiry, forward, forward)
+    }
+
+    /// `(expiry, ATM vol)` at each quoted expiry.
+    pub fn atm_term_structure(&self, market: &Market<T>) -> Result<Vec<(T, T)>> {
+        self.smiles()
+            .map(|(expiry, _)| Ok((expiry, self.atm_vol(expiry, market)?)))
+            .collect()
+    }
+
+    /// Vol from `start` to `end` implied by the ATM total variances at the
+    /// two, `sqrt((w(end) - w(start)) / (end - start))`, with `start` zero
+    /// for the spot-starting vol. Fails where the variance falls between
+    /// them, which is calendar arbitrage.
+    pub fn forward_vol(&self, start: T, end: T, market: &Market<T>) -> Result<T> {
+        let start = validate::check("start", start, Domain::NonNegative)?;
+        let end = validate::check("end", end, Domain::Above(to_f64(start)))?;
+        let variance = |time: T| -> Result<T> {
+            if time == T::zero() {
+                return Ok(T::zero());
+            }
+            let vol = self.atm_vol(time, market)?;
+            Ok(vol * vol * time)
+        };
+        let forward_variance = (variance(end)? - variance(start)?) / (end - start);
+        if forward_variance.is_nan() || forward_variance <= T::zero() {
+            return Err(OptOpsError::InvalidParameter {
+                name: "forward variance",
+                value: to_f64(forward_variance),
+                domain: Domain::Positive,
+            });
+        }
+        Ok(forward_variance.sqrt())
+    }
+
+    /// The ATM term structure as a curve of forward vols between the quoted
+    /// expiries, for `Market::with_vol_curve` where the smile is not
+    /// needed.
+    pub fn atm_vol_curve(&self, market: &Market<T>) -> Result<VolCurve<T>> {
+        VolCurve::from_implied(self.atm_term_structure(market)?)
+    }
+}
@@ -0,0 +1,1525 @@
+//! Recombining binomial trees with optimal early exercise.
+
+use crate::american;
+use crate::analytic::{self, ImpliedVolSolver};
+use crate::barrier::Barrier;
+use crate::core::{self, TreeScheme, Triangle};
+use crate::engine::{Greeks, PriceResult, PricingEngine, PricingResult};
+use crate::error::{Domain, OptOpsError, Result};
+use crate::float::{cast, to_f64, Float};
+use crate::instrument::{ExerciseStyle, OptionSpec, OptionType};
+use crate::market::Market;
+use crate::payoff::{Payoff, VanillaCall, VanillaPut};
+use crate::units::Strike;
+use crate::validate;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::time::Instant;
+
+mod export;
+mod implied;
+mod view;
+
+pub use export::MAX_JSON_STEPS;
+pub use implied::ImpliedTree;
+pub use view::{Node, TreeView};
+
+/// Option values at time step `i` after `j` up-moves, indexed `[(i, j)]`.
+pub type ValueFunction<T = f64> = Triangle<T>;
+/// Exercise decisions at time step `i` after `j` up-moves, indexed `[(i, j)]`.
+pub type Policy = Triangle<bool>;
+/// Values, policy and optional European values of one rollback.
+type Surface<T> = (ValueFunction<T>, Policy, Option<ValueFunction<T>>);
+/// `(time, asset price)` of the first exercisable node at each step.
+pub type ExerciseBoundary<T = f64> = Vec<(T, T)>;
+
+/// Everything one rollback produces, bundled for persistence.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TreeSolution<T = f64> {
+    pub value_function: ValueFunction<T>,
+    pub policy: Policy,
+    pub exercise_boundary: ExerciseBoundary<T>,
+}
+
+/// Root value of a tree split into the European value and the premium for
+/// early exercise, both from a single rollback.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PremiumDecomposition<T = f64> {
+    pub price: T,
+    pub european_value: T,
+    pub early_exercise_premium: T,
+}
+
+/// Call and put prices on one strike from a joint rollback, with the
+/// exercise boundary of each.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CallPut<T = f64> {
+    pub call: T,
+    pub put: T,
+    pub call_boundary: ExerciseBoundary<T>,
+    pub put_boundary: ExerciseBoundary<T>,
+}
+
+/// Node times and step factors of one rollback. Node `(i, j)` sits at log
+/// spot `log_offsets[i] + (2j - i) * log_spread`.
+struct Lattice<T> {
+    times: Vec<T>,
+    factors: Vec<core::StepFactors<T>>,
+    /// Forward rate over each step.
+    rates: Vec<T>,
+    /// Variance of the log spot over one step.
+    step_variance: T,
+    log_offsets: Vec<T>,
+    log_spread: T,
+    /// Per step, what the diffused spot is multiplied by: the escrowed spot
+    /// times the share left by proportional dividends paid so far.
+    spot_scale: Vec<T>,
+    /// Per step, the value of cash dividends still to be paid.
+    pending: Vec<T>,
+}
+
+/// What a lattice keeps whatever the vol: node times, the forward rate
+/// over each step and the dividend adjustments of `Lattice`.
+#[derive(Clone)]
+struct Geometry<T> {
+    times: Vec<T>,
+    rates: Vec<T>,
+    spot_scale: Vec<T>,
+    pending: Vec<T>,
+}
+
+impl<T: Float> Lattice<T> {
+    fn num_steps(&self) -> usize {
+        self.factors.len()
+    }
+
+    fn node_price(&self, i: usize, j: usize) -> T {
+        self.spot_at(i, cast((2 * j as i64 - i as i64) as f64))
+    }
+
+    /// Spot at step `i`, `moves` net up-moves from the centre of the layer;
+    /// fractional moves fall between nodes.
+    fn spot_at(&self, i: usize, moves: T) -> T {
+        let diffusion = (self.log_offsets[i] + moves * self.log_spread).exp();
+        self.spot_scale[i] * diffusion + self.pending[i]
+    }
+}
+
+/// Contract terms and lattice resolution; the market is supplied per pricing
+/// call so one tree can be rolled back under many snapshots.
+pub struct OptimalExerciseBinTree<P = Box<dyn Payoff>, T = f64> {
+    payoff: P,
+    expiry: T,
+    num_steps: usize,
+    exercise: ExerciseStyle,
+    scheme: TreeScheme,
+    black_scholes_smoothing: bool,
+    cell_averaging: bool,
+    barrier: Option<Barrier<T>>,
+    #[cfg(feature = "rayon")]
+    parallel_threshold: usize,
+}
+
+// Trees over the default boxed payoff must stay shareable across threads.
+const _: fn() = || {
+    fn assert_send_sync<X: Send + Sync>() {}
+    assert_send_sync::<OptimalExerciseBinTree>();
+};
+
+/// Step-by-step construction of an `OptimalExerciseBinTree`.
+///
+/// `expiry` defaults to one year, `num_steps` to 300 and the scheme to
+/// Cox-Ross-Rubinstein; the payoff must be supplied.
+pub struct OptimalExerciseBinTreeBuilder<P = Box<dyn Payoff>, T = f64> {
+    payoff: Option<P>,
+    expiry: T,
+    num_steps: usize,
+    exercise: ExerciseStyle,
+    scheme: TreeScheme,
+    black_scholes_smoothing: bool,
+    cell_averaging: bool,
+    barrier: Option<Barrier<T>>,
+    #[cfg(feature = "rayon")]
+    parallel_threshold: usize,
+}
+
+impl<P, T: Float> Default for OptimalExerciseBinTreeBuilder<P, T> {
+    fn default() -> Self {
+        OptimalExerciseBinTreeBuilder {
+            payoff: None,
+            expiry: T::one(),
+            num_steps: 300,
+            exercise: ExerciseStyle::American,
+            scheme: TreeScheme::default(),
+            black_scholes_smoothing: false,
+            cell_averaging: false,
+            barrier: None,
+            #[cfg(feature = "rayon")]
+            parallel_threshold: 10_000,
+        }
+    }
+}
+
+impl<P, T: Float> OptimalExerciseBinTreeBuilder<P, T> {
+    pub fn expiry(mut self, expiry: T) -> Self {
+        self.expiry = expiry;
+        self
+    }
+
+    pub fn num_steps(mut self, num_steps: usize) -> Self {
+        self.num_steps = num_steps;
+        self
+    }
+
+    /// Restricts exercise to expiry when set to `European`; American by default.
+    pub fn exercise(mut self, exercise: ExerciseStyle) -> Self {
+        self.exercise = exercise;
+        self
+    }
+
+    /// Chooses the lattice parameterisation; Cox-Ross-Rubinstein by default.
+    pub fn scheme(mut self, scheme: TreeScheme) -> Self {
+        self.scheme = scheme;
+        self
+    }
+
+    /// Replaces the continuation value one step before expiry with the
+    /// Black-Scholes value over the last step (the binomial Black-Scholes
+    /// method), removing the sawtooth in price against `num_steps`. Needs a
+    /// vanilla call or put payoff.
+    pub fn black_scholes_smoothing(mut self, enabled: bool) -> Self {
+        self.black_scholes_smoothing = enabled;
+        self
+    }
+
+    /// Pays the payoff averaged over each terminal node's cell, the spots
+    /// within half a move of the node, instead of its value at the node.
+    /// Restores smooth convergence for digitals, whose jump otherwise lands
+    /// anywhere between two nodes.
+    pub fn cell_averaging(mut self, enabled: bool) -> Self {
+        self.cell_averaging = enabled;
+        self
+    }
+
+    /// Monitors `barrier` at every step. Knock-outs are worth the rebate at
+    /// and beyond the barrier; knock-ins are worth the plain option once the
+    /// barrier is touched and the rebate at expiry otherwise. A barrier
+    /// between two layers biases the price; `Barrier::boyle_lau_steps`
+    /// picks step counts that avoid this.
+    pub fn barrier(mut self, barrier: Barrier<T>) -> Self {
+        self.barrier = Some(barrier);
+        self
+    }
+
+    /// Rolls back levels of at least `nodes` nodes on the rayon thread
+    /// pool; 10,000 by default, below which threading costs more than it
+    /// saves. `usize::MAX` keeps every rollback on the calling thread.
+    #[cfg(feature = "rayon")]
+    pub fn parallel_threshold(mut self, nodes: usize) -> Self {
+        self.parallel_threshold = nodes;
+        self
+    }
+
+    /// Uses an arbitrary exercise payoff, replacing any previously set one.
+    pub fn payoff<Q: Payoff<T>>(self, payoff: Q) -> OptimalExerciseBinTreeBuilder<Q, T> {
+        OptimalExerciseBinTreeBuilder {
+            payoff: Some(payoff),
+            expiry: self.expiry,
+            num_steps: self.num_steps,
+            exercise: self.exercise,
+            scheme: self.scheme,
+            black_scholes_smoothing: self.black_scholes_smoothing,
+            cell_averaging: self.cell_averaging,
+            barrier: self.barrier,
+            #[cfg(feature = "rayon")]
+            parallel_threshold: self.parallel_threshold,
+        }
+    }
+
+    pub fn vanilla_call(self, strike: T) -> OptimalExerciseBinTreeBuilder<VanillaCall<T>, T> {
+        self.payoff(VanillaCall { strike })
+    }
+
+    pub fn vanilla_put(self, strike: T) -> OptimalExerciseBinTreeBuilder<VanillaPut<T>, T> {
+        self.payoff(VanillaPut { strike })
+    }
+
+    /// Takes payoff, expiry, exercise style and any barrier from a contract.
+    pub fn option(self, spec: OptionSpec<T>) -> OptimalExerciseBinTreeBuilder<OptionSpec<T>, T> {
+        let builder = OptimalExerciseBinTreeBuilder {
+            barrier: spec.barrier.or(self.barrier),
+            ..self
+        };
+        builder
+            .expiry(spec.expiry)
+            .exercise(spec.exercise)
+            .payoff(spec)
+    }
+
+    pub fn build(self) -> Result<OptimalExerciseBinTree<P, T>> {
+        let payoff = self.payoff.ok_or(OptOpsError::MissingParameter("payoff"))?;
+        let expiry = validate::positive("expiry", self.expiry)?;
+        validate::check("num_steps", self.num_steps as f64, Domain::AtLeast(1.0))?;
+        let barrier = match self.barrier {
+            Some(barrier) => {
+                validate::positive("barrier", barrier.level)?;
+                validate::finite("rebate", barrier.rebate)?;
+                Some(barrier)
+            }
+            None => None,
+        };
+
+        Ok(OptimalExerciseBinTree {
+            payoff,
+            expiry,
+            num_steps: self.num_steps,
+            exercise: self.exercise,
+            scheme: self.scheme,
+            black_scholes_smoothing: self.black_scholes_smoothing,
+            cell_averaging: self.cell_averaging,
+            barrier,
+            #[cfg(feature = "rayon")]
+            parallel_threshold: self.parallel_threshold,
+        })
+    }
+}
+
+impl<T: Float + 'static> OptimalExerciseBinTree<Box<dyn Payoff<T>>, T> {
+    /// Starts a builder; the float type is inferred from the inputs and
+    /// falls back to `f64` for plain literals.
+    pub fn builder() -> OptimalExerciseBinTreeBuilder<Box<dyn Payoff<T>>, T> {
+        OptimalExerciseBinTreeBuilder::default()
+    }
+}
+
+impl<P: Payoff<T>, T: Float> OptimalExerciseBinTree<P, T> {
+    pub fn payoff(&self) -> &P {
+        &self.payoff
+    }
+
+    pub fn expiry(&self) -> T {
+        self.expiry
+    }
+
+    pub fn num_steps(&self) -> usize {
+        self.num_steps
+    }
+
+    pub fn exercise(&self) -> ExerciseStyle {
+        self.exercise
+    }
+
+    pub fn scheme(&self) -> TreeScheme {
+        self.scheme
+    }
+
+    pub fn black_scholes_smoothing(&self) -> bool {
+        self.black_scholes_smoothing
+    }
+
+    pub fn cell_averaging(&self) -> bool {
+        self.cell_averaging
+    }
+
+    pub fn barrier(&self) -> Option<Barrier<T>> {
+        self.barrier
+    }
+
+    #[cfg(feature = "rayon")]
+    pub fn parallel_threshold(&self) -> usize {
+        self.parallel_threshold
+    }
+
+    /// Average step length; steps are uneven under a vol curve.
+    pub fn dt(&self) -> T {
+        self.expiry / cast(self.num_steps as f64)
+    }
+
+    /// Spot at node `(i, j)`: the escrowed spot diffused along the lattice and
+    /// reduced by the proportional dividends paid so far, plus the value at
+    /// step `i` of cash dividends still to be paid. An invalid market yields
+    /// NaN; the pricing methods report it as an error instead.
+    pub fn state_price(&self, market: &Market<T>, i: usize, j: usize) -> T {
+        self.lattice(market)
+            .map_or_else(|_| T::nan(), |lattice| lattice.node_price(i, j))
+    }
+
+    /// Node times under `market`. A flat vol or a vol surface gives evenly
+    /// spaced steps; a vol curve spaces them so every step carries the same
+    /// variance, keeping the moves constant and the lattice recombining.
+    fn step_times(&self, market: &Market<T>, n: usize) -> Vec<T> {
+        let dt = self.expiry / cast(n as f64);
+        match market
+            .vol_curve
+            .as_ref()
+            .filter(|_| market.vol_surface.is_none())
+        {
+            None => (0..=n).map(|i| cast::<T>(i as f64) * dt).collect(),
+            Some(curve) => {
+                let step_variance = curve.total_variance(self.expiry) / cast(n as f64);
+                let mut times: Vec<T> = (0..n)
+                    .map(|i| curve.time_for_variance(step_variance * cast(i as f64)))
+                    .collect();
+                times.push(self.expiry);
+                times
+            }
+        }
+    }
+
+    fn lattice(&self, market: &Market<T>) -> Result<Lattice<T>> {
+        self.lattice_with(market, self.num_steps)
+    }
+
+    /// Validated `num_steps`-step lattice under `market`: each step
+    /// discounts at the forward rate of the market's discount curve over
+    /// that step and drifts at it less the market's carry yield.
+    fn lattice_with(&self, market: &Market<T>, num_steps: usize) -> Result<Lattice<T>> {
+        market.validate()?;
+        let geometry = self.geometry(market, num_steps)?;
+        self.fit(market, geometry, self.vol(market)?)
+    }
+
+    /// Whole-life vol of the tree under `market`, read off any vol surface
+    /// at the payoff's strike, or at the forward for payoffs without one.
+    fn vol(&self, market: &Market<T>) -> Result<T> {
+        let strike = match self.payoff.strike() {
+            Some(strike) => strike,
+            None => market.forward(self.expiry)?,
+        };
+        market.vol_for(strike, self.expiry)
+    }
+
+    /// The parts of a lattice under `market` that do not depend on the
+    /// level of its vol.
+    fn geometry(&self, market: &Market<T>, num_steps: usize) -> Result<Geometry<T>> {
+        let escrowed = market.escrowed_spot(self.expiry)?;
+        let curve = market.discount_curve(self.expiry)?;
+        let times = self.step_times(market, num_steps);
+        let rates = times
+            .windows(2)
+            .map(|w| curve.forward(w[0], w[1]))
+            .collect();
+        let spot_scale = times
+            .iter()
+            .map(|&time| escrowed * market.dividends.retained_fraction(time))
+            .collect();
+        let pending = times
+            .iter()
+            .map(|&time| market.dividends.present_value(time, self.expiry, &curve))
+            .collect();
+        Ok(Geometry {
+            times,
+            rates,
+            spot_scale,
+            pending,
+        })
+    }
+
+    /// Moves and probabilities over `geometry` for a whole-life `vol`.
+    fn fit(&self, market: &Market<T>, geometry: Geometry<T>, vol: T) -> Result<Lattice<T>> {
+        let Geometry {
+            times,
+            rates,
+            spot_scale,
+            pending,
+        } = geometry;
+        let num_steps = rates.len();
+        if self.scheme == TreeScheme::CoxRossRubinstein {
+            let max_carry = rates
+                .iter()
+                .map(|&rate| (rate - market.carry_yield(rate, vol)).abs())
+                .fold(T::zero(), T::max);
+            validate::tree_steps(num_steps, max_carry, vol, self.expiry)?;
+        }
+
+        let centred = match self.scheme {
+            TreeScheme::LeisenReimer => Some(self.leisen_reimer_factors(market, vol, num_steps)?),
+            _ => None,
+        };
+
+        // Per-step vol chosen so that every step carries the same variance.
+        let step_variance = vol * vol * self.expiry / cast(num_steps as f64);
+        let factors = rates
+            .iter()
+            .copied()
+            .zip(times.windows(2))
+            .map(|(rate, w)| {
+                let dt = w[1] - w[0];
+                let carry_yield = market.carry_yield(rate, vol);
+                let factors = match centred {
+                    // Keep the strike-centred moves and re-solve the
+                    // probability for this step's own drift.
+                    Some(moves) => {
+                        let growth = ((rate - carry_yield) * dt).exp();
+                        core::StepFactors {
+                            up_prob: (growth - moves.down_factor)
+                                / (moves.up_factor - moves.down_factor),
+                            discount: (-rate * dt).exp(),
+                            ..moves
+                        }
+                    }
+                    None => {
+                        let step_vol = (step_variance / dt).sqrt();
+                        core::step_factors(self.scheme, rate, carry_yield, step_vol, dt)
+                    }
+                };
+                validate::check("up_prob", factors.up_prob, Domain::Between(0.0, 1.0))?;
+                Ok(factors)
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let half = cast::<T>(0.5);
+        let log_spread = half * (factors[0].up_factor / factors[0].down_factor).ln();
+        let mut log_offsets = Vec::with_capacity(times.len());
+        log_offsets.push(T::zero());
+        for step in &factors {
+            let last = log_offsets[log_offsets.len() - 1];
+            log_offsets.push(last + half * (step.up_factor * step.down_factor).ln());
+        }
+        Ok(Lattice {
+            times,
+            factors,
+            rates,
+            step_variance,
+            log_offsets,
+            log_spread,
+            spot_scale,
+            pending,
+        })
+    }
+
+    /// Leisen-Reimer moves from whole-life inputs, centred on the payoff's
+    /// strike.
+    fn leisen_reimer_factors(
+        &self,
+        market: &Market<T>,
+        vol: T,
+        num_steps: usize,
+    ) -> Result<core::StepFactors<T>> {
+        let strike = self.payoff.strike().ok_or(OptOpsError::Unsupported {
+            engine: "Leisen-Reimer",
+            reason: "payoffs without a single strike",
+        })?;
+        let spot =
+            market.escrowed_spot(self.expiry)? * market.dividends.retained_fraction(self.expiry);
+        let rate = market.continuous_rate(self.expiry)?;
+        Ok(core::leisen_reimer_factors(
+            spot,
+            strike,
+            rate,
+            market.carry_yield(rate, vol),
+            vol,
+            self.expiry,
+            num_steps,
+        ))
+    }
+
+    /// Payoff at node `(i, j)`, cell-averaged on the terminal layer when
+    /// enabled.
+    fn exercise_value(&self, lattice: &Lattice<T>, i: usize, j: usize) -> T {
+        let time = lattice.times[i];
+        if self.cell_averaging && i == lattice.num_steps() {
+            let moves = cast::<T>((2 * j as i64 - i as i64) as f64);
+            let lower = lattice.spot_at(i, moves - T::one());
+            let upper = lattice.spot_at(i, moves + T::one());
+            return self.payoff.cell_average(time, lower, upper);
+        }
+        self.payoff.value(time, lattice.node_price(i, j))
+    }
+
+    /// `core::induct` over the tree's lattice.
+    #[cfg(not(feature = "rayon"))]
+    fn induct<F, C, Q>(
+        &self,
+        factors: &[core::StepFactors<T>],
+        early_exercise: bool,
+        exercise_value: F,
+        continuation: C,
+        pinned: Q,
+        buffers: core::Buffers<'_, T>,
+    ) -> T
+    where
+        F: Fn(usize, usize) -> T + Sync,
+        C: Fn(usize, usize, T) -> T + Sync,
+        Q: Fn(usize, usize) -> Option<T> + Sync,
+    {
+        core::induct(
+            factors,
+            early_exercise,
+            exercise_value,
+            continuation,
+            pinned,
+            buffers,
+        )
+    }
+
+    /// `core::induct`, with levels of at least `parallel_threshold` nodes
+    /// split across the rayon thread pool, unless an adjoint tape is
+    /// recording on this thread.
+    #[cfg(feature = "rayon")]
+    fn induct<F, C, Q>(
+        &self,
+        factors: &[core::StepFactors<T>],
+        early_exercise: bool,
+        exercise_value: F,
+        continuation: C,
+        pinned: Q,
+        buffers: core::Buffers<'_, T>,
+    ) -> T
+    where
+        F: Fn(usize, usize) -> T + Sync,
+        C: Fn(usize, usize, T) -> T + Sync,
+        Q: Fn(usize, usize) -> Option<T> + Sync,
+    {
+        core::induct_parallel(
+            factors,
+            early_exercise,
+            exercise_value,
+            continuation,
+            pinned,
+            buffers,
+            if crate::adjoint::recording() {
+                usize::MAX
+            } else {
+                self.parallel_threshold
+            },
+        )
+    }
+
+    /// Rolls back under `lattice` into `buffers` and returns the root value.
+    fn rollback(
+        &self,
+        market: &Market<T>,
+        lattice: &Lattice<T>,
+        buffers: core::Buffers<'_, T>,
+    ) -> Result<T> {
+        let early_exercise = self.exercise == ExerciseStyle::American;
+        let exercise_value = |i: usize, j: usize| self.exercise_value(lattice, i, j);
+        let plain = |_: usize, _: usize, v_continue: T| v_continue;
+        if let Some(barrier) = self.barrier {
+            if self.black_scholes_smoothing {
+                return Err(OptOpsError::Unsupported {
+                    engine: "binomial Black-Scholes",
+                    reason: "barrier options",
+                });
+            }
+            return Ok(self.rollback_barrier(lattice, barrier, early_exercise, buffers));
+        }
+        if !self.black_scholes_smoothing {
+            return Ok(self.induct(
+                &lattice.factors,
+                early_exercise,
+                exercise_value,
+                plain,
+                |_, _| None,
+                buffers,
+            ));
+        }
+
+        let (Some(option_type), Some(strike)) = (self.payoff.option_type(), self.payoff.strike())
+        else {
+            return Err(OptOpsError::Unsupported {
+                engine: "binomial Black-Scholes",
+                reason: "payoffs other than vanilla calls and puts",
+            });
+        };
+        let last_step = self.last_step_black_scholes(market, lattice, strike);
+        Ok(self.induct(
+            &lattice.factors,
+            early_exercise,
+            exercise_value,
+            core::penultimate_continuation(lattice.num_steps(), |j| last_step(option_type, j)),
+            |_, _| None,
+            buffers,
+        ))
+    }
+
+    /// Black-Scholes value over the last step of a call or put struck at
+    /// `strike`, from node `j` of the penultimate layer.
+    fn last_step_black_scholes<'a>(
+        &self,
+        market: &'a Market<T>,
+        lattice: &'a Lattice<T>,
+        strike: T,
+    ) -> impl Fn(OptionType, usize) -> T + Sync + 'a {
+        let num_steps = lattice.num_steps();
+        let last = num_steps - 1;
+        let remaining = self.expiry - lattice.times[last];
+        let rate = lattice.rates[last];
+        let vol = (lattice.step_variance / remaining).sqrt();
+        // Strip the dividends paid over the last step out of the node spot.
+        let pending = lattice.pending[last];
+        let retained = lattice.spot_scale[num_steps] / lattice.spot_scale[last];
+        move |option_type, j| {
+            let spot = (lattice.node_price(last, j) - pending) * retained;
+            let dividend_yield = market.carry_yield(rate, vol);
+            core::black_scholes(
+                option_type,
+                spot,
+                strike,
+                rate,
+                dividend_yield,
+                vol,
+                remaining,
+            )
+        }
+    }
+
+    /// Knock-outs pin every node at or beyond the barrier to the rebate.
+    /// Knock-ins roll back the plain option first, then a contract paying
+    /// the rebate at expiry whose nodes at the barrier are pinned to the
+    /// plain value; exercise is only possible once knocked in. The plain
+    /// surface is kept in full, so knock-ins need O(n^2) memory even when
+    /// only the price is wanted.
+    fn rollback_barrier(
+        &self,
+        lattice: &Lattice<T>,
+        barrier: Barrier<T>,
+        early_exercise: bool,
+        buffers: core::Buffers<'_, T>,
+    ) -> T {
+        let hit = |i: usize, j: usize| barrier.is_hit(lattice.node_price(i, j));
+        let exercise_value = |i: usize, j: usize| self.exercise_value(lattice, i, j);
+        let plain = |_: usize, _: usize, v_continue: T| v_continue;
+        if barrier.kind.is_knock_out() {
+            let pinned = |i, j| hit(i, j).then_some(barrier.rebate);
+            return self.induct(
+                &lattice.factors,
+                early_exercise,
+                exercise_value,
+                plain,
+                pinned,
+                buffers,
+            );
+        }
+
+        let core::Buffers {
+            layout,
+            values,
+            policy,
+            european,
+            head,
+        } = buffers;
+        let num_steps = lattice.num_steps();
+        let triangle = core::Layout::Triangle;
+        let len = triangle.len(num_steps);
+        let (mut plain_vf, mut plain_policy) = (vec![T::zero(); len], vec![false; len]);
+        let mut plain_european = european.is_some().then(|| vec![T::zero(); len]);
+        let mut plain_buffers = core::Buffers::triangle(&mut plain_vf, &mut plain_policy);
+        plain_buffers.european = plain_european.as_deref_mut();
+        self.induct(
+            &lattice.factors,
+            early_exercise,
+            exercise_value,
+            plain,
+            |_, _| None,
+            plain_buffers,
+        );
+        let knock_in = |buffers: core::Buffers<'_, T>, plain_values: &[T]| {
+            self.induct(
+                &lattice.factors,
+                false,
+                |_, _| barrier.rebate,
+                plain,
+                |i, j| hit(i, j).then(|| plain_values[triangle.index(i, j)]),
+                buffers,
+            )
+        };
+        let buffers = core::Buffers {
+            layout,
+            values,
+            policy,
+            european: None,
+            head,
+        };
+        let price = knock_in(buffers, &plain_vf);
+        if let (Some(european), Some(plain_european)) = (european, &plain_european) {
+            let mut european_policy = vec![false; layout.len(num_steps)];
+            let buffers = core::Buffers {
+                layout,
+                values: european,
+                policy: &mut european_policy,
+                european: None,
+                head: None,
+            };
+            knock_in(buffers, plain_european);
+        }
+        if layout == triangle {
+            for i in 0..=num_steps {
+                for j in 0..=i {
+                    let k = triangle.index(i, j);
+                    policy[k] = plain_policy[k] && hit(i, j);
+                }
+            }
+        }
+        price
+    }
+
+    /// Rolls back keeping the full value surface and policy, O(n^2) in
+    /// memory; use `price_only` when just the price is needed.
+    pub fn get_opt_vf_and_policy(&self, market: &Market<T>) -> Result<(ValueFunction<T>, Policy)> {
+        let (values, policy, _) = self.surface(market, false)?;
+        Ok((values, policy))
+    }
+
+    /// Value and policy of a full rollback, plus the European values on
+    /// the same lattice when `european` is set.
+    fn surface(&self, market: &Market<T>, european: bool) -> Result<Surface<T>> {
+        let lattice = self.lattice(market)?;
+        let n = self.num_steps;
+        let (mut values, mut policy) = (Triangle::filled(n, T::zero()), Triangle::filled(n, false));
+        let mut european = european.then(|| Triangle::filled(n, T::zero()));
+        let mut buffers = core::Buffers::triangle(values.as_mut_slice(), policy.as_mut_slice());
+        buffers.european = european.as_mut().map(Triangle::as_mut_slice);
+        self.rollback(market, &lattice, buffers)?;
+        Ok((values, policy, european))
+    }
+
+    /// Value at the root, keeping a single layer of the lattice in memory.
+    pub fn price_only(&self, market: &Market<T>) -> Result<T> {
+        let lattice = self.lattice(market)?;
+        let len = core::Layout::Layer.len(self.num_steps);
+        let (mut values, mut policy) = (vec![T::zero(); len], vec![false; len]);
+        self.rollback(
+            market,
+            &lattice,
+            core::Buffers::layer(&mut values, &mut policy),
+        )
+    }
+
+    /// Root price with its European value and early-exercise premium,
+    /// rolling both back together over a single lattice layer.
+    pub fn decompose(&self, market: &Market<T>) -> Result<PremiumDecomposition<T>> {
+        let lattice = self.lattice(market)?;
+        let len = core::Layout::Layer.len(self.num_steps);
+        let (mut values, mut policy) = (vec![T::zero(); len], vec![false; len]);
+        let mut european = vec![T::zero(); len];
+        let buffers = core::Buffers::layer(&mut values, &mut policy).with_european(&mut european);
+        let price = self.rollback(market, &lattice, buffers)?;
+        Ok(PremiumDecomposition {
+            price,
+            european_value: european[0],
+            early_exercise_premium: price - european[0],
+        })
+    }
+
+    /// Root price `(num_steps, price)` at each entry of `steps`, ignoring the
+    /// tree's own `num_steps`. Only the lattice is rebuilt per count; the
+    /// rollback buffers are allocated once. `extrapolate_price` estimates the
+    /// limit from the table.
+    pub fn convergence_table(
+        &self,
+        market: &Market<T>,
+        steps: &[usize],
+    ) -> Result<Vec<(usize, T)>> {
+        let len = core::Layout::Layer.len(steps.iter().copied().max().unwrap_or(0));
+        let (mut values, mut policy) = (vec![T::zero(); len], vec![false; len]);
+        steps
+            .iter()
+            .map(|&num_steps| {
+                validate::check("num_steps", num_steps as f64, Domain::AtLeast(1.0))?;
+                let lattice = self.lattice_with(market, num_steps)?;
+                let buffers = core::Buffers::layer(&mut values, &mut policy);
+                Ok((num_steps, self.rollback(market, &lattice, buffers)?))
+            })
+            .collect()
+    }
+
+    pub fn option_exercise_boundary(
+        &self,
+        market: &Market<T>,
+        policy_seq: &Policy,
+        option_type: OptionType,
+    ) -> ExerciseBoundary<T> {
+        let Ok(lattice) = self.lattice(market) else {
+            return Vec::new();
+        };
+        self.exercise_boundary(&lattice, policy_seq, option_type)
+    }
+
+    /// First exercisable node of each step of `policy_seq` on `lattice`.
+    fn exercise_boundary(
+        &self,
+        lattice: &Lattice<T>,
+        policy_seq: &Policy,
+        option_type: OptionType,
+    ) -> ExerciseBoundary<T> {
+        let mut ex_boundary = Vec::new();
+        for (i, policy) in policy_seq.levels().enumerate() {
+            let mut ex_points = Vec::new();
+            for (j, &action) in policy.iter().enumerate() {
+                if action {
+                    let s = lattice.node_price(i, j);
+                    let payoff = self.payoff.value(lattice.times[i], s);
+                    if payoff > T::zero() {
+                        ex_points.push(j);
+                    }
+                }
+            }
+            if !ex_points.is_empty() {
+                let boundary_j = match option_type {
+                    OptionType::Call => *ex_points.iter().min().unwrap(),
+                    OptionType::Put => *ex_points.iter().max().unwrap(),
+                };
+                let boundary_s = lattice.node_price(i, boundary_j);
+                ex_boundary.push((lattice.times[i], boundary_s));
+            }
+        }
+        ex_boundary
+    }
+
+    /// Call and put struck at the payoff's strike, carried through one
+    /// backward induction together with their exercise boundaries; the
+    /// node spots are computed once for both. Barriers are not supported.
+    pub fn price_call_put(&self, market: &Market<T>) -> Result<CallPut<T>> {
+        let strike = self.payoff.strike().ok_or(OptOpsError::Unsupported {
+            engine: "binomial",
+            reason: "joint call/put pricing of payoffs without a single strike",
+        })?;
+        if self.barrier.is_some() {
+            return Err(OptOpsError::Unsupported {
+                engine: "binomial",
+                reason: "joint call/put pricing of barrier options",
+            });
+        }
+        let (call, put) = (VanillaCall { strike }, VanillaPut { strike });
+        let (call_tree, put_tree) = (self.with_payoff(call), self.with_payoff(put));
+        let lattice = self.lattice(market)?;
+        let n = self.num_steps;
+        let exercise_values = |i: usize, j: usize| {
+            if self.cell_averaging && i == n {
+                return [
+                    call_tree.exercise_value(&lattice, i, j),
+                    put_tree.exercise_value(&lattice, i, j),
+                ];
+            }
+            let (time, spot) = (lattice.times[i], lattice.node_price(i, j));
+            [call.value(time, spot), put.value(time, spot)]
+        };
+        let last_step = self.last_step_black_scholes(market, &lattice, strike);
+        let continuation = |i: usize, j: usize, v_continue: [T; 2]| {
+            if self.black_scholes_smoothing && i + 1 == n {
+                [
+                    last_step(OptionType::Call, j),
+                    last_step(OptionType::Put, j),
+                ]
+            } else {
+                v_continue
+            }
+        };
+
+        let mut call_values = (Triangle::filled(n, T::zero()), Triangle::filled(n, false));
+        let mut put_values = (Triangle::filled(n, T::zero()), Triangle::filled(n, false));
+        let buffers = [
+            core::Buffers::triangle(call_values.0.as_mut_slice(), call_values.1.as_mut_slice()),
+            core::Buffers::triangle(put_values.0.as_mut_slice(), put_values.1.as_mut_slice()),
+        ];
+        let early_exercise = self.exercise == ExerciseStyle::American;
+        let [call_price, put_price] = core::induct_pair(
+            &lattice.factors,
+            early_exercise,
+            exercise_values,
+            continuation,
+            buffers,
+        );
+        Ok(CallPut {
+            call: call_price,
+            put: put_price,
+            call_boundary: call_tree.exercise_boundary(&lattice, &call_values.1, OptionType::Call),
+            put_boundary: put_tree.exercise_boundary(&lattice, &put_values.1, OptionType::Put),
+        })
+    }
+
+    /// Price of a chooser on the tree's strike: at `choose_at` the holder
+    /// picks the call or the put, both expiring with the tree and
+    /// exercisable in its style once chosen. The choice is one more
+    /// decision in the rollback, at the layer nearest `choose_at`, where
+    /// each node keeps the larger of the two values and nothing before it
+    /// can be exercised.
+    pub fn price_chooser(&self, market: &Market<T>, choose_at: T) -> Result<T> {
+        let strike = self.payoff.strike().ok_or(OptOpsError::Unsupported {
+            engine: "binomial",
+            reason: "choosers on payoffs without a single strike",
+        })?;
+        if self.barrier.is_some() {
+            return Err(OptOpsError::Unsupported {
+                engine: "binomial",
+                reason: "chooser barrier options",
+            });
+        }
+        let window = Domain::Between(0.0, to_f64(self.expiry));
+        let choose_at = validate::check("choose_at", choose_at, window)?;
+        let (call, put) = (VanillaCall { strike }, VanillaPut { strike });
+        let (call_tree, put_tree) = (self.with_payoff(call), self.with_payoff(put));
+        let lattice = self.lattice(market)?;
+        let n = self.num_steps;
+        let distance = |i: usize| (lattice.times[i] - choose_at).abs();
+        let choice = (0..=n)
+            .min_by(|&a, &b| distance(a).partial_cmp(&distance(b)).expect("finite times"))
+            .expect("at least one layer");
+        let intrinsic = |i: usize, j: usize| {
+            if self.cell_averaging && i == n {
+                return [
+                    call_tree.exercise_value(&lattice, i, j),
+                    put_tree.exercise_value(&lattice, i, j),
+                ];
+            }
+            let (time, spot) = (lattice.times[i], lattice.node_price(i, j));
+            [call.value(time, spot), put.value(time, spot)]
+        };
+        let early_exercise = self.exercise == ExerciseStyle::American;
+        let chosen = |[call, put]: [T; 2]| {
+            let best = call.max(put);
+            [best, best]
+        };
+        // Nothing is exercised before the choice, which at expiry is taken
+        // on the payoffs themselves.
+        let exercise_values = |i: usize, j: usize| match i.cmp(&choice) {
+            Ordering::Equal if i == n => chosen(intrinsic(i, j)),
+            Ordering::Greater if i == n || early_exercise => intrinsic(i, j),
+            _ => [T::neg_infinity(); 2],
+        };
+        let last_step = self.last_step_black_scholes(market, &lattice, strike);
+        let continuation = |i: usize, j: usize, v_continue: [T; 2]| {
+            let v_continue = if self.black_scholes_smoothing && i + 1 == n && i >= choice {
+                [
+                    last_step(OptionType::Call, j),
+                    last_step(OptionType::Put, j),
+                ]
+            } else {
+                v_continue
+            };
+            if i != choice {
+                return v_continue;
+            }
+            // Each side exercised or held as chosen, then the better side.
+            let [call_now, put_now] = intrinsic(i, j);
+            let held = if early_exercise {
+                [v_continue[0].max(call_now), v_continue[1].max(put_now)]
+            } else {
+                v_continue
+            };
+            chosen(held)
+        };
+
+        let mut call_values = (vec![T::zero(); n + 1], vec![false; n + 1]);
+        let mut put_values = (vec![T::zero(); n + 1], vec![false; n + 1]);
+        let buffers = [
+            core::Buffers::layer(&mut call_values.0, &mut call_values.1),
+            core::Buffers::layer(&mut put_values.0, &mut put_values.1),
+        ];
+        let [price, _] = core::induct_pair(
+            &lattice.factors,
+            true,
+            exercise_values,
+            continuation,
+            buffers,
+        );
+        Ok(price)
+    }
+
+    /// `(price, exercise boundary)` at each of `strikes`, for the tree's
+    /// vanilla call or put struck there. The asset lattice is built once
+    /// and every strike rolled back over it in the same pair of full
+    /// buffers. Leisen-Reimer moves are centred on the strike and a vol
+    /// surface gives each strike its own vol, so those rebuild the lattice
+    /// per strike.
+    pub fn price_strikes(
+        &self,
+        market: &Market<T>,
+        strikes: &[T],
+    ) -> Result<Vec<(T, ExerciseBoundary<T>)>> {
+        let option_type = self.payoff.option_type().ok_or(OptOpsError::Unsupported {
+            engine: "binomial",
+            reason: "strike ladders of payoffs other than vanilla calls and puts",
+        })?;
+        let shared = match self.scheme {
+            _ if market.vol_surface.is_some() => None,
+            TreeScheme::LeisenReimer => None,
+            _ => Some(self.lattice(market)?),
+        };
+        let n = self.num_steps;
+        let (mut values, mut policy) = (Triangle::filled(n, T::zero()), Triangle::filled(n, false));
+        strikes
+            .iter()
+            .map(|&strike| {
+                let strike = validate::positive("strike", strike)?;
+                let spec = OptionSpec::new(option_type, Strike(strike), self.expiry, self.exercise);
+                let tree = self.with_payoff(spec);
+                let rebuilt = match shared {
+                    Some(_) => None,
+                    None => Some(tree.lattice(market)?),
+                };
+                let lattice = shared
+                    .as_ref()
+                    .or(rebuilt.as_ref())
+                    .expect("one lattice is built");
+                let buffers = core::Buffers::triangle(values.as_mut_slice(), policy.as_mut_slice());
+                let price = tree.rollback(market, lattice, buffers)?;
+                Ok((price, tree.exercise_boundary(lattice, &policy, option_type)))
+            })
+            .collect()
+    }
+
+    /// The same tree over `payoff`.
+    fn with_payoff<Q>(&self, payoff: Q) -> OptimalExerciseBinTree<Q, T> {
+        OptimalExerciseBinTree {
+            payoff,
+            expiry: self.expiry,
+            num_steps: self.num_steps,
+            exercise: self.exercise,
+            scheme: self.scheme,
+            black_scholes_smoothing: self.black_scholes_smoothing,
+            cell_averaging: self.cell_averaging,
+            barrier: self.barrier,
+            #[cfg(feature = "rayon")]
+            parallel_threshold: self.parallel_threshold,
+        }
+    }
+
+    /// Runs the rollback and extracts the exercise boundary in one go.
+    pub fn solve(&self, market: &Market<T>, option_type: OptionType) -> Result<TreeSolution<T>> {
+        let (value_function, policy) = self.get_opt_vf_and_policy(market)?;
+        let exercise_boundary = self.option_exercise_boundary(market, &policy, option_type);
+        Ok(TreeSolution {
+            value_function,
+            policy,
+            exercise_boundary,
+        })
+    }
+
+    /// Delta and gamma from the first two layers after the root and theta
+    /// from the layer two steps in, all from the one rollback that prices
+    /// the tree. Only Cox-Ross-Rubinstein's middle node two steps in sits
+    /// at the spot, so theta reads that layer at the spot through the
+    /// quadratic in its three nodes, which removes the drift of the
+    /// middle node under the other schemes. Vega and rho are left unset;
+    /// see `bumped_greeks`.
+    pub fn greeks(&self, market: &Market<T>) -> Result<Greeks<T>> {
+        validate::check("num_steps", self.num_steps as f64, Domain::AtLeast(2.0))?;
+        let lattice = self.lattice(market)?;
+        let len = core::Layout::Layer.len(self.num_steps);
+        let (mut values, mut policy) = (vec![T::zero(); len], vec![false; len]);
+        let mut head = [T::zero(); 6];
+        let buffers = core::Buffers::layer(&mut values, &mut policy).with_head(&mut head);
+        self.rollback(market, &lattice, buffers)?;
+
+        let head = Triangle::from_vec(2, head.to_vec()).expect("three layers fill six slots");
+        let spot = |i, j| lattice.node_price(i, j);
+        let slope = |i, j| (head[(i, j + 1)] - head[(i, j)]) / (spot(i, j + 1) - spot(i, j));
+        let gamma = (slope(2, 1) - slope(2, 0)) / ((spot(2, 2) - spot(2, 0)) * cast(0.5));
+        // Lagrange interpolation of layer two at the root's spot.
+        let root = spot(0, 0);
+        let at_root: T = (0..3)
+            .map(|j| {
+                (0..3).filter(|&k| k != j).fold(head[(2, j)], |weight, k| {
+                    weight * (root - spot(2, k)) / (spot(2, j) - spot(2, k))
+                })
+            })
+            .fold(T::zero(), |sum, term| sum + term);
+        Ok(Greeks {
+            delta: slope(1, 0),
+            gamma,
+            theta: (at_root - head[(0, 0)]) / (lattice.times[2] - lattice.times[0]),
+            vega: None,
+            rho: None,
+        })
+    }
+
+    /// `greeks` plus vega and rho from central differences of the price
+    /// under a one-vol-point and a one-basis-point bump, reusing the tree
+    /// and rebuilding only the lattice. Needs a flat vol and rate; the
+    /// curves and surfaces have no single level to bump.
+    pub fn bumped_greeks(&self, market: &Market<T>) -> Result<Greeks<T>> {
+        let curved = market.vol_curve.is_some() || market.rate_curve.is_some();
+        if curved || market.vol_surface.is_some() {
+            return Err(OptOpsError::Unsupported {
+                engine: "binomial",
+                reason: "bumped vega and rho under vol or rate curves or vol surfaces",
+            });
+        }
+        let greeks = self.greeks(market)?;
+        let central = |bump: T, shifted: &dyn Fn(T) -> Market<T>| -> Result<T> {
+            let up = self.price_only(&shifted(bump))?;
+            let down = self.price_only(&shifted(-bump))?;
+            Ok((up - down) / (bump + bump))
+        };
+        let vega = central(cast(0.01), &|bump| Market {
+            vol: market.vol + bump,
+            ..market.clone()
+        })?;
+        let rho = central(cast(1e-4), &|bump| Market {
+            rate: market.rate + bump,
+            ..market.clone()
+        })?;
+        Ok(Greeks {
+            vega: Some(vega),
+            rho: Some(rho),
+            ..greeks
+        })
+    }
+
+    /// Flat vol at which the tree prices its vanilla call or put at
+    /// `price`, replacing the market's vol, vol curve or surface; listed
+    /// equity options are American, and inverting Black-Scholes on them
+    /// misreads the vol near dividends. Node times, rates and dividend
+    /// adjustments are built once and only the moves and probabilities
+    /// are refitted per trial vol, over one reused layer of buffers.
+    /// Prices are bounded as `AmericanApprox::implied_vol` bounds them.
+    pub fn implied_vol(&self, price: T, market: &Market<T>) -> Result<T> {
+        let engine = "binomial implied vol";
+        let (Some(option_type), Some(strike)) = (self.payoff.option_type(), self.payoff.strike())
+        else {
+            return Err(OptOpsError::Unsupported {
+                engine,
+                reason: "payoffs other than vanilla calls and puts",
+            });
+        };
+        if self.barrier.is_some() {
+            return Err(OptOpsError::Unsupported {
+                engine,
+                reason: "barrier options",
+            });
+        }
+        if market.quanto.is_some() {
+            return Err(OptOpsError::Unsupported {
+                engine,
+                reason: "quanto prices, which need not rise with vol",
+            });
+        }
+        market.validate()?;
+        let price = american::check_american_price(option_type, market.spot, strike, price)?;
+        if price == option_type.intrinsic(market.spot, strike) {
+            return Ok(T::zero());
+        }
+        let flat = Market {
+            vol_curve: None,
+            vol_surface: None,
+            ..market.clone()
+        };
+        let geometry = self.geometry(&flat, self.num_steps)?;
+        let len = core::Layout::Layer.len(self.num_steps);
+        let (mut values, mut policy) = (vec![T::zero(); len], vec![false; len]);
+        ImpliedVolSolver::default().invert(price, market.vol, |vol| {
+            let lattice = self.fit(&flat, geometry.clone(), vol)?;
+            self.rollback(
+                &flat,
+                &lattice,
+                core::Buffers::layer(&mut values, &mut policy),
+            )
+        })
+    }
+
+    /// Rolls back under `market` and exposes every node's time, price,
+    /// continuation and exercise values and policy.
+    pub fn view(&self, market: &Market<T>) -> Result<TreeView<T>> {
+        let (values, policy, european) = self.surface(market, true)?;
+        let european = european.unwrap_or_else(|| values.clone());
+        let lattice = self.lattice(market)?;
+        let nodes = (0..=self.num_steps)
+            .flat_map(|i| (0..=i).map(move |j| (i, j)))
+            .map(|(i, j)| {
+                let time = lattice.times[i];
+                let asset_price = lattice.node_price(i, j);
+                let continuation_value = lattice.factors.get(i).map(|step| {
+                    step.discount
+                        * (step.up_prob * values[(i + 1, j + 1)]
+                            + (T::one() - step.up_prob) * values[(i + 1, j)])
+                });
+                Node {
+                    step: i,
+                    index: j,
+                    time,
+                    asset_price,
+                    value: values[(i, j)],
+                    european_value: european[(i, j)],
+                    continuation_value,
+                    exercise_value: self.exercise_value(&lattice, i, j),
+                    exercise: policy[(i, j)],
+                }
+            })
+            .collect();
+        let levels = Triangle::from_vec(self.num_steps, nodes).expect("one node per lattice slot");
+        Ok(TreeView::new(levels))
+    }
+
+    /// Black-Scholes-Merton value of `spec` under `market`, for comparison
+    /// with the tree. It carries the same continuous dividend or borrow
+    /// yield and discrete dividends as the lattice, so the two stay
+    /// comparable for dividend-paying stocks.
+    pub fn european_price(&self, market: &Market<T>, spec: &OptionSpec<T>) -> Result<T> {
+        analytic::black_scholes_price(spec, market)
+    }
+}
+
+/// Converged price estimated from a `convergence_table`: the intercept of a
+/// least-squares fit of price against `1 / num_steps`, the leading error
+/// term of binomial trees. Fitting every row rather than extrapolating from
+/// the last two damps the odd-even sawtooth of Cox-Ross-Rubinstein prices.
+/// `None` without at least two distinct step counts.
+pub fn extrapolate_price<T: Float>(table: &[(usize, T)]) -> Option<T> {
+    let count = cast::<T>(table.len() as f64);
+    let inverse = |num_steps: usize| T::one() / cast(num_steps as f64);
+    let mean_x = table
+        .iter()
+        .fold(T::zero(), |sum, &(n, _)| sum + inverse(n))
+        / count;
+    let mean_y = table.iter().fold(T::zero(), |sum, &(_, price)| sum + price) / count;
+    let (sxx, sxy) = table
+        .iter()
+        .fold((T::zero(), T::zero()), |(sxx, sxy), &(n, price)| {
+            let dx = inverse(n) - mean_x;
+            (sxx + dx * dx, sxy + dx * (price - mean_y))
+        });
+    if table.len() < 2 || sxx <= T::zero() {
+        return None;
+    }
+    Some(mean_y - sxy / sxx * mean_x)
+}
+
+/// Prices vanilla contracts by building a fresh tree per call.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BinomialEngine {
+    pub num_steps: usize,
+    /// Return the full value surface in `PricingResult::value_function`;
+    /// without it pricing keeps a single lattice layer in memory.
+    pub keep_value_function: bool,
+    #[serde(default, skip_serializing_if = "TreeScheme::is_default")]
+    pub scheme: TreeScheme,
+    /// Binomial Black-Scholes smoothing of the last step.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub black_scholes_smoothing: bool,
+    /// Also price at `2 * num_steps` and extrapolate `2 P(2n) - P(n)`,
+    /// cancelling the leading `1/n` error term. Needs smooth convergence, so
+    /// pair it with `black_scholes_smoothing` (BBSR) or Leisen-Reimer.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub richardson: bool,
+    /// Cell-average the payoff on the terminal layer.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub cell_averaging: bool,
+    /// Round each step count up to the next Boyle-Lau count for the
+    /// instrument's barrier, so the barrier sits just inside a layer.
+    /// Ignored for contracts without a barrier.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub align_barrier: bool,
+    /// Fill `PricingResult::greeks` from the tree at `num_steps`, with
+    /// bumped vega and rho.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub greeks: bool,
+}
+
+impl Default for BinomialEngine {
+    fn default() -> Self {
+        BinomialEngine {
+            num_steps: 300,
+            keep_value_function: false,
+            scheme: TreeScheme::default(),
+            black_scholes_smoothing: false,
+            richardson: false,
+            cell_averaging: false,
+            align_barrier: false,
+            greeks: false,
+        }
+    }
+}
+
+impl BinomialEngine {
+    /// Leisen-Reimer engine at an odd `num_steps`. European prices converge
+    /// as the square of the steps; early exercise brings American prices
+    /// back to first order, so an at-the-money put needs about a hundred
+    /// steps to come within a cent.
+    pub fn leisen_reimer(num_steps: usize) -> Self {
+        BinomialEngine {
+            num_steps,
+            scheme: TreeScheme::LeisenReimer,
+            ..BinomialEngine::default()
+        }
+    }
+
+    /// Binomial Black-Scholes with Richardson extrapolation over `num_steps`
+    /// and `2 * num_steps`.
+    pub fn bbsr(num_steps: usize) -> Self {
+        BinomialEngine {
+            num_steps,
+            black_scholes_smoothing: true,
+            richardson: true,
+            ..BinomialEngine::default()
+        }
+    }
+
+    fn tree(
+        &self,
+        instrument: &OptionSpec,
+        num_steps: usize,
+    ) -> Result<OptimalExerciseBinTree<OptionSpec>> {
+        OptimalExerciseBinTree::builder()
+            .num_steps(num_steps)
+            .scheme(self.scheme)
+            .black_scholes_smoothing(self.black_scholes_smoothing)
+            .cell_averaging(self.cell_averaging)
+            .option(*instrument)
+            .build()
+    }
+
+    fn steps_for(&self, instrument: &OptionSpec, market: &Market, num_steps: usize) -> usize {
+        match instrument.barrier {
+            Some(barrier) if self.align_barrier => barrier.boyle_lau_steps(
+                market.spot,
+                market.effective_vol(instrument.expiry),
+                instrument.expiry,
+                num_steps,
+            ),
+            _ => num_steps,
+        }
+    }
+
+    /// Price at `num_steps`, the early-exercise premium and, when
+    /// `keep_value_function` is set, the value surface. The premium is
+    /// measured against the European value rolled back on the same lattice
+    /// so the discretisation error cancels.
+    fn solve(
+        &self,
+        instrument: &OptionSpec,
+        market: &Market,
+        num_steps: usize,
+    ) -> Result<(f64, f64, Option<ValueFunction>)> {
+        let tree = self.tree(instrument, num_steps)?;
+        if !self.keep_value_function {
+            let split = tree.decompose(market)?;
+            return Ok((split.price, split.early_exercise_premium, None));
+        }
+        let (values, _, european) = tree.surface(market, true)?;
+        let price = *values.root();
+        let premium = european.map_or(0.0, |european| price - european.root());
+        Ok((price, premium, Some(values)))
+    }
+}
+
+impl PricingEngine for BinomialEngine {
+    /// With `richardson` set, `num_steps` and the value function in the
+    /// result are those of the finer tree, and `error_estimate` is the gap
+    /// between the two resolutions.
+    fn price(&self, instrument: &OptionSpec, market: &Market) -> PriceResult {
+        let start = Instant::now();
+        let mut num_steps = self.steps_for(instrument, market, self.num_steps);
+        let (mut price, mut premium, mut vf_seq) = self.solve(instrument, market, num_steps)?;
+        let mut error_estimate = None;
+        if self.richardson {
+            num_steps = self.steps_for(instrument, market, 2 * self.num_steps);
+            let (fine, fine_premium, fine_vf) = self.solve(instrument, market, num_steps)?;
+            error_estimate = Some((fine - price).abs());
+            price = 2.0 * fine - price;
+            premium = 2.0 * fine_premium - premium;
+            vf_seq = fine_vf;
+        }
+
+        let greeks = if self.greeks {
+            let num_steps = self.steps_for(instrument, market, self.num_steps);
+            Some(self.tree(instrument, num_steps)?.bumped_greeks(market)?)
+        } else {
+            None
+        };
+
+        let mut result = PricingResult::new("Binomial", price, start.elapsed());
+        result.early_exercise_premium = Some(premium);
+        result.num_steps = Some(num_steps);
+        result.error_estimate = error_estimate;
+        result.greeks = greeks;
+        result.value_function = vf_seq;
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::units::{Rate, Spot, Vol};
+
+    fn market() -> Market {
+        Market::new(Spot(100.0), Rate(0.05), Vol(0.2)).with_dividend_yield(Rate(0.02))
+    }
+
+    fn price(spec: OptionSpec, scheme: TreeScheme, num_steps: usize) -> f64 {
+        OptimalExerciseBinTree::builder()
+            .num_steps(num_steps)
+            .scheme(scheme)
+            .option(spec)
+            .build()
+            .unwrap()
+            .price_only(&market())
+            .unwrap()
+    }
+
+    /// CRR errors shrink roughly as `1 / n`; Leisen-Reimer's as `1 / n^2`.
+    #[test]
+    fn european_prices_converge_to_black_scholes() {
+        for option_type in [OptionType::Call, OptionType::Put] {
+            for strike in [90.0, 100.0, 110.0] {
+                let spec = OptionSpec::european(option_type, Strike(strike), 1.0);
+                let exact = analytic::black_scholes_price(&spec, &market()).unwrap();
+                let errors: Vec<f64> = [100, 400, 1600]
+                    .map(|n| (price(spec, TreeScheme::default(), n) - exact).abs())
+                    .to_vec();
+                assert!(errors[2] < 5e-3, "{option_type:?} {strike}: {errors:?}");
+                assert!(
+                    errors[2] < errors[0],
+                    "{option_type:?} {strike}: {errors:?}"
+                );
+                let leisen_reimer = price(spec, TreeScheme::LeisenReimer, 201);
+                assert!(
+                    (leisen_reimer - exact).abs() < 1e-4,
+                    "{leisen_reimer} vs {exact}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn american_prices_bound_the_european_and_intrinsic() {
+        for option_type in [OptionType::Call, OptionType::Put] {
+            for strike in [80.0, 100.0, 120.0] {
+                let american = OptionSpec::american(option_type, Strike(strike), 1.0);
+                let european = OptionSpec::european(option_type, Strike(strike), 1.0);
+                let (american, european) = (
+                    price(american, TreeScheme::LeisenReimer, 501),
+                    price(european, TreeScheme::LeisenReimer, 501),
+                );
+                assert!(american >= european - 1e-12);
+                assert!(american >= option_type.intrinsic(100.0, strike));
+            }
+        }
+    }
+
+    /// Theta read off every scheme's tree matches Black-Scholes, however
+    /// far the scheme's middle node two steps in drifts from the spot.
+    #[test]
+    fn tree_theta_matches_black_scholes_for_every_scheme() {
+        let market: Market =
+            Market::new(Spot(100.0), Rate(0.05), Vol(0.25)).with_dividend_yield(Rate(0.02));
+        let spec = OptionSpec::european(OptionType::Put, Strike(105.0), 0.75);
+        let exact = analytic::greeks(&spec, &market).unwrap();
+        for scheme in [
+            TreeScheme::CoxRossRubinstein,
+            TreeScheme::JarrowRudd,
+            TreeScheme::Tian,
+            TreeScheme::LeisenReimer,
+        ] {
+            let greeks = OptimalExerciseBinTree::builder()
+                .num_steps(2001)
+                .scheme(scheme)
+                .option(spec)
+                .build()
+                .unwrap()
+                .greeks(&market)
+                .unwrap();
+            assert!(
+                (greeks.theta - exact.theta).abs() < 2e-3,
+                "{scheme:?}: {} vs {}",
+                greeks.theta,
+                exact.theta
+            );
+            assert!((greeks.delta - exact.delta).abs() < 2e-3, "{scheme:?}");
+            assert!((greeks.gamma - exact.gamma).abs() < 2e-3, "{scheme:?}");
+        }
+    }
+}
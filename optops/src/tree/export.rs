@@ -0,0 +1,140 @@
+//! Graphviz DOT and JSON renderings of a solved lattice, meant for small
+//! trees in teaching and debugging.
+
+use super::view::{Node, TreeView};
+use crate::error::{persistence, Domain, Result};
+use crate::float::{to_f64, Float};
+use crate::validate;
+use serde::Serialize;
+use std::fmt::Write;
+
+/// Most steps `TreeView::to_json` exports: the nested form repeats every
+/// shared successor, so it doubles in size with each step.
+pub const MAX_JSON_STEPS: usize = 12;
+
+/// A node of the JSON export with the two nodes it moves to nested inside
+/// it, down to expiry.
+#[derive(Serialize)]
+struct Branch<'a, T> {
+    #[serde(flatten)]
+    node: &'a Node<T>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    children: Vec<Branch<'a, T>>,
+}
+
+impl<T: Float> TreeView<T> {
+    /// The lattice as a left-to-right Graphviz digraph, one column per step.
+    /// Each node is labelled with its asset price, value and continuation
+    /// value; nodes where the policy exercises for a positive payoff are
+    /// filled.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph lattice {\n    rankdir=LR;\n    node [shape=box];\n");
+        for level in self.levels() {
+            let mut rank = String::from("    { rank=same;");
+            for node in level {
+                let id = node_id(node.step, node.index);
+                let continuation = node
+                    .continuation_value
+                    .map_or_else(|| "-".to_string(), |value| format!("{:.4}", to_f64(value)));
+                let style = if node.exercise && node.exercise_value > T::zero() {
+                    ", style=filled, fillcolor=lightcoral"
+                } else {
+                    ""
+                };
+                // Writing to a String cannot fail.
+                let _ = writeln!(
+                    dot,
+                    "    {id} [label=\"S={:.4}\\nV={:.4}\\nC={continuation}\"{style}];",
+                    to_f64(node.asset_price),
+                    to_f64(node.value),
+                );
+                if node.step < self.num_steps() {
+                    for next in [node.index, node.index + 1] {
+                        let _ = writeln!(dot, "    {id} -> {};", node_id(node.step + 1, next));
+                    }
+                }
+                rank.push_str(&format!(" {id};"));
+            }
+            dot.push_str(&rank);
+            dot.push_str(" }\n");
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+impl<T: Float + Serialize> TreeView<T> {
+    /// The lattice as a JSON tree: the root node, with its down and up
+    /// successors as `children`, each holding its own in turn; nodes at
+    /// expiry have none. Recombining paths are written out once per path,
+    /// so trees over `MAX_JSON_STEPS` steps are rejected.
+    pub fn to_json(&self) -> Result<String> {
+        validate::check(
+            "num_steps",
+            self.num_steps() as f64,
+            Domain::Between(0.0, MAX_JSON_STEPS as f64),
+        )?;
+        serde_json::to_string_pretty(&self.branch(0, 0)).map_err(persistence)
+    }
+
+    fn branch(&self, step: usize, index: usize) -> Branch<'_, T> {
+        let children = if step < self.num_steps() {
+            vec![
+                self.branch(step + 1, index),
+                self.branch(step + 1, index + 1),
+            ]
+        } else {
+            Vec::new()
+        };
+        Branch {
+            node: self.node(step, index).expect("node inside the lattice"),
+            children,
+        }
+    }
+}
+
+fn node_id(step: usize, index: usize) -> String {
+    format!("n{step}_{index}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::market::Market;
+    use crate::tree::OptimalExerciseBinTree;
+    use crate::units::{Rate, Spot, Vol};
+
+    fn view(num_steps: usize) -> TreeView {
+        let market: Market = Market::new(Spot(100.0), Rate(0.05), Vol(0.2));
+        OptimalExerciseBinTree::builder()
+            .num_steps(num_steps)
+            .vanilla_put(100.0)
+            .build()
+            .unwrap()
+            .view(&market)
+            .unwrap()
+    }
+
+    /// Every path from the root is nested out to expiry.
+    #[test]
+    fn json_nests_each_node_under_its_predecessor() {
+        let json: serde_json::Value = serde_json::from_str(&view(2).to_json().unwrap()).unwrap();
+        assert_eq!(json["step"], 0);
+        let children = json["children"].as_array().unwrap();
+        assert_eq!(children.len(), 2);
+        for (index, child) in children.iter().enumerate() {
+            assert_eq!(child["step"], 1);
+            assert_eq!(child["index"], index);
+            let leaves = child["children"].as_array().unwrap();
+            assert_eq!(leaves[0]["index"], index);
+            assert_eq!(leaves[1]["index"], index + 1);
+            assert!(leaves.iter().all(|leaf| leaf.get("children").is_none()));
+        }
+    }
+
+    #[test]
+    fn json_rejects_large_trees() {
+        assert!(view(MAX_JSON_STEPS).to_json().is_ok());
+        assert!(view(MAX_JSON_STEPS + 1).to_json().is_err());
+    }
+}
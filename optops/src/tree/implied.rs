@@ -0,0 +1,265 @@
+//! Derman-Kani implied binomial trees, whose node spots and probabilities
+//! reprice a smile of European options instead of assuming one flat vol.
+
+use super::{Policy, ValueFunction};
+use crate::core::{self, Triangle};
+use crate::error::{Domain, OptOpsError, Result};
+use crate::float::{cast, to_f64, Float};
+use crate::instrument::{ExerciseStyle, OptionType};
+use crate::market::Market;
+use crate::payoff::Payoff;
+use crate::validate;
+
+/// Recombining tree with its own spot and up-probability at every node,
+/// fitted one step at a time to European options struck at the spots of a
+/// layer and expiring one step later (Derman and Kani, 1994).
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImpliedTree<T = f64> {
+    times: Vec<T>,
+    spots: Triangle<T>,
+    /// Value today of one unit paid at each node.
+    state_prices: Triangle<T>,
+    /// Up-probability at each node before expiry.
+    up_probs: Triangle<T>,
+    /// Discount factor over one step.
+    discount: T,
+}
+
+impl<T: Float> ImpliedTree<T> {
+    /// Fits `num_steps` even steps to `expiry`, where `price(option_type,
+    /// strike, expiry)` quotes European options: calls fit the nodes above
+    /// the centre of each layer and puts those below. Quotes are needed at
+    /// arbitrary strikes, so interpolating a quoted grid is left to `price`;
+    /// `from_implied_vols` does it through Black-Scholes.
+    ///
+    /// A fitted node that would leave a parent's forward outside its two
+    /// children admits arbitrage and is overridden, as Derman and Kani
+    /// suggest, to keep the parent layer's log spacing; outer nodes for
+    /// which even that fails move between the neighbouring forwards.
+    pub fn derman_kani<F>(market: &Market<T>, expiry: T, num_steps: usize, price: F) -> Result<Self>
+    where
+        F: Fn(OptionType, T, T) -> T,
+    {
+        market.validate()?;
+        let expiry = validate::positive("expiry", expiry)?;
+        validate::check("num_steps", num_steps as f64, Domain::AtLeast(1.0))?;
+        if !market.dividends.is_empty() {
+            return Err(OptOpsError::Unsupported {
+                engine: "implied tree",
+                reason: "discrete dividends",
+            });
+        }
+        let dt = expiry / cast(num_steps as f64);
+        let rate = market.continuous_rate(expiry)?;
+        let growth = ((rate - market.carry_yield(rate, market.effective_vol(expiry))) * dt).exp();
+        let discount = (-rate * dt).exp();
+        let times: Vec<T> = (0..=num_steps).map(|i| cast::<T>(i as f64) * dt).collect();
+
+        let (mut level, mut lambda) = (vec![market.spot], vec![T::one()]);
+        let (mut spots, mut state_prices) = (level.clone(), lambda.clone());
+        let mut up_probs = Vec::with_capacity(num_steps * (num_steps + 1) / 2);
+        for n in 0..num_steps {
+            let forward: Vec<T> = level.iter().map(|&s| s * growth).collect();
+            let maturity = times[n + 1];
+            // What a call (put) struck at parent `i` pays one step on through
+            // parent `i` itself: the quote, undiscounted, less the forward
+            // payoff of the parents above (below) it.
+            let call_excess = |i: usize| {
+                let beyond = (i + 1..=n).fold(T::zero(), |sum, j| {
+                    sum + lambda[j] * (forward[j] - level[i])
+                });
+                price(OptionType::Call, level[i], maturity) / discount - beyond
+            };
+            let put_excess = |i: usize| {
+                let beyond = (0..i).fold(T::zero(), |sum, j| {
+                    sum + lambda[j] * (level[i] - forward[j])
+                });
+                price(OptionType::Put, level[i], maturity) / discount - beyond
+            };
+
+            let mut next = vec![T::zero(); n + 2];
+            let centre = n.div_ceil(2);
+            if n % 2 == 0 {
+                // An odd layer: its centre spawns a pair straddling it with
+                // the centre as their geometric mean.
+                let c = n / 2;
+                let (s, excess) = (level[c], call_excess(c));
+                if n == 0 {
+                    let lower = to_f64(discount * (forward[0] - s).max(T::zero()));
+                    let upper = to_f64(discount * forward[0]);
+                    let bounds = Domain::Between(lower, upper);
+                    validate::check("call price", excess * discount, bounds)?;
+                }
+                let mut up = s * (excess + lambda[c] * s) / (lambda[c] * forward[c] - excess);
+                let straddles = |up: T| {
+                    let down = s * s / up;
+                    down < forward[c]
+                        && forward[c] < up
+                        && (c == 0 || forward[c - 1] < down)
+                        && (c == n || up < forward[c + 1])
+                };
+                if n > 0 && !straddles(up) {
+                    up = s * (level[c + 1] / level[c - 1]).sqrt();
+                }
+                next[c + 1] = up;
+                next[c] = s * s / up;
+            } else {
+                // An even layer: the next one is centred on today's spot.
+                next[centre] = market.spot;
+            }
+
+            let first_up = if n % 2 == 0 { centre + 1 } else { centre };
+            for i in first_up..=n {
+                let (excess, gap) = (call_excess(i), lambda[i] * (forward[i] - next[i]));
+                let up = (excess * next[i] - gap * level[i]) / (excess - gap);
+                let ceiling = if i < n { forward[i + 1] } else { T::infinity() };
+                let admissible = |up: T| forward[i] < up && up < ceiling;
+                let spaced = if i < n {
+                    next[i] * level[i + 1] / level[i]
+                } else {
+                    next[i] * level[i] / level[i - 1]
+                };
+                next[i + 1] = if admissible(up) {
+                    up
+                } else if admissible(spaced) {
+                    spaced
+                } else if i < n {
+                    (forward[i] * forward[i + 1]).sqrt()
+                } else {
+                    forward[i] * forward[i] / next[i]
+                };
+            }
+            for i in (0..centre).rev() {
+                let (excess, gap) = (put_excess(i), lambda[i] * (next[i + 1] - forward[i]));
+                let down = (excess * next[i + 1] - gap * level[i]) / (excess - gap);
+                let floor = if i > 0 { forward[i - 1] } else { T::zero() };
+                let admissible = |down: T| floor < down && down < forward[i];
+                let spaced = if i > 0 {
+                    next[i + 1] * level[i - 1] / level[i]
+                } else {
+                    next[1] * level[0] / level[1]
+                };
+                next[i] = if admissible(down) {
+                    down
+                } else if admissible(spaced) {
+                    spaced
+                } else if i > 0 {
+                    (forward[i - 1] * forward[i]).sqrt()
+                } else {
+                    forward[0] * forward[0] / next[1]
+                };
+            }
+
+            let probs: Vec<T> = (0..=n)
+                .map(|i| (forward[i] - next[i]) / (next[i + 1] - next[i]))
+                .collect();
+            lambda = (0..=n + 1)
+                .map(|k| {
+                    let from_below = if k > 0 {
+                        lambda[k - 1] * probs[k - 1]
+                    } else {
+                        T::zero()
+                    };
+                    let from_above = if k <= n {
+                        lambda[k] * (T::one() - probs[k])
+                    } else {
+                        T::zero()
+                    };
+                    discount * (from_below + from_above)
+                })
+                .collect();
+            up_probs.extend_from_slice(&probs);
+            spots.extend_from_slice(&next);
+            state_prices.extend_from_slice(&lambda);
+            level = next;
+        }
+
+        Ok(ImpliedTree {
+            times,
+            spots: Triangle::from_vec(num_steps, spots).expect("one spot per node"),
+            state_prices: Triangle::from_vec(num_steps, state_prices).expect("one price per node"),
+            up_probs: Triangle::from_vec(num_steps - 1, up_probs).expect("one per inner node"),
+            discount,
+        })
+    }
+
+    /// `derman_kani` on Black-Scholes prices of `vol(strike, expiry)`, the
+    /// implied vol of the smile at that strike and expiry.
+    pub fn from_implied_vols<V>(
+        market: &Market<T>,
+        expiry: T,
+        num_steps: usize,
+        vol: V,
+    ) -> Result<Self>
+    where
+        V: Fn(T, T) -> T,
+    {
+        let rate = market.continuous_rate(expiry)?;
+        let spot = market.spot;
+        ImpliedTree::derman_kani(market, expiry, num_steps, |option_type, strike, expiry| {
+            let vol = vol(strike, expiry);
+            let dividend_yield = market.carry_yield(rate, vol);
+            core::black_scholes(option_type, spot, strike, rate, dividend_yield, vol, expiry)
+        })
+    }
+
+    pub fn num_steps(&self) -> usize {
+        self.spots.num_steps()
+    }
+
+    /// Node times, evenly spaced from zero to expiry.
+    pub fn times(&self) -> &[T] {
+        &self.times
+    }
+
+    /// Asset price at node `(i, j)`, indexed like a value function.
+    pub fn spots(&self) -> &Triangle<T> {
+        &self.spots
+    }
+
+    /// Arrow-Debreu price of each node; a layer's prices sum to the
+    /// discount factor to its time.
+    pub fn state_prices(&self) -> &Triangle<T> {
+        &self.state_prices
+    }
+
+    /// Risk-neutral probability of moving up from node `(i, j)`, `i` before
+    /// expiry.
+    pub fn up_prob(&self, i: usize, j: usize) -> T {
+        self.up_probs[(i, j)]
+    }
+
+    /// Rolls `payoff` back over the implied lattice, exercising early when
+    /// `exercise` is American, keeping the full value surface and policy.
+    pub fn rollback<P: Payoff<T>>(
+        &self,
+        payoff: &P,
+        exercise: ExerciseStyle,
+    ) -> (ValueFunction<T>, Policy) {
+        let n = self.num_steps();
+        let (mut values, mut policy) = (Triangle::filled(n, T::zero()), Triangle::filled(n, false));
+        for i in (0..=n).rev() {
+            for j in 0..=i {
+                let v_exercise = payoff.value(self.times[i], self.spots[(i, j)]);
+                let k = Triangle::<T>::offset(i, j);
+                if i == n {
+                    values.as_mut_slice()[k] = v_exercise.max(T::zero());
+                    policy.as_mut_slice()[k] = v_exercise >= T::zero();
+                    continue;
+                }
+                let (down, up) = (values[(i + 1, j)], values[(i + 1, j + 1)]);
+                let p = self.up_prob(i, j);
+                let v_continue = self.discount * (p * up + (T::one() - p) * down);
+                let exercise = exercise == ExerciseStyle::American && v_exercise >= v_continue;
+                values.as_mut_slice()[k] = if exercise { v_exercise } else { v_continue };
+                policy.as_mut_slice()[k] = exercise;
+            }
+        }
+        (values, policy)
+    }
+
+    /// Root value of `payoff` on the implied lattice.
+    pub fn price<P: Payoff<T>>(&self, payoff: &P, exercise: ExerciseStyle) -> T {
+        *self.rollback(payoff, exercise).0.root()
+    }
+}
@@ -0,0 +1,71 @@
+//! Node-level inspection of a rolled-back lattice.
+
+use crate::core::Triangle;
+use crate::float::Float;
+use serde::{Deserialize, Serialize};
+
+/// One lattice node after backward induction.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Node<T = f64> {
+    /// Time step `i`.
+    pub step: usize,
+    /// Number of up-moves `j` taken to reach the node.
+    pub index: usize,
+    pub time: T,
+    pub asset_price: T,
+    /// Option value: the larger of exercise and continuation where allowed.
+    pub value: T,
+    /// Value of the same contract without early exercise, rolled back on the
+    /// same lattice.
+    pub european_value: T,
+    /// Discounted expected value of holding on; `None` at expiry.
+    pub continuation_value: Option<T>,
+    pub exercise_value: T,
+    /// Whether the optimal policy exercises here.
+    pub exercise: bool,
+}
+
+impl<T: Float> Node<T> {
+    /// What the right to exercise before expiry is worth at this node.
+    pub fn early_exercise_premium(&self) -> T {
+        self.value - self.european_value
+    }
+}
+
+/// Every node of a solved tree, laid out like the value function.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TreeView<T = f64> {
+    levels: Triangle<Node<T>>,
+}
+
+impl<T: Float> TreeView<T> {
+    pub(crate) fn new(levels: Triangle<Node<T>>) -> Self {
+        TreeView { levels }
+    }
+
+    pub fn num_steps(&self) -> usize {
+        self.levels.num_steps()
+    }
+
+    pub fn root(&self) -> &Node<T> {
+        self.levels.root()
+    }
+
+    pub fn node(&self, i: usize, j: usize) -> Option<&Node<T>> {
+        self.levels.get(i, j)
+    }
+
+    /// All `i + 1` nodes at step `i`, ordered by up-move count.
+    pub fn level(&self, i: usize) -> Option<&[Node<T>]> {
+        (i <= self.num_steps()).then(|| self.levels.level(i))
+    }
+
+    pub fn levels(&self) -> impl Iterator<Item = &[Node<T>]> {
+        self.levels.levels()
+    }
+
+    /// Nodes step by step from the root, each level bottom to top.
+    pub fn iter_nodes(&self) -> impl Iterator<Item = &Node<T>> {
+        self.levels.as_slice().iter()
+    }
+}
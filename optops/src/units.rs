@@ -0,0 +1,74 @@
+//! Newtypes for the scalar inputs that are easy to swap by accident.
+//!
+//! Constructors such as `Market::new` take these instead of bare floats, so
+//! passing a volatility where a rate is expected fails to compile.
+
+use crate::float::{cast, Float};
+use serde::{Deserialize, Serialize};
+
+macro_rules! quantity {
+    ($(#[$doc:meta])* $name:ident) => {
+        $(#[$doc])*
+        #[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Default, Serialize, Deserialize)]
+        #[serde(transparent)]
+        pub struct $name<T = f64>(pub T);
+
+        impl<T: Float> $name<T> {
+            pub fn new(value: T) -> Self {
+                $name(value)
+            }
+
+            pub fn value(self) -> T {
+                self.0
+            }
+        }
+
+        impl From<$name<f64>> for f64 {
+            fn from(quantity: $name<f64>) -> f64 {
+                quantity.0
+            }
+        }
+
+        impl From<$name<f32>> for f32 {
+            fn from(quantity: $name<f32>) -> f32 {
+                quantity.0
+            }
+        }
+    };
+}
+
+quantity!(
+    /// Price of the underlying.
+    Spot
+);
+quantity!(
+    /// Strike price of an option.
+    Strike
+);
+quantity!(
+    /// Continuously compounded annual rate as a decimal (0.05 = 5%).
+    Rate
+);
+quantity!(
+    /// Annualised volatility as a decimal (0.25 = 25%).
+    Vol
+);
+
+impl<T: Float> Rate<T> {
+    /// `Rate::percent(5.0)` is 0.05.
+    pub fn percent(value: T) -> Self {
+        Rate(value / cast(100.0))
+    }
+
+    /// `Rate::bps(25.0)` is 0.0025.
+    pub fn bps(value: T) -> Self {
+        Rate(value / cast(10_000.0))
+    }
+}
+
+impl<T: Float> Vol<T> {
+    /// `Vol::percent(25.0)` is 0.25.
+    pub fn percent(value: T) -> Self {
+        Vol(value / cast(100.0))
+    }
+}
@@ -0,0 +1,44 @@
+//! Parameter checks run before any model maths touches the inputs.
+
+use crate::error::{Domain, OptOpsError, Result};
+use crate::float::{to_f64, Float};
+
+/// Returns `value` unchanged if it lies in `domain`.
+pub(crate) fn check<T: Float>(name: &'static str, value: T, domain: Domain) -> Result<T> {
+    let value_f64 = to_f64(value);
+    if domain.contains(value_f64) {
+        Ok(value)
+    } else {
+        Err(OptOpsError::InvalidParameter {
+            name,
+            value: value_f64,
+            domain,
+        })
+    }
+}
+
+pub(crate) fn positive<T: Float>(name: &'static str, value: T) -> Result<T> {
+    check(name, value, Domain::Positive)
+}
+
+pub(crate) fn finite<T: Float>(name: &'static str, value: T) -> Result<T> {
+    check(name, value, Domain::Finite)
+}
+
+/// Smallest step count for which the risk-neutral up-probability of the tree
+/// stays inside [0, 1], i.e. `|carry| * sqrt(dt) <= vol` with the cost of
+/// carry `rate - dividend_yield`.
+pub(crate) fn min_tree_steps<T: Float>(carry: T, vol: T, expiry: T) -> usize {
+    let (carry, vol, expiry) = (to_f64(carry), to_f64(vol), to_f64(expiry));
+    (expiry * carry * carry / (vol * vol)).ceil().max(1.0) as usize
+}
+
+pub(crate) fn tree_steps<T: Float>(num_steps: usize, carry: T, vol: T, expiry: T) -> Result<usize> {
+    let min_steps = min_tree_steps(carry, vol, expiry);
+    check(
+        "num_steps",
+        num_steps as f64,
+        Domain::AtLeast(min_steps as f64),
+    )?;
+    Ok(num_steps)
+}